@@ -1,7 +1,11 @@
-use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use criterion::{
+    black_box, criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput,
+};
 use rand::{distributions::Standard, Rng, SeedableRng};
-use smartstring::{Compact, LazyCompact, SmartString, SmartStringMode};
-use std::collections::BTreeSet;
+use rustc_hash::FxBuildHasher;
+use smartstring::{Compact, HashedSmartString, LazyCompact, SmartString, SmartStringMode};
+use std::collections::{hash_map::RandomState, BTreeSet, HashSet};
+use std::hash::BuildHasher;
 
 const SIZES: &[usize] = &[4096, 16384, 32768, 65536, 131072];
 // const SIZES: &[usize] = &[4096, 65536];
@@ -51,7 +55,7 @@ fn make_string_input(count: usize, key_len: usize) -> (Vec<String>, BTreeSet<Str
 }
 
 fn make_smart_input<Mode>(
-    indices: &Vec<String>,
+    indices: &[String],
     set: &BTreeSet<String>,
 ) -> (Vec<SmartString<Mode>>, BTreeSet<SmartString<Mode>>)
 where
@@ -100,6 +104,204 @@ fn lookup_random(key_size: usize, c: &mut Criterion) {
     group.finish();
 }
 
+fn hash_lookup<S: BuildHasher + Default>(name: &str, key_size: usize, c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!(
+        "HashSet<{}> repeated lookup/key_len={}",
+        name, key_size
+    ));
+    for size in SIZES {
+        group.throughput(Throughput::Elements(*size as u64));
+        let string_indices = make_indices(*size, key_size);
+        let smart_indices: Vec<SmartString<LazyCompact>> =
+            string_indices.iter().map(From::from).collect();
+        let precompute = S::default();
+        let hashed_indices: Vec<_> = smart_indices
+            .iter()
+            .cloned()
+            .map(|key| HashedSmartString::new(key, &precompute))
+            .collect();
+        let string_set: HashSet<_, S> = string_indices.iter().cloned().collect();
+        let smart_set: HashSet<_, S> = smart_indices.iter().cloned().collect();
+        let hashed_set: HashSet<_, S> = hashed_indices.iter().cloned().collect();
+
+        group.bench_function(BenchmarkId::new("String", size), |b| {
+            b.iter(|| {
+                for k in &string_indices {
+                    black_box(string_set.contains(k));
+                }
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("SmartString<LazyCompact>", size), |b| {
+            b.iter(|| {
+                for k in &smart_indices {
+                    black_box(smart_set.contains(k));
+                }
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("HashedSmartString", size), |b| {
+            b.iter(|| {
+                for k in &hashed_indices {
+                    black_box(hashed_set.contains(k));
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn hash_lookup_16b(c: &mut Criterion) {
+    hash_lookup::<RandomState>("SipHash", 16, c);
+    hash_lookup::<FxBuildHasher>("FxHash", 16, c);
+}
+
+fn hash_lookup_256b(c: &mut Criterion) {
+    hash_lookup::<RandomState>("SipHash", 256, c);
+    hash_lookup::<FxBuildHasher>("FxHash", 256, c);
+}
+
+fn join(field_len: usize, c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("join/field_len={}", field_len));
+    for size in &[4usize, 16, 256] {
+        let fields: Vec<SmartString<LazyCompact>> = make_indices(*size, field_len)
+            .into_iter()
+            .map(From::from)
+            .collect();
+        group.throughput(Throughput::Elements(*size as u64));
+
+        group.bench_function(BenchmarkId::new("[SmartString]::join", size), |b| {
+            b.iter(|| black_box(fields.join(",")))
+        });
+
+        group.bench_function(BenchmarkId::new("SmartString::join", size), |b| {
+            b.iter(|| black_box(SmartString::<LazyCompact>::join(",", &fields)))
+        });
+    }
+    group.finish();
+}
+
+fn join_4b(c: &mut Criterion) {
+    join(4, c)
+}
+
+fn join_16b(c: &mut Criterion) {
+    join(16, c)
+}
+
+fn push_ascii(c: &mut Criterion) {
+    let mut group = c.benchmark_group("push_ascii");
+    for size in &[16usize, 256] {
+        let input: Vec<u8> = (b'a'..=b'z').cycle().take(*size).collect();
+        group.throughput(Throughput::Bytes(*size as u64));
+
+        group.bench_function(BenchmarkId::new("push", size), |b| {
+            b.iter(|| {
+                let mut out = SmartString::<LazyCompact>::new();
+                for &byte in &input {
+                    out.push(byte as char);
+                }
+                black_box(out)
+            })
+        });
+
+        group.bench_function(BenchmarkId::new("push_ascii", size), |b| {
+            b.iter(|| {
+                let mut out = SmartString::<LazyCompact>::new();
+                for &byte in &input {
+                    out.push_ascii(byte);
+                }
+                black_box(out)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn escape_json(c: &mut Criterion) {
+    let mut group = c.benchmark_group("escape_json");
+    let inputs = [
+        (
+            "clean",
+            "a perfectly ordinary log message with nothing to escape".repeat(4),
+        ),
+        (
+            "dirty",
+            "a \"quoted\"\tmessage\r\nwith \\ escapes ".repeat(4),
+        ),
+    ];
+    for (name, input) in inputs.iter() {
+        let input = SmartString::<LazyCompact>::from(input.as_str());
+        group.throughput(Throughput::Bytes(input.len() as u64));
+
+        group.bench_function(BenchmarkId::new("serde_json::to_string", name), |b| {
+            b.iter(|| black_box(serde_json::to_string(input.as_str()).unwrap()))
+        });
+
+        group.bench_function(BenchmarkId::new("escape_json_into", name), |b| {
+            b.iter(|| {
+                let mut out = SmartString::<LazyCompact>::new();
+                out.push('"');
+                input.escape_json_into(&mut out);
+                out.push('"');
+                black_box(out)
+            })
+        });
+    }
+    group.finish();
+}
+
+fn sort(key_size: usize, c: &mut Criterion) {
+    let mut group = c.benchmark_group(format!("sort/key_size={}", key_size));
+    for size in &[1024usize, 65536] {
+        let keys = make_indices(*size, key_size);
+        let smart: Vec<SmartString<Compact>> = keys.iter().map(|key| key.as_str().into()).collect();
+        group.throughput(Throughput::Elements(*size as u64));
+
+        group.bench_function(BenchmarkId::new("String", size), |b| {
+            b.iter_batched(
+                || keys.clone(),
+                |mut keys| {
+                    keys.sort_unstable();
+                    keys
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        group.bench_function(BenchmarkId::new("SmartString", size), |b| {
+            b.iter_batched(
+                || smart.clone(),
+                |mut keys| {
+                    keys.sort_unstable();
+                    keys
+                },
+                BatchSize::LargeInput,
+            )
+        });
+
+        group.bench_function(BenchmarkId::new("SmartString::cmp_bytes", size), |b| {
+            b.iter_batched(
+                || smart.clone(),
+                |mut keys| {
+                    keys.sort_unstable_by(|left, right| left.cmp_bytes(right));
+                    keys
+                },
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+fn sort_16b(c: &mut Criterion) {
+    sort(16, c)
+}
+
+fn sort_256b(c: &mut Criterion) {
+    sort(256, c)
+}
+
 fn lookup_random_16b(c: &mut Criterion) {
     lookup_random(16, c)
 }
@@ -116,6 +318,14 @@ criterion_group!(
     smartstring,
     lookup_random_16b,
     lookup_random_256b,
-    lookup_random_4096b
+    lookup_random_4096b,
+    hash_lookup_16b,
+    hash_lookup_256b,
+    join_4b,
+    join_16b,
+    push_ascii,
+    escape_json,
+    sort_16b,
+    sort_256b
 );
 criterion_main!(smartstring);
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use smartstring::{test::test_eq, Compact};
+
+type Input = (String, String);
+
+fuzz_target!(|input: Input| {
+    let (left, right) = input;
+    test_eq::<Compact>(left, right);
+});
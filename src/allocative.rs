@@ -0,0 +1,47 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{casts::StringCast, SmartString, SmartStringMode};
+use allocative::{Allocative, Key, Visitor};
+use core::mem::size_of;
+
+impl<Mode: SmartStringMode> Allocative for SmartString<Mode> {
+    /// Report the string's heap buffer, if it has one.
+    ///
+    /// An inline string is reported as only its own size, as it has no heap
+    /// allocation. A boxed string reports its whole capacity, split into the
+    /// bytes in use and the unused capacity, like a [`String`].
+    fn visit<'a, 'b: 'a>(&self, visitor: &'a mut Visitor<'b>) {
+        let mut visitor = visitor.enter_self_sized::<Self>();
+        if let StringCast::Boxed(string) = self.cast() {
+            let mut visitor = visitor.enter_unique(Key::new("ptr"), size_of::<*const u8>());
+            visitor.visit_simple(Key::new("str"), string.len());
+            visitor.visit_simple(
+                Key::new("unused_capacity"),
+                string.capacity() - string.len(),
+            );
+            visitor.exit();
+        }
+        visitor.exit();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Compact, LazyCompact, SmartString};
+    use allocative::size_of_unique_allocated_data;
+
+    #[test]
+    fn heap_size() {
+        let inline = SmartString::<Compact>::from("short");
+        assert_eq!(0, size_of_unique_allocated_data(&inline));
+
+        let mut boxed = SmartString::<LazyCompact>::from("a string long enough to need the heap");
+        boxed.reserve(100);
+        assert_eq!(boxed.capacity(), size_of_unique_allocated_data(&boxed));
+        boxed.truncate(3);
+        assert!(!boxed.is_inline());
+        assert_eq!(boxed.capacity(), size_of_unique_allocated_data(&boxed));
+    }
+}
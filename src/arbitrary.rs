@@ -2,16 +2,56 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{SmartString, SmartStringMode};
+use crate::{SmartString, SmartStringMode, MAX_INLINE};
 use alloc::string::String;
 use arbitrary::{Arbitrary, Result, Unstructured};
 
+/// Generate a string of about `target` bytes.
+///
+/// If `exact` is set, a `char` which would overshoot the target is replaced by
+/// ASCII padding, so the string comes out at exactly `target` bytes. Otherwise,
+/// the last `char` is allowed to straddle the target.
+fn string_near(u: &mut Unstructured<'_>, target: usize, exact: bool) -> Result<String> {
+    let mut string = String::with_capacity(target + 3);
+    while string.len() < target {
+        let ch = char::arbitrary(u)?;
+        if exact && string.len() + ch.len_utf8() > target {
+            string.push('.');
+        } else {
+            string.push(ch);
+        }
+    }
+    Ok(string)
+}
+
 impl<'a, Mode: SmartStringMode> Arbitrary<'a> for SmartString<Mode>
 where
     Mode: 'static,
 {
+    /// Generate an arbitrary string.
+    ///
+    /// Most of the time, this is any string [`String`]'s implementation would
+    /// generate. The rest of the time, it's a string which is one byte shorter
+    /// than, exactly as long as, or one byte longer than [`MAX_INLINE`], where
+    /// the switch between inline and boxed strings happens, possibly with a
+    /// multi-byte `char` straddling the boundary, and possibly boxed even if it
+    /// could be inline.
     fn arbitrary(u: &mut Unstructured<'_>) -> Result<Self> {
-        String::arbitrary(u).map(Self::from)
+        let target = match u.int_in_range(0u8..=5)? {
+            0..=2 => return String::arbitrary(u).map(Self::from),
+            choice => MAX_INLINE + choice as usize - 4,
+        };
+        let exact = bool::arbitrary(u)?;
+        let string = string_near(u, target, exact)?;
+        if bool::arbitrary(u)? {
+            // Build the string in a heap buffer, which it keeps even if it's
+            // short enough to inline.
+            let mut out = Self::with_capacity(MAX_INLINE + 1);
+            out.push_str(&string);
+            Ok(out)
+        } else {
+            Ok(Self::from(string))
+        }
     }
 
     fn arbitrary_take_rest(u: Unstructured<'_>) -> Result<Self> {
@@ -19,6 +59,6 @@ where
     }
 
     fn size_hint(depth: usize) -> (usize, Option<usize>) {
-        String::size_hint(depth)
+        arbitrary::size_hint::and(<u8 as Arbitrary<'_>>::size_hint(depth), (0, None))
     }
 }
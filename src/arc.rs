@@ -0,0 +1,515 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{
+    inline::{InlineString, Repr},
+    marker_byte::Discriminant,
+    LazyCompact, SmartString, SmartStringMode, MAX_INLINE,
+};
+use alloc::{string::String, sync::Arc};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{Debug, Display, Error, Formatter},
+    hash::{Hash, Hasher},
+    mem::{ManuallyDrop, MaybeUninit},
+    ops::{Deref, DerefMut, RangeBounds},
+    ptr::NonNull,
+};
+
+/// The heap representation of a [`SmartArcString`], a string shared between
+/// all of its clones.
+pub(crate) type Shared = Arc<SmartString<LazyCompact>>;
+
+/// The shared representation of a [`SmartArcString`]: the pointer from a
+/// [`Shared`] string, laid out so it overlaps an inline string's marker byte.
+#[cfg(target_endian = "little")]
+#[repr(C)]
+struct SharedStr {
+    ptr: NonNull<SmartString<LazyCompact>>,
+    _unused: [MaybeUninit<usize>; 2],
+}
+
+#[cfg(target_endian = "big")]
+#[repr(C)]
+struct SharedStr {
+    _unused: [MaybeUninit<usize>; 2],
+    ptr: NonNull<SmartString<LazyCompact>>,
+}
+
+static_assertions::assert_eq_size!(SharedStr, InlineString);
+
+impl SharedStr {
+    fn new(string: Shared) -> Self {
+        let ptr = Arc::into_raw(string) as *mut SmartString<LazyCompact>;
+        // The value follows the reference counts inside the `Arc`'s
+        // allocation, so it's always aligned to at least a `usize`, leaving the
+        // least significant bit free to tell it apart from an inline marker.
+        debug_assert!(ptr.align_offset(2) == 0);
+        Self {
+            #[allow(unsafe_code)]
+            ptr: unsafe { NonNull::new_unchecked(ptr) },
+            _unused: [MaybeUninit::uninit(); 2],
+        }
+    }
+
+    /// Borrow the `Arc` without touching its reference count.
+    fn arc(&self) -> ManuallyDrop<Shared> {
+        #[allow(unsafe_code)]
+        ManuallyDrop::new(unsafe { Arc::from_raw(self.ptr.as_ptr()) })
+    }
+
+    fn get(&self) -> &SmartString<LazyCompact> {
+        // The `Arc` is kept alive for as long as we are.
+        #[allow(unsafe_code)]
+        unsafe {
+            self.ptr.as_ref()
+        }
+    }
+}
+
+/// A string which is stored inline when it fits, and in a reference counted
+/// [`Arc`] when it doesn't.
+///
+/// Cloning a `SmartArcString` never allocates: an inline string is copied, as
+/// with [`SmartString`], and a long string just has its reference count
+/// incremented. This suits strings which are shared a lot more often than
+/// they're modified. It's the same size as a [`String`], and an
+/// `Option<SmartArcString>` is no larger.
+///
+/// A long string is a [`SmartString`] inside the `Arc`, and modifying it is
+/// copy-on-write, like [`Arc::make_mut()`]: [`make_mut()`][SmartArcString::make_mut]
+/// gives access to the whole [`SmartString`] API, and copies the string first
+/// only if its buffer is shared with another clone or a [`SmartSlice`]. A
+/// string with no other owners is modified in place, so building one up a
+/// piece at a time costs no more than it would with a [`SmartString`]. The
+/// most common modifications, like [`push_str()`][SmartArcString::push_str]
+/// and [`truncate()`][SmartArcString::truncate], are also available directly.
+///
+/// This is a separate type rather than a [`SmartStringMode`], because every mode
+/// shares the same heap representation, which a shared buffer can't use.
+///
+/// ```rust
+/// # use smartstring::SmartArcString;
+/// let string = SmartArcString::new("a string long enough to need the heap");
+/// let shared = string.clone();
+/// assert!(SmartArcString::ptr_eq(&string, &shared));
+///
+/// let mut modified = shared.clone();
+/// modified.truncate(8);
+/// assert_eq!("a string", modified);
+/// assert!(modified.is_inline());
+/// assert_eq!(string, shared);
+/// ```
+///
+/// [`SmartSlice`]: crate::SmartSlice
+pub struct SmartArcString {
+    data: Repr,
+}
+
+impl SmartArcString {
+    /// Construct a string holding a copy of `string`.
+    pub fn new(string: &str) -> Self {
+        if string.len() > MAX_INLINE {
+            Self::from_shared(Arc::new(string.into()))
+        } else {
+            Self::from_inline(string.into())
+        }
+    }
+
+    fn from_inline(inline: InlineString) -> Self {
+        Self {
+            data: Repr::new(inline),
+        }
+    }
+
+    fn from_shared(string: Shared) -> Self {
+        if string.len() <= MAX_INLINE {
+            return Self::from_inline(string.as_str().into());
+        }
+        let mut out = Self::from_inline(InlineString::new());
+        #[allow(unsafe_code)]
+        unsafe {
+            out.data
+                .as_mut_ptr()
+                .cast::<SharedStr>()
+                .write(SharedStr::new(string))
+        };
+        out
+    }
+
+    fn discriminant(&self) -> Discriminant {
+        #[allow(unsafe_code)]
+        let marker = unsafe { (*self.data.as_ptr()).marker };
+        marker.discriminant()
+    }
+
+    fn shared(&self) -> Option<&SharedStr> {
+        match self.discriminant() {
+            Discriminant::Inline => None,
+            #[allow(unsafe_code)]
+            Discriminant::Boxed => Some(unsafe { &*self.data.as_ptr().cast() }),
+        }
+    }
+
+    /// Test whether the string is stored inline.
+    pub fn is_inline(&self) -> bool {
+        self.discriminant() == Discriminant::Inline
+    }
+
+    /// Get the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        match self.shared() {
+            None => {
+                #[allow(unsafe_code)]
+                let inline = unsafe { &*self.data.as_ptr() };
+                inline.deref()
+            }
+            Some(shared) => shared.get(),
+        }
+    }
+
+    /// Test whether two strings share the same heap buffer.
+    ///
+    /// Inline strings are never considered to share anything.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        match (this.shared(), other.shared()) {
+            (Some(this), Some(other)) => this.ptr == other.ptr,
+            _ => false,
+        }
+    }
+
+    /// Get mutable access to the string, as a [`SmartString`].
+    ///
+    /// If the string's heap buffer is shared, it's copied first, so the other
+    /// owners don't see the change. Otherwise, the string is modified in
+    /// place. An inline string is modified as an inline [`SmartString`], and
+    /// only moved to the heap if it grows too long. When the guard is
+    /// dropped, a heap allocated string which has become short enough is moved
+    /// back inline.
+    ///
+    /// ```rust
+    /// # use smartstring::SmartArcString;
+    /// let original = SmartArcString::new("a string long enough to need the heap");
+    /// let mut string = original.clone();
+    /// string.make_mut().replace_range(..1, "the");
+    /// string.make_mut().retain(|ch| ch != ' ');
+    /// assert_eq!("thestringlongenoughtoneedtheheap", string);
+    /// assert_eq!("a string long enough to need the heap", original);
+    /// ```
+    pub fn make_mut(&mut self) -> ArcEditGuard<'_> {
+        let inline = match self.shared() {
+            None => Some(self.as_str().into()),
+            Some(shared) => {
+                if Arc::get_mut(&mut shared.arc()).is_none() {
+                    let copy = shared.get().clone();
+                    *self = Self::from_shared(Arc::new(copy));
+                }
+                None
+            }
+        };
+        ArcEditGuard {
+            owner: self,
+            inline,
+        }
+    }
+
+    /// Modify the string through [`make_mut()`][SmartArcString::make_mut].
+    pub fn modify<R>(&mut self, f: impl FnOnce(&mut SmartString<LazyCompact>) -> R) -> R {
+        f(&mut self.make_mut())
+    }
+
+    /// Append a string slice to the end of the string.
+    ///
+    /// See [`SmartString::push_str()`].
+    pub fn push_str(&mut self, string: &str) {
+        self.make_mut().push_str(string)
+    }
+
+    /// Append a `char` to the end of the string.
+    ///
+    /// See [`SmartString::push()`].
+    pub fn push(&mut self, ch: char) {
+        self.make_mut().push(ch)
+    }
+
+    /// Insert a `char` at a byte index.
+    ///
+    /// See [`SmartString::insert()`].
+    pub fn insert(&mut self, index: usize, ch: char) {
+        self.make_mut().insert(index, ch)
+    }
+
+    /// Insert a string slice at a byte index.
+    ///
+    /// See [`SmartString::insert_str()`].
+    pub fn insert_str(&mut self, index: usize, string: &str) {
+        self.make_mut().insert_str(index, string)
+    }
+
+    /// Remove the `char` at a byte index and return it.
+    ///
+    /// See [`SmartString::remove()`].
+    pub fn remove(&mut self, index: usize) -> char {
+        self.make_mut().remove(index)
+    }
+
+    /// Remove the last `char` from the string and return it.
+    ///
+    /// See [`SmartString::pop()`].
+    pub fn pop(&mut self) -> Option<char> {
+        self.make_mut().pop()
+    }
+
+    /// Retain only the `char`s for which `f` returns `true`.
+    ///
+    /// See [`SmartString::retain()`].
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(char) -> bool,
+    {
+        self.make_mut().retain(f)
+    }
+
+    /// Replace a range of the string with a string slice.
+    ///
+    /// See [`SmartString::replace_range()`].
+    pub fn replace_range<R>(&mut self, range: R, replace_with: &str)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.make_mut().replace_range(range, replace_with)
+    }
+
+    /// Truncate the string to `new_len` bytes.
+    ///
+    /// See [`SmartString::truncate()`].
+    pub fn truncate(&mut self, new_len: usize) {
+        self.make_mut().truncate(new_len)
+    }
+
+    /// Truncate the string to zero length, releasing this string's share of the
+    /// heap buffer.
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Get a new reference to the shared buffer, if the string isn't inline.
+    pub(crate) fn to_shared(&self) -> Option<Shared> {
+        self.shared().map(|shared| Arc::clone(&shared.arc()))
+    }
+
+    /// Copy the string into a [`SmartString`].
+    pub fn to_smart_string<Mode: SmartStringMode>(&self) -> SmartString<Mode> {
+        self.as_str().into()
+    }
+}
+
+impl Drop for SmartArcString {
+    fn drop(&mut self) {
+        if let Some(shared) = self.shared() {
+            drop(ManuallyDrop::into_inner(shared.arc()));
+        }
+    }
+}
+
+impl Clone for SmartArcString {
+    fn clone(&self) -> Self {
+        match self.to_shared() {
+            None => Self {
+                #[allow(unsafe_code)]
+                data: Repr::new(unsafe { *self.data.as_ptr() }),
+            },
+            Some(shared) => Self::from_shared(shared),
+        }
+    }
+}
+
+/// A guard giving mutable access to a [`SmartArcString`] as a [`SmartString`].
+///
+/// This is returned by [`SmartArcString::make_mut()`]. When it's dropped, the
+/// string is stored back, inline if it's short enough.
+pub struct ArcEditGuard<'a> {
+    owner: &'a mut SmartArcString,
+    /// The string being edited, if the owner was inline. Otherwise, the owner's
+    /// `Arc` has no other owners, and it's edited in place.
+    inline: Option<SmartString<LazyCompact>>,
+}
+
+impl<'a> Deref for ArcEditGuard<'a> {
+    type Target = SmartString<LazyCompact>;
+
+    fn deref(&self) -> &Self::Target {
+        match (&self.inline, self.owner.shared()) {
+            (Some(string), _) => string,
+            (None, Some(shared)) => shared.get(),
+            (None, None) => unreachable!("an edited SmartArcString lost its buffer"),
+        }
+    }
+}
+
+impl<'a> DerefMut for ArcEditGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match (&mut self.inline, self.owner.shared()) {
+            (Some(string), _) => string,
+            // `make_mut()` made sure nothing else refers to the `Arc`, and we
+            // hold the only reference to its owner.
+            #[allow(unsafe_code)]
+            (None, Some(shared)) => unsafe { &mut *shared.ptr.as_ptr() },
+            (None, None) => unreachable!("an edited SmartArcString lost its buffer"),
+        }
+    }
+}
+
+impl<'a> Drop for ArcEditGuard<'a> {
+    fn drop(&mut self) {
+        if let Some(string) = self.inline.take() {
+            *self.owner = string.into();
+        } else if self.owner.len() <= MAX_INLINE {
+            let inline = self.owner.as_str().into();
+            *self.owner = SmartArcString::from_inline(inline);
+        }
+    }
+}
+
+impl<'a> Debug for ArcEditGuard<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_tuple("ArcEditGuard").field(self.deref()).finish()
+    }
+}
+
+impl Default for SmartArcString {
+    fn default() -> Self {
+        Self::from_inline(InlineString::new())
+    }
+}
+
+impl Deref for SmartArcString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SmartArcString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmartArcString {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for SmartArcString {
+    fn from(string: &str) -> Self {
+        Self::new(string)
+    }
+}
+
+impl From<String> for SmartArcString {
+    fn from(string: String) -> Self {
+        SmartString::<LazyCompact>::from(string).into()
+    }
+}
+
+impl From<Arc<str>> for SmartArcString {
+    /// Copy an [`Arc<str>`][Arc] into a new string.
+    fn from(string: Arc<str>) -> Self {
+        Self::new(&string)
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartString<Mode>> for SmartArcString {
+    /// Move a [`SmartString`] into a new string, keeping its heap buffer if
+    /// it's too long to inline.
+    fn from(string: SmartString<Mode>) -> Self {
+        if string.len() <= MAX_INLINE {
+            Self::from_inline(string.as_str().into())
+        } else {
+            Self::from_shared(Arc::new(string.convert()))
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartArcString> for SmartString<Mode> {
+    /// Move the string out of a [`SmartArcString`], keeping its heap buffer if
+    /// nothing else shares it, and copying it otherwise.
+    fn from(string: SmartArcString) -> Self {
+        let string = ManuallyDrop::new(string);
+        match string.shared() {
+            None => string.as_str().into(),
+            // We've taken over the string's reference to the `Arc`.
+            Some(shared) => match Arc::try_unwrap(ManuallyDrop::into_inner(shared.arc())) {
+                Ok(string) => string.convert(),
+                Err(shared) => shared.as_str().into(),
+            },
+        }
+    }
+}
+
+impl PartialEq for SmartArcString {
+    fn eq(&self, other: &Self) -> bool {
+        Self::ptr_eq(self, other) || self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmartArcString {}
+
+impl PartialEq<str> for SmartArcString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmartArcString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<SmartArcString> for &str {
+    fn eq(&self, other: &SmartArcString) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<SmartArcString> for str {
+    fn eq(&self, other: &SmartArcString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialOrd for SmartArcString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmartArcString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for SmartArcString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl Debug for SmartArcString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for SmartArcString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Display::fmt(self.as_str(), f)
+    }
+}
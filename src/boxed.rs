@@ -2,14 +2,14 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use alloc::{alloc::Layout, string::String};
+use alloc::{alloc::Layout, collections::TryReserveError, string::String, vec::Vec};
 use core::{
-    mem::align_of,
+    mem::{align_of, ManuallyDrop},
     ops::{Deref, DerefMut},
     ptr::NonNull,
 };
 
-use crate::{ops::GenericString, MAX_INLINE};
+use crate::ops::GenericString;
 
 #[cfg(target_endian = "little")]
 #[repr(C)]
@@ -32,16 +32,19 @@ pub(crate) struct BoxedString {
 ///
 /// Returns `true` if aligned to an odd address, `false` if even. The sense of
 /// the boolean is "does this look like an InlineString? true/false"
+#[inline]
 fn check_alignment(ptr: *const u8) -> bool {
     ptr.align_offset(2) > 0
 }
 
 impl GenericString for BoxedString {
+    #[inline]
     fn set_size(&mut self, size: usize) {
         self.len = size;
-        debug_assert!(self.len <= self.cap);
+        debug_assert!(self.len <= self.capacity());
     }
 
+    #[inline]
     fn as_mut_capacity_slice(&mut self) -> &mut [u8] {
         #[allow(unsafe_code)]
         unsafe {
@@ -51,8 +54,36 @@ impl GenericString for BoxedString {
 }
 
 impl BoxedString {
-    const MINIMAL_CAPACITY: usize = MAX_INLINE * 2;
+    /// The top bit of `cap` marks a pinned string, which mustn't be demoted.
+    /// An allocation can't be larger than `isize::MAX` bytes, so it's never
+    /// part of the capacity.
+    const PINNED: usize = !(usize::MAX >> 1);
 
+    /// On 64-bit targets, the 17 bits of `cap` below the pinned flag hold a
+    /// digest of the contents for modes with
+    /// [`DIGEST`][crate::SmartStringMode::DIGEST] set: a flag marking the
+    /// digest as valid, and the 16 bit digest itself. That leaves 46 bits for
+    /// the capacity, which is more memory than any current 64-bit CPU can
+    /// address.
+    #[cfg(target_pointer_width = "64")]
+    const DIGEST_VALID: usize = 1 << 62;
+    #[cfg(target_pointer_width = "64")]
+    const DIGEST_SHIFT: u32 = 46;
+    #[cfg(target_pointer_width = "64")]
+    const FLAGS: usize = Self::PINNED | Self::DIGEST_VALID | (0xffff << Self::DIGEST_SHIFT);
+    #[cfg(not(target_pointer_width = "64"))]
+    const FLAGS: usize = Self::PINNED;
+
+    /// The largest capacity we can allocate, which leaves the flag bits of
+    /// `cap` clear. It's kept even, so `try_alloc()` rounding up can't exceed
+    /// it.
+    ///
+    /// A boxed string doesn't know its mode, and strings change mode without
+    /// touching their buffers, so the digest bits are reserved in every mode.
+    /// On 64-bit targets, this caps every string at 2^46 bytes (64 TiB).
+    const MAX_CAPACITY: usize = !Self::FLAGS & !1;
+
+    #[inline]
     pub(crate) fn check_alignment(this: &Self) -> bool {
         check_alignment(this.ptr.as_ptr())
     }
@@ -64,7 +95,7 @@ impl BoxedString {
             .and_then(|layout| layout.align_to(align_of::<u16>()))
             .unwrap();
         assert!(
-            layout.size() <= isize::MAX as usize,
+            layout.size() <= isize::MAX as usize && cap & Self::FLAGS == 0,
             "allocation too large!"
         );
         layout
@@ -81,9 +112,29 @@ impl BoxedString {
         ptr
     }
 
+    /// Allocate a buffer of at least `cap` bytes, returning an error rather than
+    /// aborting if the allocation fails.
+    ///
+    /// We allocate through a `Vec<u16>` to get our hands on a `TryReserveError`.
+    /// Its layout is identical to `layout_for()` as long as the capacity is
+    /// even, so we round it up and return it along with the pointer.
+    fn try_alloc(cap: usize) -> Result<(NonNull<u8>, usize), TryReserveError> {
+        if cap > Self::MAX_CAPACITY {
+            return Err(capacity_overflow());
+        }
+        let mut buffer: Vec<u16> = Vec::new();
+        buffer.try_reserve_exact(cap / 2 + cap % 2)?;
+        let mut buffer = ManuallyDrop::new(buffer);
+        let cap = buffer.capacity() * 2;
+        #[allow(unsafe_code)]
+        let ptr = unsafe { NonNull::new_unchecked(buffer.as_mut_ptr().cast()) };
+        debug_assert!(Self::layout_for(cap) == Layout::array::<u16>(cap / 2).unwrap());
+        Ok((ptr, cap))
+    }
+
     fn realloc(&mut self, cap: usize) {
         let layout = Self::layout_for(cap);
-        let old_layout = Self::layout_for(self.cap);
+        let old_layout = Self::layout_for(self.capacity());
         let old_ptr = self.ptr.as_ptr();
         #[allow(unsafe_code)]
         let ptr = unsafe { alloc::alloc::realloc(old_ptr, old_layout, layout.size()) };
@@ -91,20 +142,76 @@ impl BoxedString {
             Some(ptr) => ptr,
             None => alloc::alloc::handle_alloc_error(layout),
         };
-        self.cap = cap;
+        self.set_capacity(cap);
         debug_assert!(self.ptr.as_ptr().align_offset(2) == 0);
     }
 
+    pub(crate) fn ensure_capacity_exact(&mut self, target_cap: usize) {
+        if target_cap > self.capacity() {
+            self.realloc(target_cap)
+        }
+    }
+
     pub(crate) fn ensure_capacity(&mut self, target_cap: usize) {
-        let mut cap = self.cap;
+        let mut cap = self.capacity();
         while cap < target_cap {
             cap *= 2;
         }
-        self.realloc(cap)
+        if cap != self.capacity() {
+            self.realloc(cap)
+        }
     }
 
+    pub(crate) fn try_ensure_capacity(&mut self, target_cap: usize) -> Result<(), TryReserveError> {
+        if target_cap > Self::MAX_CAPACITY {
+            return Err(capacity_overflow());
+        }
+        let mut cap = self.capacity();
+        while cap < target_cap {
+            cap = cap.checked_mul(2).unwrap_or(target_cap);
+        }
+        // Doubling may overshoot the largest capacity even when the target
+        // doesn't.
+        let cap = cap.min(Self::MAX_CAPACITY);
+        if cap <= self.capacity() {
+            return Ok(());
+        }
+        let layout = Self::layout_for(cap);
+        let old_layout = Self::layout_for(self.capacity());
+        #[allow(unsafe_code)]
+        let ptr = unsafe { alloc::alloc::realloc(self.ptr.as_ptr(), old_layout, layout.size()) };
+        if let Some(ptr) = NonNull::new(ptr) {
+            self.ptr = ptr;
+            self.set_capacity(cap);
+            return Ok(());
+        }
+        // The realloc failed; allocating through `try_alloc` gets us the
+        // appropriate error, or a new buffer if memory has become available in
+        // the meantime.
+        let (ptr, cap) = Self::try_alloc(cap)?;
+        #[allow(unsafe_code)]
+        unsafe {
+            core::ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len);
+            alloc::alloc::dealloc(self.ptr.as_ptr(), Self::layout_for(self.capacity()));
+        }
+        self.ptr = ptr;
+        self.set_capacity(cap);
+        Ok(())
+    }
+
+    pub(crate) fn try_from_str(cap: usize, src: &str) -> Result<Self, TryReserveError> {
+        let (ptr, cap) = Self::try_alloc(cap)?;
+        let mut out = Self { cap, len: 0, ptr };
+        out.len = src.len();
+        out.as_mut_capacity_slice()[..src.len()].copy_from_slice(src.as_bytes());
+        Ok(out)
+    }
+
+    /// Allocate an empty string with a capacity of exactly `cap`, which must
+    /// not be zero. Deciding how much to allocate is up to the caller, usually
+    /// through `SmartStringMode::promotion_capacity()`.
     pub(crate) fn new(cap: usize) -> Self {
-        let cap = cap.max(Self::MINIMAL_CAPACITY);
+        debug_assert!(cap > 0);
         Self {
             cap,
             len: 0,
@@ -119,8 +226,80 @@ impl BoxedString {
         out
     }
 
+    #[inline]
     pub(crate) fn capacity(&self) -> usize {
-        self.cap
+        self.cap & !Self::FLAGS
+    }
+
+    /// Change the capacity, keeping the flags.
+    #[inline]
+    fn set_capacity(&mut self, cap: usize) {
+        self.cap = cap | (self.cap & Self::FLAGS);
+    }
+
+    /// Get the digest of the contents, if one has been stored since they were
+    /// last changed.
+    #[cfg(target_pointer_width = "64")]
+    #[inline]
+    pub(crate) fn digest(&self) -> Option<u16> {
+        if self.cap & Self::DIGEST_VALID != 0 {
+            Some((self.cap >> Self::DIGEST_SHIFT) as u16)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(target_pointer_width = "64"))]
+    #[inline]
+    pub(crate) fn digest(&self) -> Option<u16> {
+        None
+    }
+
+    /// Compute and store a digest of the contents. There's nowhere to store it
+    /// on targets other than 64-bit ones.
+    pub(crate) fn store_digest(&mut self) {
+        #[cfg(target_pointer_width = "64")]
+        {
+            let hash = crate::ops::fnv1a(self.as_bytes());
+            let digest = (hash ^ (hash >> 16) ^ (hash >> 32) ^ (hash >> 48)) as u16;
+            self.cap = self.capacity()
+                | (self.cap & Self::PINNED)
+                | Self::DIGEST_VALID
+                | ((digest as usize) << Self::DIGEST_SHIFT);
+        }
+    }
+
+    /// Forget the stored digest, because the contents may be about to change.
+    #[inline]
+    pub(crate) fn clear_digest(&mut self) {
+        #[cfg(target_pointer_width = "64")]
+        {
+            self.cap &= !Self::DIGEST_VALID;
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_pinned(&self) -> bool {
+        self.cap & Self::PINNED != 0
+    }
+
+    #[inline]
+    pub(crate) fn set_pinned(&mut self, pinned: bool) {
+        if pinned {
+            self.cap |= Self::PINNED;
+        } else {
+            self.cap &= !Self::PINNED;
+        }
+    }
+
+    #[cfg(feature = "debug-canary")]
+    pub(crate) fn check_canary(&self) {
+        assert!(
+            self.len <= self.capacity(),
+            "SmartString representation is corrupt: boxed string has length {} and capacity {}",
+            self.len,
+            self.capacity()
+        );
     }
 
     pub(crate) fn shrink_to_fit(&mut self) {
@@ -128,24 +307,39 @@ impl BoxedString {
     }
 }
 
+/// Make the error for a capacity too large to allocate. There's no way to build
+/// a [`TryReserveError`] directly on stable, so we ask a `Vec` for more than it
+/// could ever hold.
+fn capacity_overflow() -> TryReserveError {
+    Vec::<u8>::new().try_reserve(usize::MAX).unwrap_err()
+}
+
 impl Drop for BoxedString {
     fn drop(&mut self) {
         #[allow(unsafe_code)]
         unsafe {
-            alloc::alloc::dealloc(self.ptr.as_ptr(), Self::layout_for(self.cap))
+            alloc::alloc::dealloc(self.ptr.as_ptr(), Self::layout_for(self.capacity()))
         }
     }
 }
 
 impl Clone for BoxedString {
     fn clone(&self) -> Self {
-        Self::from_str(self.capacity(), self.deref())
+        #[cfg_attr(not(target_pointer_width = "64"), allow(unused_mut))]
+        let mut out = Self::from_str(self.capacity(), self.deref());
+        // The clone has the same contents, so it can have the same digest.
+        #[cfg(target_pointer_width = "64")]
+        {
+            out.cap = out.capacity() | (self.cap & Self::FLAGS & !Self::PINNED);
+        }
+        out
     }
 }
 
 impl Deref for BoxedString {
     type Target = str;
 
+    #[inline]
     fn deref(&self) -> &Self::Target {
         #[allow(unsafe_code)]
         unsafe {
@@ -155,6 +349,7 @@ impl Deref for BoxedString {
 }
 
 impl DerefMut for BoxedString {
+    #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
         #[allow(unsafe_code)]
         unsafe {
@@ -166,29 +361,32 @@ impl DerefMut for BoxedString {
     }
 }
 
-impl From<String> for BoxedString {
+impl BoxedString {
+    /// Take over the buffer of a [`String`] if we can, or else copy it into a
+    /// new buffer of `cap` bytes, which must be enough to hold it.
     #[allow(unsafe_code, unused_mut)]
-    fn from(mut s: String) -> Self {
+    pub(crate) fn from_string(mut s: String, cap: usize) -> Self {
+        debug_assert!(cap >= s.len());
         if s.is_empty() {
-            Self::new(s.capacity())
+            Self::new(cap)
         } else {
             #[cfg(has_allocator)]
             {
                 // TODO: Use String::into_raw_parts when stabilised, meanwhile let's get unsafe
                 let len = s.len();
-                let cap = s.capacity();
+                let old_cap = s.capacity();
                 #[allow(unsafe_code)]
                 let ptr = unsafe { NonNull::new_unchecked(s.as_mut_ptr()) };
-                let old_layout = Layout::array::<u8>(cap).unwrap();
+                let old_layout = Layout::array::<u8>(old_cap).unwrap();
 
                 use alloc::alloc::Allocator;
                 let allocator = alloc::alloc::Global;
                 if let Ok(aligned_ptr) =
-                    unsafe { allocator.grow(ptr, old_layout, Self::layout_for(cap)) }
+                    unsafe { allocator.grow(ptr, old_layout, Self::layout_for(old_cap)) }
                 {
                     core::mem::forget(s);
                     Self {
-                        cap,
+                        cap: old_cap,
                         len,
                         ptr: aligned_ptr.cast(),
                     }
@@ -197,7 +395,7 @@ impl From<String> for BoxedString {
                 }
             }
             #[cfg(not(has_allocator))]
-            Self::from_str(s.capacity(), &s)
+            Self::from_str(cap, &s)
         }
     }
 }
@@ -208,7 +406,7 @@ impl From<BoxedString> for String {
         #[cfg(has_allocator)]
         {
             let ptr = s.ptr;
-            let cap = s.cap;
+            let cap = s.capacity();
             let len = s.len;
             let new_layout = Layout::array::<u8>(cap).unwrap();
 
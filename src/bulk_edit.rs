@@ -0,0 +1,75 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{Deferred, SmartString, SmartStringMode};
+use core::{
+    fmt::{Debug, Error, Formatter},
+    ops::{Deref, DerefMut},
+};
+
+/// A guard which defers demoting a [`SmartString`] until the end of an edit.
+///
+/// This is returned by [`SmartString::begin_bulk_edit()`]. It dereferences to
+/// the string in the [`Deferred`] version of its mode, which never moves a
+/// heap allocated string back inline, so a string which shrinks and grows
+/// repeatedly around [`MAX_INLINE`][crate::MAX_INLINE] bytes isn't copied
+/// back and forth between inline and heap storage. When the guard is dropped,
+/// the string is demoted if its own mode would have demoted it.
+///
+/// ```rust
+/// # use smartstring::{SmartString, Compact, MAX_INLINE};
+/// let mut string: SmartString<Compact> = "x".repeat(MAX_INLINE + 1).into();
+/// {
+///     let mut edit = string.begin_bulk_edit();
+///     edit.pop();
+///     assert!(!edit.is_inline());
+///     edit.push('y');
+///     edit.pop();
+///     assert!(!edit.is_inline());
+/// }
+/// assert!(string.is_inline());
+/// ```
+pub struct BulkEditGuard<'a, Mode: SmartStringMode> {
+    string: &'a mut SmartString<Mode>,
+}
+
+impl<'a, Mode: SmartStringMode> BulkEditGuard<'a, Mode> {
+    pub(crate) fn new(string: &'a mut SmartString<Mode>) -> Self {
+        Self { string }
+    }
+}
+
+impl<'a, Mode: SmartStringMode> Deref for BulkEditGuard<'a, Mode> {
+    type Target = SmartString<Deferred<Mode>>;
+
+    fn deref(&self) -> &Self::Target {
+        // `SmartString` is `repr(transparent)` over its storage, which means
+        // the same thing in every mode.
+        #[allow(unsafe_code)]
+        unsafe {
+            &*(self.string as *const SmartString<Mode>).cast()
+        }
+    }
+}
+
+impl<'a, Mode: SmartStringMode> DerefMut for BulkEditGuard<'a, Mode> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        #[allow(unsafe_code)]
+        unsafe {
+            &mut *(self.string as *mut SmartString<Mode>).cast()
+        }
+    }
+}
+
+impl<'a, Mode: SmartStringMode> Drop for BulkEditGuard<'a, Mode> {
+    fn drop(&mut self) {
+        self.string.try_demote();
+    }
+}
+
+impl<'a, Mode: SmartStringMode> Debug for BulkEditGuard<'a, Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_tuple("BulkEditGuard").field(&self.string).finish()
+    }
+}
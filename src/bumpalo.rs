@@ -0,0 +1,35 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use bumpalo::Bump;
+
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Copy the string into a [`Bump`] arena, returning a view of the copy
+    /// which lives as long as the arena.
+    ///
+    /// The contents are copied once, straight from the inline or heap buffer
+    /// into the arena.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// # use bumpalo::Bump;
+    /// let arena = Bump::new();
+    /// let name = String::from("identifier");
+    /// let view: &str = name.as_bump_str(&arena);
+    /// drop(name);
+    /// assert_eq!("identifier", view);
+    /// ```
+    pub fn as_bump_str<'bump>(&self, bump: &'bump Bump) -> &'bump str {
+        bump.alloc_str(self)
+    }
+
+    /// Copy an arena allocated string into a [`SmartString`].
+    ///
+    /// This is the counterpart to [`as_bump_str()`][SmartString::as_bump_str],
+    /// and copies the string once, into inline storage if it fits.
+    pub fn from_bump_str(string: &str) -> Self {
+        Self::from(string)
+    }
+}
@@ -0,0 +1,116 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{Debug, Error, Formatter},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+/// A [`SmartString`] keyed by its bytes.
+///
+/// [`SmartString`] can't implement [`Borrow<[u8]>`][Borrow], because it hashes
+/// like a [`str`], and `str` and `[u8]` don't hash the same way. This wrapper
+/// hashes, compares and borrows as a `[u8]` instead, so it can be used as the
+/// key of byte oriented collections, and looked up using a `&[u8]`.
+///
+/// ```rust
+/// # use smartstring::{ByteKeyed, alias::String};
+/// # use std::collections::HashSet;
+/// let mut set = HashSet::new();
+/// set.insert(ByteKeyed::new(String::from("hello")));
+/// assert!(set.contains(b"hello".as_ref()));
+/// ```
+#[repr(transparent)]
+pub struct ByteKeyed<Mode: SmartStringMode>(pub SmartString<Mode>);
+
+impl<Mode: SmartStringMode> ByteKeyed<Mode> {
+    /// Wrap a [`SmartString`].
+    pub fn new(string: SmartString<Mode>) -> Self {
+        Self(string)
+    }
+
+    /// Unwrap the [`SmartString`].
+    pub fn into_inner(self) -> SmartString<Mode> {
+        self.0
+    }
+
+    /// Get a reference to the key's bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.0.as_bytes()
+    }
+}
+
+impl<Mode: SmartStringMode> Clone for ByteKeyed<Mode> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Mode: SmartStringMode> Deref for ByteKeyed<Mode> {
+    type Target = SmartString<Mode>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<Mode: SmartStringMode> Borrow<[u8]> for ByteKeyed<Mode> {
+    fn borrow(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<Mode: SmartStringMode> AsRef<[u8]> for ByteKeyed<Mode> {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartString<Mode>> for ByteKeyed<Mode> {
+    fn from(string: SmartString<Mode>) -> Self {
+        Self(string)
+    }
+}
+
+impl<Mode: SmartStringMode> From<ByteKeyed<Mode>> for SmartString<Mode> {
+    fn from(key: ByteKeyed<Mode>) -> Self {
+        key.0
+    }
+}
+
+impl<Mode: SmartStringMode> PartialEq for ByteKeyed<Mode> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl<Mode: SmartStringMode> Eq for ByteKeyed<Mode> {}
+
+impl<Mode: SmartStringMode> PartialOrd for ByteKeyed<Mode> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Mode: SmartStringMode> Ord for ByteKeyed<Mode> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+}
+
+impl<Mode: SmartStringMode> Hash for ByteKeyed<Mode> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state)
+    }
+}
+
+impl<Mode: SmartStringMode> Debug for ByteKeyed<Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(&self.0, f)
+    }
+}
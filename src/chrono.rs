@@ -0,0 +1,75 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use chrono::{
+    format::{Fixed, Item, StrftimeItems},
+    DateTime, Offset, SecondsFormat, TimeZone, Timelike,
+};
+use core::fmt::{Display, Write};
+
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Format a [`DateTime`] using a `strftime` style format string.
+    ///
+    /// See [`chrono::format::strftime`] for the supported specifiers.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `format` is invalid, like [`DateTime::format()`] does when
+    /// it's displayed.
+    pub fn from_datetime<Tz>(datetime: &DateTime<Tz>, format: &str) -> Self
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        Self::from_display(&datetime.format(format))
+    }
+
+    /// Format a [`DateTime`] as an RFC 3339 timestamp, exactly like
+    /// [`DateTime::to_rfc3339_opts()`] does.
+    ///
+    /// The length of the output is worked out in advance, so the string is
+    /// inlined if it fits, and allocated only once otherwise.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// # use chrono::{SecondsFormat, TimeZone, Utc};
+    /// let datetime = Utc.with_ymd_and_hms(2022, 2, 22, 22, 22, 22).unwrap();
+    /// let timestamp = String::from_rfc3339(&datetime, SecondsFormat::Secs, true);
+    /// assert_eq!("2022-02-22T22:22:22Z", timestamp);
+    /// assert!(timestamp.is_inline());
+    /// ```
+    pub fn from_rfc3339<Tz>(datetime: &DateTime<Tz>, seconds: SecondsFormat, use_z: bool) -> Self
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        let nanos = datetime.nanosecond() % 1_000_000_000;
+        let (fraction, fraction_len) = match seconds {
+            SecondsFormat::Secs => (None, 0),
+            SecondsFormat::Millis => (Some(Fixed::Nanosecond3), 4),
+            SecondsFormat::Micros => (Some(Fixed::Nanosecond6), 7),
+            SecondsFormat::Nanos => (Some(Fixed::Nanosecond9), 10),
+            _ if nanos == 0 => (None, 0),
+            _ if nanos % 1_000_000 == 0 => (Some(Fixed::Nanosecond3), 4),
+            _ if nanos % 1_000 == 0 => (Some(Fixed::Nanosecond6), 7),
+            _ => (Some(Fixed::Nanosecond9), 10),
+        };
+        let utc = datetime.offset().fix().local_minus_utc() == 0;
+        let (offset, offset_len) = if use_z && utc {
+            (Fixed::TimezoneOffsetColonZ, 1)
+        } else {
+            (Fixed::TimezoneOffsetColon, 6)
+        };
+        let items = StrftimeItems::new("%Y-%m-%dT%H:%M:%S")
+            .chain(fraction.map(Item::Fixed))
+            .chain(Some(Item::Fixed(offset)));
+        // The estimate is only wrong for years outside 0000-9999, which just
+        // costs us a reallocation.
+        let mut out = Self::with_capacity(19 + fraction_len + offset_len);
+        write!(out, "{}", datetime.format_with_items(items))
+            .expect("a Display implementation returned an error unexpectedly");
+        out
+    }
+}
@@ -2,10 +2,19 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{boxed::BoxedString, inline::InlineString, SmartString};
+use crate::{boxed::BoxedString, inline::InlineString, SmartStr, SmartString, StaticSmartString};
+#[cfg(target_has_atomic = "ptr")]
+use crate::{SmartArcString, SmartSlice};
 use alloc::string::String;
-use core::mem::{align_of, size_of};
-use static_assertions::{assert_eq_align, assert_eq_size, const_assert, const_assert_eq};
+use core::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    mem::{align_of, size_of},
+    panic::{RefUnwindSafe, UnwindSafe},
+};
+use static_assertions::{
+    assert_eq_align, assert_eq_size, assert_impl_all, const_assert, const_assert_eq,
+};
 
 /// A compact string representation equal to [`String`] in size with guaranteed inlining.
 ///
@@ -20,6 +29,13 @@ use static_assertions::{assert_eq_align, assert_eq_size, const_assert, const_ass
 ///
 /// This performs generally as well as [`String`] on all ops on boxed strings, and
 /// better than [`String`]s on inlined strings.
+///
+/// The one exception to guaranteed inlining is a string that has been explicitly
+/// given a heap allocated capacity, using
+/// [`with_capacity()`][SmartString::with_capacity], [`reserve()`][SmartString::reserve]
+/// or [`make_boxed_with_capacity()`][SmartString::make_boxed_with_capacity], which
+/// stays on the heap, even while it's short enough to inline, until an operation
+/// shrinks it.
 #[derive(Debug)]
 pub struct Compact;
 
@@ -46,16 +62,131 @@ pub trait SmartStringMode {
     /// string whenever possible (`true`) or leave it as a wrapped string once wrapping
     /// has occurred (`false`).
     const DEALLOC: bool;
+    /// A constant to decide whether cloning a heap allocated string which is short
+    /// enough to be inlined should produce an inlined string (`true`) or a heap
+    /// allocated string with the same capacity as the original (`false`).
+    const COMPACT_ON_CLONE: bool = false;
+    /// A constant to decide whether to store a 16 bit digest of a heap
+    /// allocated string's contents in spare bits of its capacity (`true`), so
+    /// that comparing two heap allocated strings with different digests can
+    /// return `false` without looking at their contents. See [`Digested`].
+    const DIGEST: bool = false;
+
+    /// Decide how many bytes to allocate when a string moves to the heap and
+    /// needs room for `required` bytes. The result must be at least `required`.
+    ///
+    /// This applies to every move to the heap except the ones asking for an
+    /// exact capacity, [`SmartString::reserve_exact()`],
+    /// [`SmartString::make_boxed_with_capacity()`] and
+    /// [`SmartString::force_box()`].
+    ///
+    /// The default allocates at least twice [`MAX_INLINE`], so a string which
+    /// has only just outgrown its inline capacity has room to grow. Override it
+    /// to return `required` if you'd rather allocate exactly as much as is
+    /// needed, eg. for lots of strings just over [`MAX_INLINE`] bytes long.
+    ///
+    /// ```rust
+    /// # use smartstring::{SmartString, SmartStringMode, MAX_INLINE};
+    /// struct Exact;
+    ///
+    /// impl SmartStringMode for Exact {
+    ///     type InlineArray = [u8; MAX_INLINE];
+    ///     const DEALLOC: bool = true;
+    ///
+    ///     fn promotion_capacity(required: usize) -> usize {
+    ///         required
+    ///     }
+    /// }
+    ///
+    /// let mut string = SmartString::<Exact>::new();
+    /// string.push_str(&"x".repeat(MAX_INLINE + 1));
+    /// assert_eq!(MAX_INLINE + 1, string.capacity());
+    /// ```
+    #[inline]
+    fn promotion_capacity(required: usize) -> usize {
+        required.max(MAX_INLINE * 2)
+    }
 }
 
 impl SmartStringMode for Compact {
     type InlineArray = [u8; size_of::<String>() - 1];
     const DEALLOC: bool = true;
+    const COMPACT_ON_CLONE: bool = true;
 }
 
 impl SmartStringMode for LazyCompact {
     type InlineArray = [u8; size_of::<String>() - 1];
     const DEALLOC: bool = false;
+    const COMPACT_ON_CLONE: bool = true;
+}
+
+/// A mode which behaves like `Mode`, except that it never demotes a heap
+/// allocated string back to an inline one.
+///
+/// This is the mode of the string you edit through a
+/// [`BulkEditGuard`][crate::BulkEditGuard], which leaves the demotion check to
+/// the end of the edit. You shouldn't need to name it otherwise.
+pub struct Deferred<Mode: SmartStringMode>(PhantomData<Mode>);
+
+impl<Mode: SmartStringMode> Debug for Deferred<Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Deferred")
+    }
+}
+
+impl<Mode: SmartStringMode> SmartStringMode for Deferred<Mode> {
+    type InlineArray = Mode::InlineArray;
+    const DEALLOC: bool = false;
+    const COMPACT_ON_CLONE: bool = Mode::COMPACT_ON_CLONE;
+    const DIGEST: bool = Mode::DIGEST;
+
+    #[inline]
+    fn promotion_capacity(required: usize) -> usize {
+        Mode::promotion_capacity(required)
+    }
+}
+
+/// A mode which behaves like `Mode`, except that it stores a digest of each
+/// heap allocated string's contents.
+///
+/// The digest is computed when a heap allocated string is created, and
+/// copied when it's cloned, and it's kept in bits of the capacity that
+/// are never needed, so it doesn't make the string any larger. Comparing two
+/// heap allocated strings with different digests for equality fails without
+/// comparing their contents, which speeds up deduplicating lots of long
+/// strings that tend to share long prefixes.
+///
+/// Modifying a string discards its digest, and it won't get a new one until
+/// it's moved back onto the heap or you call
+/// [`store_digest()`][SmartString::store_digest]. Digests are only stored on
+/// 64-bit targets, and elsewhere this mode behaves exactly like `Mode`.
+///
+/// ```rust
+/// # use smartstring::{Compact, Digested, SmartString};
+/// let path = "/a/rather/long/path/to/some/file/or/other";
+/// let one: SmartString<Digested<Compact>> = format!("{}/one", path).into();
+/// let two: SmartString<Digested<Compact>> = format!("{}/two", path).into();
+/// assert_ne!(one, two);
+/// assert_eq!(one, one.clone());
+/// ```
+pub struct Digested<Mode: SmartStringMode>(PhantomData<Mode>);
+
+impl<Mode: SmartStringMode> Debug for Digested<Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Digested")
+    }
+}
+
+impl<Mode: SmartStringMode> SmartStringMode for Digested<Mode> {
+    type InlineArray = Mode::InlineArray;
+    const DEALLOC: bool = Mode::DEALLOC;
+    const COMPACT_ON_CLONE: bool = Mode::COMPACT_ON_CLONE;
+    const DIGEST: bool = true;
+
+    #[inline]
+    fn promotion_capacity(required: usize) -> usize {
+        Mode::promotion_capacity(required)
+    }
 }
 
 /// The maximum capacity of an inline string, in bytes.
@@ -65,6 +196,10 @@ pub const MAX_INLINE: usize = size_of::<String>() - 1;
 // just in case we're on a 1024-bit architecture.
 const_assert!(MAX_INLINE < 128);
 
+// The `debug-canary` feature claims the top two bits of the header byte's data.
+#[cfg(feature = "debug-canary")]
+const_assert!(MAX_INLINE < 32);
+
 // Assert that all the structs are of the expected size.
 assert_eq_size!(BoxedString, SmartString<Compact>);
 assert_eq_size!(BoxedString, SmartString<LazyCompact>);
@@ -79,6 +214,30 @@ assert_eq_align!(SmartString<LazyCompact>, String);
 assert_eq_size!(String, SmartString<Compact>);
 assert_eq_size!(String, SmartString<LazyCompact>);
 
+// Assert that strings can be shared between threads and kept in statics.
+assert_impl_all!(SmartString<Compact>: Send, Sync);
+assert_impl_all!(SmartString<LazyCompact>: Send, Sync);
+assert_impl_all!(StaticSmartString: Send, Sync);
+#[cfg(target_has_atomic = "ptr")]
+assert_impl_all!(SmartArcString: Send, Sync);
+#[cfg(target_has_atomic = "ptr")]
+assert_impl_all!(SmartSlice: Send, Sync);
+
+// Assert that strings can be used across `catch_unwind()`. None of them has any
+// interior mutability that a panic could leave in an inconsistent state.
+assert_impl_all!(SmartString<Compact>: UnwindSafe, RefUnwindSafe);
+assert_impl_all!(SmartString<LazyCompact>: UnwindSafe, RefUnwindSafe);
+assert_impl_all!(StaticSmartString: UnwindSafe, RefUnwindSafe);
+assert_impl_all!(SmartStr: UnwindSafe, RefUnwindSafe);
+#[cfg(target_has_atomic = "ptr")]
+assert_impl_all!(SmartArcString: UnwindSafe, RefUnwindSafe);
+
+// Assert that `Option<SmartString>` makes use of the niche in the marker word.
+assert_eq_size!(Option<SmartString<Compact>>, SmartString<Compact>);
+assert_eq_size!(Option<SmartString<LazyCompact>>, SmartString<LazyCompact>);
+#[cfg(target_has_atomic = "ptr")]
+assert_eq_size!(Option<SmartArcString>, String);
+
 // Assert that `SmartString` is aligned correctly.
 const_assert_eq!(align_of::<String>(), align_of::<SmartString<Compact>>());
 const_assert_eq!(align_of::<String>(), align_of::<SmartString<LazyCompact>>());
@@ -0,0 +1,71 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use alloc::vec::Vec;
+
+#[cfg(feature = "base64")]
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+#[cfg(feature = "hex")]
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Encode bytes as lowercase hexadecimal.
+    ///
+    /// The output is written straight into a string of the right length, which
+    /// is inlined if it fits and otherwise allocated just once.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let id = String::encode_hex(&[0xde, 0xad, 0xbe, 0xef]);
+    /// assert_eq!("deadbeef", id);
+    /// assert!(id.is_inline());
+    /// ```
+    pub fn encode_hex(data: &[u8]) -> Self {
+        #[allow(unsafe_code)]
+        unsafe {
+            Self::from_utf8_fill(data.len() * 2, |buffer| {
+                hex::encode_to_slice(data, buffer).expect("hex output length mismatch")
+            })
+        }
+    }
+
+    /// Decode a hexadecimal string into bytes.
+    ///
+    /// Both lowercase and uppercase digits are accepted.
+    pub fn decode_hex(&self) -> Result<Vec<u8>, hex::FromHexError> {
+        hex::decode(self.as_str())
+    }
+}
+
+#[cfg(feature = "base64")]
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Encode bytes as padded base64, using the standard alphabet.
+    ///
+    /// The output is written straight into a string of the right length, which
+    /// is inlined if it fits and otherwise allocated just once.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let token = String::encode_base64(b"hello");
+    /// assert_eq!("aGVsbG8=", token);
+    /// assert!(token.is_inline());
+    /// ```
+    pub fn encode_base64(data: &[u8]) -> Self {
+        let len = base64::encoded_len(data.len(), true).expect("base64 output length overflow");
+        #[allow(unsafe_code)]
+        unsafe {
+            Self::from_utf8_fill(len, |buffer| {
+                let written = STANDARD
+                    .encode_slice(data, buffer)
+                    .expect("base64 output length mismatch");
+                debug_assert_eq!(len, written);
+            })
+        }
+    }
+
+    /// Decode a padded base64 string, using the standard alphabet, into bytes.
+    pub fn decode_base64(&self) -> Result<Vec<u8>, base64::DecodeError> {
+        STANDARD.decode(self.as_str())
+    }
+}
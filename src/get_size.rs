@@ -0,0 +1,41 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use get_size::GetSize;
+
+impl<Mode: SmartStringMode> GetSize for SmartString<Mode> {
+    /// Report the capacity of the string's heap buffer, or zero for an inline
+    /// string.
+    fn get_heap_size(&self) -> usize {
+        if self.is_inline() {
+            0
+        } else {
+            self.capacity()
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Compact, LazyCompact, SmartString};
+    use get_size::GetSize;
+
+    #[test]
+    fn heap_size() {
+        let inline = SmartString::<Compact>::from("short");
+        assert_eq!(0, inline.get_heap_size());
+        assert_eq!(
+            core::mem::size_of::<SmartString<Compact>>(),
+            inline.get_size()
+        );
+
+        let mut boxed = SmartString::<LazyCompact>::from("a string long enough to need the heap");
+        boxed.reserve(100);
+        assert_eq!(boxed.capacity(), boxed.get_heap_size());
+        boxed.truncate(3);
+        assert!(!boxed.is_inline());
+        assert_eq!(boxed.capacity(), boxed.get_heap_size());
+    }
+}
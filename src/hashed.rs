@@ -0,0 +1,109 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use core::{
+    cmp::Ordering,
+    fmt::{Debug, Error, Formatter},
+    hash::{BuildHasher, Hash, Hasher},
+    ops::Deref,
+};
+
+/// A [`SmartString`] with a precomputed hash.
+///
+/// This computes the string's hash once, using a [`BuildHasher`] of your
+/// choice, and feeds only the cached `u64` to any [`Hasher`] it's later
+/// hashed with. Using it as a hash map key makes repeated probes with the same
+/// key object cost the same regardless of the string's length, and lets
+/// equality checks reject most mismatches by comparing hashes before
+/// comparing strings.
+///
+/// Because it doesn't hash like a [`str`], it can't be looked up by `&str`.
+/// Keys and probes must all be `HashedSmartString`s built with the same
+/// [`BuildHasher`], or they won't compare equal. A fast, non-randomised
+/// hasher is usually the right choice for the precomputation, as the hash
+/// map's own hasher still scrambles the cached value.
+///
+/// ```rust
+/// # use smartstring::{HashedSmartString, alias::String};
+/// # use std::collections::{HashSet, hash_map::RandomState};
+/// let hasher = RandomState::new();
+/// let key = HashedSmartString::new(String::from("hello"), &hasher);
+/// let mut set = HashSet::new();
+/// set.insert(key.clone());
+/// assert!(set.contains(&key));
+/// ```
+pub struct HashedSmartString<Mode: SmartStringMode> {
+    string: SmartString<Mode>,
+    hash: u64,
+}
+
+impl<Mode: SmartStringMode> HashedSmartString<Mode> {
+    /// Wrap a [`SmartString`], computing its hash using `build_hasher`.
+    pub fn new<B: BuildHasher>(string: SmartString<Mode>, build_hasher: &B) -> Self {
+        let mut hasher = build_hasher.build_hasher();
+        string.hash(&mut hasher);
+        let hash = hasher.finish();
+        Self { string, hash }
+    }
+
+    /// Get the precomputed hash.
+    pub fn hash_value(&self) -> u64 {
+        self.hash
+    }
+
+    /// Unwrap the [`SmartString`].
+    pub fn into_inner(self) -> SmartString<Mode> {
+        self.string
+    }
+}
+
+impl<Mode: SmartStringMode> Clone for HashedSmartString<Mode> {
+    fn clone(&self) -> Self {
+        Self {
+            string: self.string.clone(),
+            hash: self.hash,
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> Deref for HashedSmartString<Mode> {
+    type Target = SmartString<Mode>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.string
+    }
+}
+
+impl<Mode: SmartStringMode> PartialEq for HashedSmartString<Mode> {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && self.string == other.string
+    }
+}
+
+impl<Mode: SmartStringMode> Eq for HashedSmartString<Mode> {}
+
+impl<Mode: SmartStringMode> PartialOrd for HashedSmartString<Mode> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Mode: SmartStringMode> Ord for HashedSmartString<Mode> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.string.cmp(&other.string)
+    }
+}
+
+impl<Mode: SmartStringMode> Hash for HashedSmartString<Mode> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u64(self.hash)
+    }
+}
+
+impl<Mode: SmartStringMode> Debug for HashedSmartString<Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(&self.string, f)
+    }
+}
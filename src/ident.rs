@@ -0,0 +1,271 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{Compact, SmartString, SmartStringMode};
+use core::{
+    borrow::Borrow,
+    convert::TryFrom,
+    fmt::{Debug, Display, Error, Formatter},
+    ops::Deref,
+    str::FromStr,
+};
+
+/// An identifier: a string which is known to be a valid identifier.
+///
+/// An identifier is a non-empty string of ASCII letters, digits and
+/// underscores, which doesn't start with a digit, like identifiers in most
+/// programming languages and configuration formats. It's checked when the
+/// `SmartIdent` is constructed, and can't be modified afterwards, so a
+/// `SmartIdent` is always valid. Most identifiers are short, so they're
+/// usually stored inline.
+///
+/// With the `serde` feature enabled, a `SmartIdent` deserialises from a
+/// string, and fails if the string isn't an identifier.
+///
+/// ```rust
+/// # use smartstring::{SmartIdent, InvalidIdent};
+/// let ident = SmartIdent::new("max_connections").unwrap();
+/// assert_eq!("max_connections", ident);
+/// assert_eq!(Err(InvalidIdent::Empty), SmartIdent::new(""));
+/// assert_eq!(
+///     Err(InvalidIdent::InvalidChar { index: 3, ch: '-' }),
+///     SmartIdent::new("max-connections")
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SmartIdent {
+    string: SmartString<Compact>,
+}
+
+/// The error returned when trying to make a [`SmartIdent`] from a string
+/// which isn't an identifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum InvalidIdent {
+    /// The string was empty.
+    Empty,
+    /// The string contained a `char` which isn't allowed at that position.
+    InvalidChar {
+        /// The byte index of the `char`.
+        index: usize,
+        /// The `char` itself.
+        ch: char,
+    },
+}
+
+impl Display for InvalidIdent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Empty => f.write_str("an identifier can't be empty"),
+            Self::InvalidChar { index, ch } => write!(
+                f,
+                "character {:?} at index {} isn't allowed in an identifier",
+                ch, index
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InvalidIdent {}
+
+/// Check that `string` is an identifier.
+fn check_ident(string: &str) -> Result<(), InvalidIdent> {
+    let mut chars = string.char_indices();
+    match chars.next() {
+        None => return Err(InvalidIdent::Empty),
+        Some((_, ch)) if ch.is_ascii_alphabetic() || ch == '_' => {}
+        Some((index, ch)) => return Err(InvalidIdent::InvalidChar { index, ch }),
+    }
+    match chars.find(|&(_, ch)| !(ch.is_ascii_alphanumeric() || ch == '_')) {
+        None => Ok(()),
+        Some((index, ch)) => Err(InvalidIdent::InvalidChar { index, ch }),
+    }
+}
+
+impl SmartIdent {
+    /// Construct an identifier from a string slice, checking that it's valid.
+    pub fn new(string: &str) -> Result<Self, InvalidIdent> {
+        check_ident(string)?;
+        Ok(Self {
+            string: string.into(),
+        })
+    }
+
+    /// Get the identifier as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.string.as_str()
+    }
+
+    /// Unwrap the identifier into a [`SmartString`].
+    pub fn into_inner<Mode: SmartStringMode>(self) -> SmartString<Mode> {
+        self.string.convert()
+    }
+}
+
+impl<Mode: SmartStringMode> TryFrom<SmartString<Mode>> for SmartIdent {
+    type Error = InvalidIdent;
+
+    /// Check that a string is an identifier, keeping its heap buffer if it has
+    /// one.
+    fn try_from(string: SmartString<Mode>) -> Result<Self, Self::Error> {
+        check_ident(&string)?;
+        Ok(Self {
+            string: string.convert(),
+        })
+    }
+}
+
+impl TryFrom<&str> for SmartIdent {
+    type Error = InvalidIdent;
+
+    fn try_from(string: &str) -> Result<Self, Self::Error> {
+        Self::new(string)
+    }
+}
+
+impl FromStr for SmartIdent {
+    type Err = InvalidIdent;
+
+    fn from_str(string: &str) -> Result<Self, Self::Err> {
+        Self::new(string)
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartIdent> for SmartString<Mode> {
+    fn from(ident: SmartIdent) -> Self {
+        ident.into_inner()
+    }
+}
+
+impl Deref for SmartIdent {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SmartIdent {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmartIdent {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq<str> for SmartIdent {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmartIdent {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<SmartIdent> for &str {
+    fn eq(&self, other: &SmartIdent) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<SmartIdent> for str {
+    fn eq(&self, other: &SmartIdent) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl Debug for SmartIdent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for SmartIdent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Display::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use super::{check_ident, SmartIdent};
+    use crate::{Compact, SmartString};
+    use serde::{
+        de::{Error, Unexpected},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    impl Serialize for SmartIdent {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_str(self.as_str())
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SmartIdent {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let string = SmartString::<Compact>::deserialize(deserializer)?;
+            match check_ident(&string) {
+                Ok(()) => Ok(Self { string }),
+                Err(_) => Err(D::Error::invalid_value(
+                    Unexpected::Str(&string),
+                    &"an identifier",
+                )),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{InvalidIdent, SmartIdent};
+    use crate::{Compact, LazyCompact, SmartString};
+    use core::convert::TryFrom;
+
+    #[test]
+    fn validation() {
+        for valid in ["a", "_", "_private", "snake_case", "CamelCase2", "x86_64"] {
+            assert_eq!(valid, SmartIdent::new(valid).unwrap());
+        }
+        for &(invalid, error) in [
+            ("", InvalidIdent::Empty),
+            ("1st", InvalidIdent::InvalidChar { index: 0, ch: '1' }),
+            ("a b", InvalidIdent::InvalidChar { index: 1, ch: ' ' }),
+            (
+                "caf\u{e9}",
+                InvalidIdent::InvalidChar {
+                    index: 3,
+                    ch: '\u{e9}',
+                },
+            ),
+        ]
+        .iter()
+        {
+            assert_eq!(Err(error), SmartIdent::new(invalid));
+        }
+
+        let long = "an_identifier_long_enough_to_need_the_heap";
+        let string = SmartString::<LazyCompact>::from(long);
+        let ptr = string.as_ptr();
+        let ident = SmartIdent::try_from(string).unwrap();
+        assert_eq!(ptr, ident.as_ptr());
+        assert_eq!(long, SmartString::<Compact>::from(ident));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde() {
+        let ident: SmartIdent = serde_json::from_str("\"key_1\"").unwrap();
+        assert_eq!("key_1", ident);
+        assert_eq!("\"key_1\"", serde_json::to_string(&ident).unwrap());
+        let error = serde_json::from_str::<SmartIdent>("\"not valid\"").unwrap_err();
+        assert!(error.to_string().contains("an identifier"));
+    }
+}
@@ -4,6 +4,9 @@
 
 use crate::{config::MAX_INLINE, marker_byte::Marker, ops::GenericString};
 use core::{
+    cmp::Ordering,
+    mem::{size_of, MaybeUninit},
+    num::NonZeroUsize,
     ops::{Deref, DerefMut},
     str::{from_utf8_unchecked, from_utf8_unchecked_mut},
 };
@@ -26,9 +29,86 @@ pub(crate) struct InlineString {
     pub(crate) marker: Marker,
 }
 
+const WORDS: usize = size_of::<InlineString>() / size_of::<usize>();
+
+static_assertions::const_assert_eq!(WORDS * size_of::<usize>(), size_of::<InlineString>());
+
+/// The number of `u64`s it takes to hold the contents of an inline string.
+pub(crate) const INLINE_WORDS: usize = (MAX_INLINE + size_of::<u64>() - 1) / size_of::<u64>();
+
+/// Copy the `index`th run of eight bytes out of `bytes`, padded with zeroes if
+/// `bytes` ends before the run does.
+#[inline]
+fn word_bytes(bytes: &[u8], index: usize) -> [u8; size_of::<u64>()] {
+    let mut word = [0; size_of::<u64>()];
+    let start = (index * word.len()).min(bytes.len());
+    let end = (start + word.len()).min(bytes.len());
+    word[..end - start].copy_from_slice(&bytes[start..end]);
+    word
+}
+
+/// Read up to [`MAX_INLINE`] bytes as little endian `u64`s, padded with zeroes.
+pub(crate) fn bytes_to_words(bytes: &[u8]) -> [u64; INLINE_WORDS] {
+    debug_assert!(bytes.len() <= INLINE_WORDS * size_of::<u64>());
+    let mut words = [0; INLINE_WORDS];
+    for (index, word) in words.iter_mut().enumerate() {
+        *word = u64::from_le_bytes(word_bytes(bytes, index));
+    }
+    words
+}
+
+/// The storage for a `SmartString`, holding either an [`InlineString`] or a
+/// `BoxedString`.
+///
+/// Whichever one it holds, the word containing the inline marker is never zero:
+/// an inline marker always has its discriminant bit set, and a boxed string
+/// keeps its non-null pointer in that word. Declaring the word as a
+/// [`NonZeroUsize`] gives the compiler a niche to use, so that an
+/// `Option<SmartString>` is no larger than a `SmartString`. The rest of the
+/// words can hold anything.
+#[cfg(target_endian = "little")]
+#[repr(C)]
+pub(crate) struct Repr {
+    marker_word: NonZeroUsize,
+    rest: [MaybeUninit<usize>; WORDS - 1],
+}
+
+#[cfg(target_endian = "big")]
+#[repr(C)]
+pub(crate) struct Repr {
+    rest: [MaybeUninit<usize>; WORDS - 1],
+    marker_word: NonZeroUsize,
+}
+
+static_assertions::assert_eq_size!(InlineString, Repr);
+static_assertions::assert_eq_align!(InlineString, Repr);
+static_assertions::const_assert!(MAX_INLINE >= size_of::<u64>());
+
+impl Repr {
+    #[inline]
+    pub(crate) const fn new(inline: InlineString) -> Self {
+        // An inline string's marker byte is never zero, so neither is its
+        // marker word.
+        #[allow(unsafe_code)]
+        unsafe {
+            core::mem::transmute(inline)
+        }
+    }
+
+    #[inline]
+    pub(crate) fn as_ptr(&self) -> *const InlineString {
+        (self as *const Self).cast()
+    }
+
+    #[inline]
+    pub(crate) fn as_mut_ptr(&mut self) -> *mut InlineString {
+        (self as *mut Self).cast()
+    }
+}
+
 impl Clone for InlineString {
     fn clone(&self) -> Self {
-        unreachable!("InlineString should be copy!")
+        *self
     }
 }
 
@@ -37,6 +117,7 @@ impl Copy for InlineString {}
 impl Deref for InlineString {
     type Target = str;
 
+    #[inline]
     fn deref(&self) -> &Self::Target {
         #[allow(unsafe_code)]
         unsafe {
@@ -46,6 +127,7 @@ impl Deref for InlineString {
 }
 
 impl DerefMut for InlineString {
+    #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
         let len = self.len();
         #[allow(unsafe_code)]
@@ -56,16 +138,19 @@ impl DerefMut for InlineString {
 }
 
 impl GenericString for InlineString {
+    #[inline]
     fn set_size(&mut self, size: usize) {
         self.marker.set_data(size as u8);
     }
 
+    #[inline]
     fn as_mut_capacity_slice(&mut self) -> &mut [u8] {
         self.data.as_mut()
     }
 }
 
 impl InlineString {
+    #[inline]
     pub(crate) const fn new() -> Self {
         Self {
             marker: Marker::empty(),
@@ -73,11 +158,73 @@ impl InlineString {
         }
     }
 
+    /// Construct an inline string in a const context, panicking if it doesn't
+    /// fit.
+    pub(crate) const fn from_str_const(string: &str) -> Self {
+        let bytes = string.as_bytes();
+        assert!(
+            bytes.len() <= MAX_INLINE,
+            "string is too long to be inlined"
+        );
+        let mut out = Self::new();
+        out.marker = Marker::new_inline(bytes.len() as u8);
+        let mut index = 0;
+        while index < bytes.len() {
+            out.data[index] = bytes[index];
+            index += 1;
+        }
+        out
+    }
+
+    #[inline]
     pub(crate) fn len(&self) -> usize {
         let len = self.marker.data() as usize;
         debug_assert!(len <= MAX_INLINE);
         len
     }
+
+    /// Compare two inline strings eight bytes at a time.
+    ///
+    /// Bytes past the end of the string may hold leftovers from earlier
+    /// contents, so they're read as zeroes.
+    pub(crate) fn eq_words(&self, other: &Self) -> bool {
+        self.len() == other.len() && self.words() == other.words()
+    }
+
+    /// Compare two inline strings bytewise, eight bytes at a time.
+    ///
+    /// Each run of bytes is read as a big endian integer, so comparing the
+    /// integers compares the bytes in order. Only the bytes the strings have in
+    /// common are read, and if they're all equal, the shorter string comes
+    /// first.
+    pub(crate) fn cmp_words(&self, other: &Self) -> Ordering {
+        let common = self.len().min(other.len());
+        let (this, that) = (&self.data[..common], &other.data[..common]);
+        for index in 0..(common + size_of::<u64>() - 1) / size_of::<u64>() {
+            let left = u64::from_be_bytes(word_bytes(this, index));
+            let right = u64::from_be_bytes(word_bytes(that, index));
+            match left.cmp(&right) {
+                Ordering::Equal => {}
+                ordering => return ordering,
+            }
+        }
+        self.len().cmp(&other.len())
+    }
+
+    /// Read the string's bytes as `u64`s, with the bytes past its end zeroed.
+    #[inline]
+    pub(crate) fn words(&self) -> [u64; INLINE_WORDS] {
+        bytes_to_words(&self.data[..self.len()])
+    }
+
+    #[cfg(feature = "debug-canary")]
+    pub(crate) fn check_canary(&self) {
+        assert!(
+            self.marker.canary_holds() && self.marker.data() as usize <= MAX_INLINE,
+            "SmartString representation is corrupt: inline marker {:?} fails its canary check",
+            self.marker
+        );
+    }
 }
 
 impl From<&str> for InlineString {
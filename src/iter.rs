@@ -2,12 +2,16 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{ops::bounds_for, SmartString, SmartStringMode};
+use crate::{
+    ops::{check_range, range_or_panic},
+    SmartString, SmartStringMode, MAX_INLINE,
+};
 use core::{
     fmt::{Debug, Error, Formatter},
     iter::FusedIterator,
+    marker::PhantomData,
     ops::RangeBounds,
-    str::Chars,
+    str::{Chars, Lines, SplitTerminator},
 };
 
 /// A draining iterator for a [`SmartString`].
@@ -24,12 +28,7 @@ impl<'a, Mode: SmartStringMode> Drain<'a, Mode> {
         R: RangeBounds<usize>,
     {
         let string_ptr: *mut _ = string;
-        let len = string.len();
-        let (start, end) = bounds_for(&range, len);
-        assert!(start <= end);
-        assert!(end <= len);
-        assert!(string.as_str().is_char_boundary(start));
-        assert!(string.as_str().is_char_boundary(end));
+        let (start, end) = range_or_panic(check_range(string, &range));
 
         let iter = string.as_str()[start..end].chars();
         Drain {
@@ -79,3 +78,136 @@ impl<'a, Mode: SmartStringMode> Debug for Drain<'a, Mode> {
         f.pad("Drain { ... }")
     }
 }
+
+/// An iterator over a string in chunks which fit inline in a [`SmartString`].
+///
+/// See [`SmartString::inline_chunks()`].
+pub struct InlineChunks<'a, Mode: SmartStringMode> {
+    remaining: &'a str,
+    mode: PhantomData<Mode>,
+}
+
+impl<'a, Mode: SmartStringMode> InlineChunks<'a, Mode> {
+    pub(crate) fn new(string: &'a str) -> Self {
+        InlineChunks {
+            remaining: string,
+            mode: PhantomData,
+        }
+    }
+}
+
+impl<'a, Mode: SmartStringMode> Iterator for InlineChunks<'a, Mode> {
+    type Item = SmartString<Mode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining.is_empty() {
+            return None;
+        }
+        let mut end = self.remaining.len().min(MAX_INLINE);
+        while !self.remaining.is_char_boundary(end) {
+            end -= 1;
+        }
+        let (chunk, rest) = self.remaining.split_at(end);
+        self.remaining = rest;
+        Some(chunk.into())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.remaining.len();
+        (
+            (len + MAX_INLINE - 1) / MAX_INLINE,
+            Some((len + MAX_INLINE - 4) / (MAX_INLINE - 3)),
+        )
+    }
+}
+
+impl<'a, Mode: SmartStringMode> FusedIterator for InlineChunks<'a, Mode> {}
+
+impl<'a, Mode: SmartStringMode> Debug for InlineChunks<'a, Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.pad("InlineChunks { ... }")
+    }
+}
+
+/// An iterator over the lines of a string, as [`SmartString`]s.
+///
+/// See [`SmartString::lines_owned()`].
+pub struct LinesOwned<'a, Mode: SmartStringMode> {
+    lines: Lines<'a>,
+    mode: PhantomData<Mode>,
+}
+
+impl<'a, Mode: SmartStringMode> LinesOwned<'a, Mode> {
+    pub(crate) fn new(string: &'a str) -> Self {
+        LinesOwned {
+            lines: string.lines(),
+            mode: PhantomData,
+        }
+    }
+}
+
+impl<'a, Mode: SmartStringMode> Iterator for LinesOwned<'a, Mode> {
+    type Item = SmartString<Mode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.lines.next().map(SmartString::from)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.lines.size_hint()
+    }
+}
+
+impl<'a, Mode: SmartStringMode> DoubleEndedIterator for LinesOwned<'a, Mode> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.lines.next_back().map(SmartString::from)
+    }
+}
+
+impl<'a, Mode: SmartStringMode> FusedIterator for LinesOwned<'a, Mode> {}
+
+impl<'a, Mode: SmartStringMode> Debug for LinesOwned<'a, Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.pad("LinesOwned { ... }")
+    }
+}
+
+/// An iterator over the substrings of a string separated by a `char`, as
+/// [`SmartString`]s.
+///
+/// See [`SmartString::split_terminator_owned()`].
+pub struct SplitTerminatorOwned<'a, Mode: SmartStringMode> {
+    split: SplitTerminator<'a, char>,
+    mode: PhantomData<Mode>,
+}
+
+impl<'a, Mode: SmartStringMode> SplitTerminatorOwned<'a, Mode> {
+    pub(crate) fn new(string: &'a str, separator: char) -> Self {
+        SplitTerminatorOwned {
+            split: string.split_terminator(separator),
+            mode: PhantomData,
+        }
+    }
+}
+
+impl<'a, Mode: SmartStringMode> Iterator for SplitTerminatorOwned<'a, Mode> {
+    type Item = SmartString<Mode>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.split.next().map(SmartString::from)
+    }
+}
+
+impl<'a, Mode: SmartStringMode> DoubleEndedIterator for SplitTerminatorOwned<'a, Mode> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.split.next_back().map(SmartString::from)
+    }
+}
+
+impl<'a, Mode: SmartStringMode> FusedIterator for SplitTerminatorOwned<'a, Mode> {}
+
+impl<'a, Mode: SmartStringMode> Debug for SplitTerminatorOwned<'a, Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.pad("SplitTerminatorOwned { ... }")
+    }
+}
@@ -0,0 +1,72 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Concatenate strings from an iterator into a [`SmartString`].
+    ///
+    /// The iterator is cloned and run twice: once to add up the lengths of the
+    /// strings, so the result is allocated exactly once, or not at all if it's
+    /// short enough to be inlined, and once to copy them into it.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let words = ["con", "cat", "en", "ate"];
+    /// assert_eq!("concatenate", String::concat(&words));
+    /// assert_eq!("catcon", String::concat(words.iter().skip(1).take(1).chain(&words[..1])));
+    /// ```
+    pub fn concat<I>(items: I) -> Self
+    where
+        I: IntoIterator,
+        I::IntoIter: Clone,
+        I::Item: AsRef<str>,
+    {
+        Self::join("", items)
+    }
+
+    /// Join strings from an iterator into a [`SmartString`], with `sep`
+    /// between each of them.
+    ///
+    /// Like [`concat()`][SmartString::concat], this runs a clone of the
+    /// iterator to work out the length of the result before building it. Any
+    /// work the iterator does to produce its strings is done twice, so this is
+    /// best suited to iterators over strings which already exist.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let fields = vec![String::from("one"), String::from("two"), String::from("three")];
+    /// assert_eq!("one, two, three", String::join(", ", &fields));
+    /// assert_eq!("o-t-t", String::join("-", fields.iter().map(|f| &f[..1])));
+    /// ```
+    pub fn join<I>(sep: &str, items: I) -> Self
+    where
+        I: IntoIterator,
+        I::IntoIter: Clone,
+        I::Item: AsRef<str>,
+    {
+        let items = items.into_iter();
+        let (count, len) = items.clone().fold((0usize, 0usize), |(count, acc), item| {
+            let len = acc
+                .checked_add(item.as_ref().len())
+                .expect("attempt to join into an oversized string");
+            (count + 1, len)
+        });
+        let len = sep
+            .len()
+            .checked_mul(count.saturating_sub(1))
+            .and_then(|seps| seps.checked_add(len))
+            .expect("attempt to join into an oversized string");
+        let mut out = Self::with_capacity(len);
+        let mut items = items;
+        if let Some(first) = items.next() {
+            out.push_str(first.as_ref());
+            for item in items {
+                out.push_str(sep);
+                out.push_str(item.as_ref());
+            }
+        }
+        out
+    }
+}
@@ -75,6 +75,11 @@
 //! memory efficient in these cases. There will always be a slight overhead on all
 //! operations on boxed strings, compared to [`String`].
 //!
+//! All of [`SmartString`]'s methods are generic over its mode, so they're
+//! compiled separately for each mode a program uses. If code size matters,
+//! eg. when targeting WebAssembly, pick one mode (the [`alias::String`] alias is a
+//! good default) and stick to it, and the other mode won't cost anything.
+//!
 //! ## Feature Flags
 //!
 //! `smartstring` comes with optional support for the following crates through Cargo
@@ -90,6 +95,20 @@
 //! | [`arbitrary`](https://crates.io/crates/arbitrary) | [`Arbitrary`][Arbitrary] implementation for [`SmartString`]. |
 //! | [`proptest`](https://crates.io/crates/proptest) | A strategy for generating [`SmartString`]s from a regular expression. |
 //! | [`serde`](https://crates.io/crates/serde) | [`Serialize`][Serialize] and [`Deserialize`][Deserialize] implementations for [`SmartString`]. |
+//! | [`percent-encoding`](https://crates.io/crates/percent-encoding) | `SmartString::from_percent_encoded()` for decoding percent-encoded strings. |
+//! | [`hex`](https://crates.io/crates/hex) | `SmartString::encode_hex()` and `SmartString::decode_hex()`. |
+//! | [`base64`](https://crates.io/crates/base64) | `SmartString::encode_base64()` and `SmartString::decode_base64()`. |
+//! | [`uuid`](https://crates.io/crates/uuid) | `SmartString::from_uuid()` and a [`From`] implementation for formatting `Uuid`s. |
+//! | [`chrono`](https://crates.io/crates/chrono) | `SmartString::from_datetime()` and `SmartString::from_rfc3339()` for formatting `DateTime`s. |
+//! | [`bumpalo`](https://crates.io/crates/bumpalo) | `SmartString::as_bump_str()` and `SmartString::from_bump_str()` for moving strings in and out of a `Bump` arena. |
+//! | [`allocative`](https://crates.io/crates/allocative) | [`Allocative`](https://docs.rs/allocative/latest/allocative/trait.Allocative.html) implementation for [`SmartString`], reporting the heap buffer of a boxed string. |
+//! | [`get-size`](https://crates.io/crates/get-size) | [`GetSize`](https://docs.rs/get-size/latest/get_size/trait.GetSize.html) implementation for [`SmartString`], reporting the heap buffer of a boxed string. |
+//! | `ident` | `SmartIdent`, a string which is checked to be an identifier when it's constructed or deserialised. |
+//! | `search-key` | `SmartString::to_search_key()` for normalising strings into search keys, using [`unicode-normalization`](https://crates.io/crates/unicode-normalization) to strip accents. |
+//! | [`simdutf8`](https://crates.io/crates/simdutf8) | SIMD accelerated UTF-8 validation for the validating constructors, like [`SmartString::from_utf8()`], which still report exactly the same errors. |
+//! | `test` | The crate's test helpers, including `test::counting_allocator` for asserting how many allocations an operation makes. |
+//! | `debug-tools` | `SmartString::debug_snapshot()` and `SmartString::from_snapshot()` for capturing and recreating a string's internal state. Snapshots are serialisable if the `serde` feature is also enabled. |
+//! | `debug-canary` | Store a redundant check of each inline string's length in its header byte, and validate the representation of a [`SmartString`] on every access, panicking if it's been corrupted. |
 //!
 //! [Serialize]: https://docs.rs/serde/latest/serde/trait.Serialize.html
 //! [Deserialize]: https://docs.rs/serde/latest/serde/trait.Deserialize.html
@@ -106,37 +125,58 @@
 extern crate alloc;
 
 use alloc::{
+    borrow::Cow,
     boxed::Box,
-    string::{String, ToString},
+    collections::TryReserveError,
+    string::{FromUtf16Error, FromUtf8Error, String, ToString},
+    vec::Vec,
 };
 use core::{
     borrow::{Borrow, BorrowMut},
     cmp::Ordering,
     convert::Infallible,
-    fmt::{Debug, Display, Error, Formatter, Write},
+    fmt::{Alignment, Arguments, Debug, Display, Error, Formatter, Write},
     hash::{Hash, Hasher},
     iter::FromIterator,
     marker::PhantomData,
-    mem::{forget, MaybeUninit},
+    mem::{forget, ManuallyDrop, MaybeUninit},
     ops::{
-        Add, Deref, DerefMut, Index, IndexMut, Range, RangeBounds, RangeFrom, RangeFull,
+        Add, AddAssign, Deref, DerefMut, Index, IndexMut, Range, RangeBounds, RangeFrom, RangeFull,
         RangeInclusive, RangeTo, RangeToInclusive,
     },
     ptr::drop_in_place,
-    str::FromStr,
+    slice::SliceIndex,
+    str::{FromStr, Utf8Error},
 };
 
 #[cfg(feature = "std")]
-use std::borrow::Cow;
+use std::{
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+};
+
+mod macros;
+
+/// Re-exports for use by this crate's macros.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "serde")]
+    pub use ::serde;
+    pub use alloc::string::String;
+
+    /// The inline capacity on a 32-bit target, which is the smallest
+    /// [`MAX_INLINE`][crate::MAX_INLINE] of any target.
+    pub const PORTABLE_MAX_INLINE: usize = 11;
+}
 
 mod config;
-pub use config::{Compact, LazyCompact, SmartStringMode, MAX_INLINE};
+pub use config::{Compact, Deferred, Digested, LazyCompact, SmartStringMode, MAX_INLINE};
 
 mod marker_byte;
 use marker_byte::Discriminant;
 
 mod inline;
-use inline::InlineString;
+use inline::{InlineString, Repr};
 
 mod boxed;
 use boxed::BoxedString;
@@ -145,13 +185,73 @@ mod casts;
 use casts::{StringCast, StringCastInto, StringCastMut};
 
 mod iter;
-pub use iter::Drain;
+pub use iter::{Drain, InlineChunks, LinesOwned, SplitTerminatorOwned};
+
+mod byte_keyed;
+pub use byte_keyed::ByteKeyed;
+
+mod hashed;
+pub use hashed::HashedSmartString;
+
+mod word_keyed;
+pub use word_keyed::WordKeyed;
+
+mod segmented;
+pub use segmented::SegmentedSmartString;
+
+mod static_string;
+pub use static_string::StaticSmartString;
+
+mod smart_str;
+pub use smart_str::SmartStr;
+
+#[cfg(target_has_atomic = "ptr")]
+mod arc;
+#[cfg(target_has_atomic = "ptr")]
+pub use arc::{ArcEditGuard, SmartArcString};
+
+#[cfg(target_has_atomic = "ptr")]
+mod slice;
+#[cfg(target_has_atomic = "ptr")]
+pub use slice::SmartSlice;
+
+mod join;
+
+mod set;
+pub use set::{SmallStringSet, SmallStringSetIter};
+
+mod pattern;
+pub use pattern::StrPattern;
+
+mod bulk_edit;
+pub use bulk_edit::BulkEditGuard;
+
+mod promoted;
+pub use promoted::BoxedStringHandle;
+
+mod utf8;
+
+#[cfg(feature = "ident")]
+mod ident;
+#[cfg(feature = "ident")]
+pub use ident::{InvalidIdent, SmartIdent};
+
+#[cfg(feature = "search-key")]
+mod search_key;
+#[cfg(feature = "search-key")]
+pub use search_key::SearchKeyOptions;
+
+#[cfg(feature = "std")]
+mod writer;
+#[cfg(feature = "std")]
+pub use writer::SmartStringWriter;
 
 mod ops;
-use ops::{string_op_grow, string_op_shrink};
+use ops::{string_op_grow, string_op_shrink, string_op_try_grow, GenericString};
+pub use ops::{NotAscii, OpError};
 
 #[cfg(feature = "serde")]
-mod serde;
+pub mod serde;
 
 #[cfg(feature = "arbitrary")]
 mod arbitrary;
@@ -159,6 +259,36 @@ mod arbitrary;
 #[cfg(feature = "proptest")]
 pub mod proptest;
 
+#[cfg(any(feature = "hex", feature = "base64"))]
+mod encoding;
+
+#[cfg(feature = "uuid")]
+mod uuid;
+#[cfg(feature = "uuid")]
+pub use self::uuid::UuidFormat;
+
+#[cfg(feature = "chrono")]
+mod chrono;
+
+#[cfg(feature = "bumpalo")]
+mod bumpalo;
+
+#[cfg(feature = "allocative")]
+mod allocative;
+
+#[cfg(feature = "get-size")]
+mod get_size;
+
+#[cfg(feature = "debug-tools")]
+mod snapshot;
+#[cfg(feature = "debug-tools")]
+pub use snapshot::{ReprKind, SmartStringSnapshot};
+
+#[cfg(feature = "percent-encoding")]
+mod percent;
+#[cfg(feature = "percent-encoding")]
+pub use percent::DecodeError;
+
 /// Convenient type aliases.
 pub mod alias {
     use super::*;
@@ -183,13 +313,15 @@ pub mod alias {
 /// be guaranteed to stay consistent between its boxed and inline states. This means
 /// you still have `capacity()` and `shrink_to_fit()`, relating to state that only
 /// really exists in the boxed variant, because the inline variant can still give
-/// sensible behaviour for these operations, but `with_capacity()`, `reserve()` etc are
-/// absent, because they would have no effect on inline strings and the requested
-/// state changes wouldn't carry over if the inline string is promoted to a boxed
-/// one - not without also storing that state in the inline representation, which
-/// would waste precious bytes for inline string data.
+/// sensible behaviour for these operations. Likewise,
+/// [`with_capacity()`][SmartString::with_capacity] and
+/// [`reserve()`][SmartString::reserve] only allocate if the requested capacity is
+/// too large to inline. Inline strings don't remember a requested capacity: doing so
+/// would waste precious bytes for inline string data, so it's reallocated with a
+/// default capacity if the string is later promoted to a boxed one.
+#[repr(transparent)]
 pub struct SmartString<Mode: SmartStringMode> {
-    data: MaybeUninit<InlineString>,
+    data: Repr,
     mode: PhantomData<Mode>,
 }
 
@@ -207,10 +339,16 @@ impl<Mode: SmartStringMode> Drop for SmartString<Mode> {
 impl<Mode: SmartStringMode> Clone for SmartString<Mode> {
     /// Clone a [`SmartString`].
     ///
-    /// If the string is inlined, this is a [`Copy`] operation. Otherwise,
-    /// a string with the same capacity as the source is allocated.
+    /// If the string is inlined, this is a [`Copy`] operation. If it's heap
+    /// allocated but short enough to be inlined, and the mode's
+    /// [`COMPACT_ON_CLONE`][SmartStringMode::COMPACT_ON_CLONE] policy is set,
+    /// the clone is inlined. Otherwise, a string with the same capacity as the
+    /// source is allocated.
     fn clone(&self) -> Self {
         match self.cast() {
+            StringCast::Boxed(string) if Mode::COMPACT_ON_CLONE && string.len() <= MAX_INLINE => {
+                Self::from_inline(string.deref().into())
+            }
             StringCast::Boxed(string) => Self::from_boxed(string.clone()),
             StringCast::Inline(string) => Self::from_inline(*string),
         }
@@ -248,7 +386,7 @@ impl SmartString<LazyCompact> {
     /// once this happens.
     pub const fn new_const() -> Self {
         Self {
-            data: MaybeUninit::new(InlineString::new()),
+            data: Repr::new(InlineString::new()),
             mode: PhantomData,
         }
     }
@@ -263,7 +401,7 @@ impl SmartString<Compact> {
     /// once this happens.
     pub const fn new_const() -> Self {
         Self {
-            data: MaybeUninit::new(InlineString::new()),
+            data: Repr::new(InlineString::new()),
             mode: PhantomData,
         }
     }
@@ -276,9 +414,119 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         Self::from_inline(InlineString::new())
     }
 
-    fn from_boxed(boxed: BoxedString) -> Self {
+    /// Construct an empty string with room for at least `cap` bytes.
+    ///
+    /// If `cap` is no greater than [`MAX_INLINE`], this doesn't allocate, and
+    /// the string starts out inline. Otherwise, the string starts out heap
+    /// allocated with at least that capacity, so it can grow to `cap` bytes
+    /// without reallocating.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::String, MAX_INLINE};
+    /// let small = String::with_capacity(MAX_INLINE);
+    /// assert!(small.is_inline());
+    /// let big = String::with_capacity(256);
+    /// assert!(!big.is_inline());
+    /// assert!(big.capacity() >= 256);
+    /// ```
+    pub fn with_capacity(cap: usize) -> Self {
+        if cap > MAX_INLINE {
+            Self::from_boxed(Self::box_str("", cap))
+        } else {
+            Self::new()
+        }
+    }
+
+    /// Construct a string of exactly `len` bytes, written by `fill`.
+    ///
+    /// The string is inlined if `len` fits, and otherwise allocated just once,
+    /// with the capacity [`with_capacity()`][SmartString::with_capacity] gives
+    /// it, which follows the mode's promotion capacity.
+    ///
+    /// # Safety
+    ///
+    /// `fill` must leave the whole of the buffer it's given holding valid UTF-8.
+    #[allow(unsafe_code)]
+    unsafe fn from_utf8_fill(len: usize, fill: impl FnOnce(&mut [u8])) -> Self {
+        let mut out = Self::with_capacity(len);
+        match out.cast_mut() {
+            StringCastMut::Boxed(string) => {
+                fill(&mut string.as_mut_capacity_slice()[..len]);
+                string.set_size(len);
+            }
+            StringCastMut::Inline(string) => {
+                fill(&mut string.as_mut_capacity_slice()[..len]);
+                string.set_size(len);
+            }
+        }
+        out
+    }
+
+    /// Construct a string holding `count` copies of `ch`.
+    ///
+    /// This works out the length of the result first, so it's inline if it
+    /// fits and otherwise allocated just once, and encodes `ch` straight into
+    /// the buffer, without going through a [`String`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the length of the result overflows `usize`.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let rule = String::repeat_char('=', 60);
+    /// assert_eq!("=".repeat(60), rule);
+    /// let dots = String::repeat_char('·', 5);
+    /// assert_eq!("·····", dots);
+    /// assert!(dots.is_inline());
+    /// ```
+    pub fn repeat_char(ch: char, count: usize) -> Self {
+        let mut encoded = [0; 4];
+        let encoded = ch.encode_utf8(&mut encoded).as_bytes();
+        let len = encoded.len().checked_mul(count).expect("capacity overflow");
+        let fill = |buffer: &mut [u8]| {
+            if let [byte] = *encoded {
+                buffer.fill(byte);
+            } else {
+                for chunk in buffer.chunks_exact_mut(encoded.len()) {
+                    chunk.copy_from_slice(encoded);
+                }
+            }
+        };
+        // The buffer is filled with whole copies of an encoded `char`.
+        #[allow(unsafe_code)]
+        unsafe {
+            Self::from_utf8_fill(len, fill)
+        }
+    }
+
+    /// How much to allocate when moving a string which needs `required` bytes
+    /// to the heap. See [`SmartStringMode::promotion_capacity()`].
+    fn promotion_capacity(required: usize) -> usize {
+        let cap = Mode::promotion_capacity(required);
+        assert!(
+            cap >= required,
+            "SmartStringMode::promotion_capacity() returned less than the required capacity"
+        );
+        // We can't allocate zero bytes.
+        cap.max(1)
+    }
+
+    /// Copy `string` to the heap, with room for at least `required` bytes, as
+    /// decided by the [promotion capacity][Self::promotion_capacity].
+    ///
+    /// Every move to the heap goes through here, except for the ones asking for
+    /// an exact capacity.
+    fn box_str(string: &str, required: usize) -> BoxedString {
+        BoxedString::from_str(Self::promotion_capacity(required.max(string.len())), string)
+    }
+
+    fn from_boxed(mut boxed: BoxedString) -> Self {
+        if Mode::DIGEST && boxed.digest().is_none() {
+            boxed.store_digest();
+        }
         let mut out = Self {
-            data: MaybeUninit::uninit(),
+            data: Repr::new(InlineString::new()),
             mode: PhantomData,
         };
         let data_ptr: *mut BoxedString = out.data.as_mut_ptr().cast();
@@ -291,20 +539,32 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
 
     fn from_inline(inline: InlineString) -> Self {
         Self {
-            data: MaybeUninit::new(inline),
+            data: Repr::new(inline),
             mode: PhantomData,
         }
     }
 
     fn discriminant(&self) -> Discriminant {
-        // unsafe { self.data.assume_init() }.marker.discriminant()
-        let str_ptr: *const BoxedString =
-            self.data.as_ptr().cast() as *const _ as *const BoxedString;
+        let str_ptr: *const BoxedString = self.data.as_ptr().cast();
         #[allow(unsafe_code)]
         Discriminant::from_bit(BoxedString::check_alignment(unsafe { &*str_ptr }))
     }
 
+    /// Panic if the string's representation fails its redundancy checks.
+    #[cfg(feature = "debug-canary")]
+    fn check_canary(&self) {
+        #[allow(unsafe_code)]
+        match self.discriminant() {
+            Discriminant::Inline => unsafe { &*self.data.as_ptr() }.check_canary(),
+            Discriminant::Boxed => {
+                unsafe { &*self.data.as_ptr().cast::<BoxedString>() }.check_canary()
+            }
+        }
+    }
+
     fn cast(&self) -> StringCast<'_> {
+        #[cfg(feature = "debug-canary")]
+        self.check_canary();
         #[allow(unsafe_code)]
         match self.discriminant() {
             Discriminant::Inline => StringCast::Inline(unsafe { &*self.data.as_ptr() }),
@@ -313,19 +573,26 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
     }
 
     fn cast_mut(&mut self) -> StringCastMut<'_> {
+        #[cfg(feature = "debug-canary")]
+        self.check_canary();
         #[allow(unsafe_code)]
         match self.discriminant() {
             Discriminant::Inline => StringCastMut::Inline(unsafe { &mut *self.data.as_mut_ptr() }),
             Discriminant::Boxed => {
-                StringCastMut::Boxed(unsafe { &mut *self.data.as_mut_ptr().cast() })
+                let string: &mut BoxedString = unsafe { &mut *self.data.as_mut_ptr().cast() };
+                // The caller may change the contents, so any digest is stale.
+                string.clear_digest();
+                StringCastMut::Boxed(string)
             }
         }
     }
 
     fn cast_into(mut self) -> StringCastInto {
+        #[cfg(feature = "debug-canary")]
+        self.check_canary();
         #[allow(unsafe_code)]
         match self.discriminant() {
-            Discriminant::Inline => StringCastInto::Inline(unsafe { self.data.assume_init() }),
+            Discriminant::Inline => StringCastInto::Inline(unsafe { *self.data.as_ptr() }),
             Discriminant::Boxed => StringCastInto::Boxed(unsafe {
                 let boxed_ptr: *mut BoxedString = self.data.as_mut_ptr().cast();
                 let string = boxed_ptr.read();
@@ -356,9 +623,11 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
     }
 
     /// Attempt to inline the string regardless of whether `Mode::DEALLOC` is set.
+    ///
+    /// A [pinned][SmartString::pin_boxed] string is never inlined.
     fn really_try_demote(&mut self) -> bool {
         if let StringCastMut::Boxed(string) = self.cast_mut() {
-            if string.len() > MAX_INLINE {
+            if string.len() > MAX_INLINE || string.is_pinned() {
                 false
             } else {
                 let s: &str = string.deref();
@@ -375,6 +644,338 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         }
     }
 
+    /// Convert the string into another mode.
+    ///
+    /// Every mode stores strings the same way, so this moves the string's
+    /// representation over as it is, keeping its heap buffer if it has one,
+    /// without copying or reallocating. The only exception is when the new
+    /// mode would have moved the string inline, like a short heap allocated
+    /// [`LazyCompact`] string converted to a [`Compact`] one, which is moved
+    /// inline as it would be after any other operation. There are also [`From`]
+    /// implementations for converting between [`Compact`] and [`LazyCompact`].
+    ///
+    /// ```rust
+    /// # use smartstring::{Compact, LazyCompact, SmartString};
+    /// let compact: SmartString<Compact> = "a string long enough to need the heap".into();
+    /// let ptr = compact.as_ptr();
+    /// let lazy: SmartString<LazyCompact> = compact.convert();
+    /// assert_eq!(ptr, lazy.as_ptr());
+    /// ```
+    pub fn convert<OtherMode: SmartStringMode>(self) -> SmartString<OtherMode> {
+        let this = ManuallyDrop::new(self);
+        let mut out = SmartString {
+            // We've taken ownership of the storage, and `this` won't drop it.
+            #[allow(unsafe_code)]
+            data: unsafe { core::ptr::read(&this.data) },
+            mode: PhantomData,
+        };
+        out.try_demote();
+        out
+    }
+
+    /// Construct a string from a value's [`Display`] implementation.
+    ///
+    /// This formats the value directly into the new string, so, unlike
+    /// [`to_string()`][ToString::to_string], it doesn't allocate unless the output
+    /// is too long to inline. See also [`ToSmartString`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`Display`] implementation returns an error, like
+    /// [`to_string()`][ToString::to_string] does.
+    pub fn from_display<T: Display + ?Sized>(value: &T) -> Self {
+        let mut out = Self::new();
+        out.write_fmt(format_args!("{}", value))
+            .expect("a Display implementation returned an error unexpectedly");
+        out
+    }
+
+    /// Append formatted text to the string, reserving room for all of it first.
+    ///
+    /// This formats `args` once with a writer which only counts the bytes, then
+    /// [reserves][SmartString::reserve] that many and formats `args` again into
+    /// the string, so the string is reallocated at most once. The
+    /// [`write_smart!`] macro is a shorthand for calling this.
+    ///
+    /// The formatting implementations involved must produce the same output
+    /// both times, or the reservation will be wrong, though the result is
+    /// still correct.
+    pub fn write_fmt_sized(&mut self, args: Arguments<'_>) -> Result<(), Error> {
+        struct Counter(usize);
+
+        impl Write for Counter {
+            fn write_str(&mut self, string: &str) -> Result<(), Error> {
+                self.0 += string.len();
+                Ok(())
+            }
+        }
+
+        let mut counter = Counter(0);
+        counter.write_fmt(args)?;
+        self.reserve(counter.0);
+        self.write_fmt(args)
+    }
+
+    /// Construct a string from a float formatted with `precision` digits after
+    /// the decimal point, like `format!("{:.precision$}", value)`.
+    ///
+    /// The float is formatted directly into the new string, which doesn't
+    /// allocate unless the output is too long to inline.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from_float_with(1.23456, 3);
+    /// assert_eq!("1.235", string);
+    /// assert!(string.is_inline());
+    /// ```
+    pub fn from_float_with(value: f64, precision: usize) -> Self {
+        let mut out = Self::new();
+        out.push_float_with(value, precision);
+        out
+    }
+
+    /// Append a float formatted with `precision` digits after the decimal
+    /// point, like `format!("{:.precision$}", value)`, to the string.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("latency=");
+    /// string.push_float_with(0.0625, 2);
+    /// assert_eq!("latency=0.06", string);
+    /// ```
+    pub fn push_float_with(&mut self, value: f64, precision: usize) {
+        // Formatting a float into a string can't fail.
+        let _ = self.write_fmt(format_args!("{:.*}", precision, value));
+    }
+
+    /// Construct a string from a vector of bytes, which must be valid UTF-8.
+    ///
+    /// If the bytes fit inline, they're copied into an inline string and the
+    /// vector is dropped. Otherwise, they're moved to the heap the same way
+    /// [`From<String>`][From] does it.
+    ///
+    /// # Allocation
+    ///
+    /// A boxed string can't simply adopt the vector's buffer the way
+    /// [`String::from_utf8()`] does. It tells itself apart from an inline string
+    /// by the least significant bit of its buffer pointer, so it allocates its
+    /// buffers aligned to two bytes, and it has to free them with that same
+    /// alignment. A `Vec<u8>`'s buffer is allocated with an alignment of one, so
+    /// even when its address happens to be even, freeing it as a boxed string's
+    /// buffer would be undefined behaviour. There's no room left in the boxed
+    /// representation to remember which alignment a buffer was allocated with.
+    ///
+    /// When the crate is built with a nightly compiler, the buffer is instead
+    /// reallocated with the stricter alignment through the unstable
+    /// `Allocator` API, which most allocators can do without moving it. On a
+    /// stable compiler, it's copied into a new buffer.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from_utf8(b"hello".to_vec()).unwrap();
+    /// assert_eq!("hello", string);
+    /// assert!(string.is_inline());
+    /// assert!(String::from_utf8(vec![0xff]).is_err());
+    /// ```
+    pub fn from_utf8(bytes: Vec<u8>) -> Result<Self, FromUtf8Error> {
+        if bytes.len() <= MAX_INLINE {
+            if let Ok(string) = utf8::from_utf8(&bytes) {
+                return Ok(Self::from_inline(string.into()));
+            }
+        }
+        utf8::string_from_utf8(bytes).map(Self::from)
+    }
+
+    /// Construct a string from bytes, replacing any invalid UTF-8 sequences with
+    /// [`U+FFFD REPLACEMENT CHARACTER`][char::REPLACEMENT_CHARACTER].
+    ///
+    /// Valid input is copied straight into the new string, which is inlined if
+    /// it fits, without going through a [`String`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// assert_eq!("hi \u{fffd}!", String::from_utf8_lossy(b"hi \xff!"));
+    /// ```
+    pub fn from_utf8_lossy(bytes: &[u8]) -> Self {
+        match String::from_utf8_lossy(bytes) {
+            Cow::Borrowed(string) => Self::from(string),
+            Cow::Owned(string) => Self::from(string),
+        }
+    }
+
+    /// Decode a UTF-16 encoded slice into a string, failing if it contains any
+    /// unpaired surrogates.
+    ///
+    /// The input is decoded straight into an inline string if the result fits,
+    /// and otherwise into a single heap allocation, sized by the mode's
+    /// [promotion capacity][SmartStringMode::promotion_capacity]. To go the
+    /// other way, use [`str::encode_utf16()`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let utf16: Vec<u16> = "h\u{e9}llo \u{1f600}".encode_utf16().collect();
+    /// let string = String::from_utf16(&utf16).unwrap();
+    /// assert_eq!("h\u{e9}llo \u{1f600}", string);
+    /// assert!(string.is_inline());
+    /// assert!(String::from_utf16(&[0xd800]).is_err());
+    /// ```
+    pub fn from_utf16(v: &[u16]) -> Result<Self, FromUtf16Error> {
+        let mut len = 0;
+        for ch in char::decode_utf16(v.iter().copied()) {
+            match ch {
+                Ok(ch) => len += ch.len_utf8(),
+                Err(_) => return String::from_utf16(v).map(Self::from),
+            }
+        }
+        Ok(Self::from_utf16_with(v, len, |_| unreachable!()))
+    }
+
+    /// Decode a UTF-16 encoded slice into a string, replacing unpaired
+    /// surrogates with [`U+FFFD REPLACEMENT CHARACTER`][char::REPLACEMENT_CHARACTER].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// assert_eq!("a\u{fffd}b", String::from_utf16_lossy(&[0x61, 0xd800, 0x62]));
+    /// ```
+    pub fn from_utf16_lossy(v: &[u16]) -> Self {
+        let len = char::decode_utf16(v.iter().copied())
+            .map(|ch| ch.unwrap_or(char::REPLACEMENT_CHARACTER).len_utf8())
+            .sum();
+        Self::from_utf16_with(v, len, |_| char::REPLACEMENT_CHARACTER)
+    }
+
+    /// Build a string from an iterator of fallible `char`s, stopping at the
+    /// first error.
+    ///
+    /// This suits decoders which yield a [`Result`] for each `char`, like
+    /// [`char::decode_utf16()`], and builds the string directly rather than
+    /// collecting into a [`String`] first. You can also get the same result by
+    /// collecting into a `Result<SmartString<_>, _>`.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let units = [0x68, 0x69, 0xd800, 0x21];
+    /// let decoded = String::try_from_iter(char::decode_utf16(units[..2].iter().copied()));
+    /// assert_eq!("hi", decoded.unwrap());
+    /// let decoded = String::try_from_iter(char::decode_utf16(units.iter().copied()));
+    /// assert_eq!(0xd800, decoded.unwrap_err().unpaired_surrogate());
+    /// ```
+    pub fn try_from_iter<E, I>(iter: I) -> Result<Self, E>
+    where
+        I: IntoIterator<Item = Result<char, E>>,
+    {
+        let mut out = Self::new();
+        out.try_extend(iter)?;
+        Ok(out)
+    }
+
+    /// Append `char`s from an iterator of fallible `char`s to the string,
+    /// stopping at the first error.
+    ///
+    /// The `char`s before the error stay appended to the string.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("ok: ");
+    /// let digits = "12x4".chars().map(|ch| if ch.is_ascii_digit() { Ok(ch) } else { Err(ch) });
+    /// assert_eq!(Err('x'), string.try_extend(digits));
+    /// assert_eq!("ok: 12", string);
+    /// ```
+    pub fn try_extend<E, I>(&mut self, iter: I) -> Result<(), E>
+    where
+        I: IntoIterator<Item = Result<char, E>>,
+    {
+        let mut this = self.begin_bulk_edit();
+        for ch in iter {
+            this.push(ch?);
+        }
+        Ok(())
+    }
+
+    /// Decode `v`, which must decode to exactly `len` bytes of UTF-8, using
+    /// `replace` to fill in for unpaired surrogates.
+    fn from_utf16_with(v: &[u16], len: usize, replace: impl Fn(u16) -> char) -> Self {
+        #[allow(unsafe_code)]
+        unsafe {
+            // Every byte gets written, because `len` is the exact size of the
+            // encoded output.
+            Self::from_utf8_fill(len, |buffer| {
+                let mut index = 0;
+                for ch in char::decode_utf16(v.iter().copied()) {
+                    let ch = ch.unwrap_or_else(|error| replace(error.unpaired_surrogate()));
+                    index += ch.encode_utf8(&mut buffer[index..]).len();
+                }
+                assert_eq!(len, index);
+            })
+        }
+    }
+
+    /// Construct a string of exactly `len` bytes, written by `fill` into a
+    /// zeroed buffer, and validated as UTF-8 once it's done.
+    ///
+    /// The buffer is inline if `len` fits, and allocated just once otherwise,
+    /// so nothing is copied or grown along the way.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::build_exact(5, |buffer| buffer.copy_from_slice(b"hello")).unwrap();
+    /// assert_eq!("hello", string);
+    /// assert!(String::build_exact(1, |buffer| buffer[0] = 0xff).is_err());
+    /// ```
+    pub fn build_exact(len: usize, fill: impl FnOnce(&mut [u8])) -> Result<Self, Utf8Error> {
+        let mut result = Ok(());
+        #[allow(unsafe_code)]
+        let string = unsafe {
+            Self::from_utf8_fill(len, |buffer| {
+                buffer.fill(0);
+                fill(buffer);
+                if let Err(error) = utf8::from_utf8(buffer) {
+                    // Leave the buffer holding valid UTF-8 on the way out.
+                    buffer.fill(0);
+                    result = Err(error);
+                }
+            })
+        };
+        result.map(|()| string)
+    }
+
+    /// Construct a string from bytes without checking that they're valid UTF-8.
+    ///
+    /// The bytes are copied straight into the inline buffer if they fit, and
+    /// into a single new allocation otherwise.
+    ///
+    /// # Safety
+    ///
+    /// `bytes` must be valid UTF-8, as for [`str::from_utf8_unchecked()`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = unsafe { String::from_utf8_unchecked(b"hello") };
+    /// assert_eq!("hello", string);
+    /// assert!(string.is_inline());
+    /// ```
+    #[allow(unsafe_code)]
+    pub unsafe fn from_utf8_unchecked(bytes: &[u8]) -> Self {
+        Self::from_utf8_fill(bytes.len(), |buffer| buffer.copy_from_slice(bytes))
+    }
+
+    /// Construct a string from bytes which must be ASCII.
+    ///
+    /// ASCII is a subset of UTF-8, so this only has to check the top bit of
+    /// each byte, which is cheaper than full UTF-8 validation. The bytes are
+    /// copied straight into a string of exactly the right size.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// assert_eq!("GET", String::from_ascii(b"GET").unwrap());
+    /// assert_eq!(1, String::from_ascii(b"G\xc3\xa9T").unwrap_err().valid_up_to());
+    /// ```
+    pub fn from_ascii(bytes: &[u8]) -> Result<Self, NotAscii> {
+        ops::check_ascii(bytes)?;
+        #[allow(unsafe_code)]
+        Ok(unsafe { Self::from_utf8_fill(bytes.len(), |buffer| buffer.copy_from_slice(bytes)) })
+    }
+
     /// Return the length in bytes of the string.
     ///
     /// Note that this may differ from the length in `char`s.
@@ -395,6 +996,26 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         self.discriminant() == Discriminant::Inline
     }
 
+    /// Test whether two strings have identical representations.
+    ///
+    /// Returns `true` if both strings are heap allocated and point at the same
+    /// buffer with the same length, or if both are inline with the same
+    /// contents. If this returns `true`, the strings are equal, so it can be
+    /// used to skip a full comparison, but a `false` says nothing either way.
+    ///
+    /// Each [`SmartString`] owns its heap buffer, so two boxed strings only
+    /// share one if they're the same string, eg. when a cache is handed back a
+    /// reference to one of its own keys.
+    pub fn same_repr(&self, other: &Self) -> bool {
+        match (self.cast(), other.cast()) {
+            (StringCast::Boxed(this), StringCast::Boxed(that)) => {
+                this.as_ptr() == that.as_ptr() && this.len() == that.len()
+            }
+            (StringCast::Inline(this), StringCast::Inline(that)) => this.eq_words(that),
+            _ => false,
+        }
+    }
+
     /// Get a reference to the string as a string slice.
     pub fn as_str(&self) -> &str {
         self.deref()
@@ -405,6 +1026,33 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         self.deref_mut()
     }
 
+    /// Get a mutable reference to the string's bytes.
+    ///
+    /// There's no equivalent of [`String::as_mut_vec()`], as an inline string
+    /// has no [`Vec`] to hand out, so the length can't be changed through this.
+    ///
+    /// # Safety
+    ///
+    /// The bytes must still be valid UTF-8 when the borrow ends, as for
+    /// [`str::as_bytes_mut()`].
+    #[allow(unsafe_code)]
+    pub unsafe fn as_bytes_mut(&mut self) -> &mut [u8] {
+        self.as_mut_str().as_bytes_mut()
+    }
+
+    /// Convert the string into a [`Vec`] of bytes.
+    ///
+    /// A boxed string hands over its buffer where the conversion into
+    /// [`String`] can, while an inline string is copied into a new [`Vec`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// assert_eq!(b"hello".to_vec(), String::from("hello").into_bytes());
+    /// ```
+    pub fn into_bytes(self) -> Vec<u8> {
+        String::from(self).into_bytes()
+    }
+
     /// Return the currently allocated capacity of the string.
     ///
     /// Note that if this is a boxed string, it returns [`String::capacity()`][String::capacity],
@@ -421,16 +1069,594 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         }
     }
 
+    /// Get the unused part of the string's buffer, after its last byte.
+    ///
+    /// This is the same as [`Vec::spare_capacity_mut()`]: you can write bytes
+    /// into the returned slice, then make them part of the string with
+    /// [`set_len()`][SmartString::set_len], without having to stage them in a
+    /// separate buffer first. Call [`reserve()`][SmartString::reserve] first to
+    /// make sure there's enough room. An inline string's spare capacity is
+    /// whatever is left of its [`MAX_INLINE`] bytes.
+    ///
+    /// # Safety
+    ///
+    /// Unlike a [`Vec`]'s, an inline string's spare capacity is always
+    /// initialised, and is read as plain bytes when the string is copied or
+    /// compared, so you mustn't write [`MaybeUninit::uninit()`] into any part
+    /// of the slice. Only write initialised bytes.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("hello");
+    /// string.reserve(6);
+    /// let spare = unsafe { string.spare_capacity_mut() };
+    /// for (slot, byte) in spare.iter_mut().zip(b" world") {
+    ///     slot.write(*byte);
+    /// }
+    /// unsafe { string.set_len(11) };
+    /// assert_eq!("hello world", string);
+    /// ```
+    #[allow(unsafe_code)]
+    pub unsafe fn spare_capacity_mut(&mut self) -> &mut [MaybeUninit<u8>] {
+        let (len, buffer) = match self.cast_mut() {
+            StringCastMut::Boxed(string) => (string.len(), string.as_mut_capacity_slice()),
+            StringCastMut::Inline(string) => (string.len(), string.as_mut_capacity_slice()),
+        };
+        let spare = &mut buffer[len..];
+        // `MaybeUninit<u8>` has the same layout as `u8`, and can't be used to
+        // write anything but a `u8`.
+        #[allow(unsafe_code)]
+        unsafe {
+            core::slice::from_raw_parts_mut(spare.as_mut_ptr().cast(), spare.len())
+        }
+    }
+
+    /// Set the length of the string, in bytes.
+    ///
+    /// This is the same as [`Vec::set_len()`], for use with
+    /// [`spare_capacity_mut()`][SmartString::spare_capacity_mut]. It doesn't
+    /// move the string between inline and heap storage.
+    ///
+    /// # Safety
+    ///
+    /// `new_len` must be no larger than [`capacity()`][SmartString::capacity],
+    /// and the first `new_len` bytes of the buffer must be initialised and be
+    /// valid UTF-8.
+    #[allow(unsafe_code)]
+    pub unsafe fn set_len(&mut self, new_len: usize) {
+        debug_assert!(new_len <= self.capacity());
+        match self.cast_mut() {
+            StringCastMut::Boxed(string) => string.set_size(new_len),
+            StringCastMut::Inline(string) => string.set_size(new_len),
+        }
+        debug_assert!(core::str::from_utf8(self.as_bytes()).is_ok());
+    }
+
+    /// Append the characters from an iterator of known length to the end of
+    /// the string.
+    ///
+    /// This is a faster version of [`extend()`][Extend::extend] for iterators
+    /// which know their length. It reserves room for one byte per character up
+    /// front, then encodes the characters straight into the buffer, so it
+    /// reallocates at most once if they're all ASCII. Each character which
+    /// doesn't fit reserves room for itself and all the characters after it.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("row:");
+    /// string.extend_from_chars_exact((0..40).map(|i| if i % 2 == 0 { '#' } else { '.' }));
+    /// assert_eq!(44, string.len());
+    /// assert!(string.ends_with("#.#."));
+    /// ```
+    pub fn extend_from_chars_exact<I>(&mut self, chars: I)
+    where
+        I: IntoIterator<Item = char>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut chars = chars.into_iter();
+        self.reserve(chars.len());
+        loop {
+            let overflow = match self.cast_mut() {
+                StringCastMut::Boxed(this) => ops::push_chars_within_capacity(this, &mut chars),
+                StringCastMut::Inline(this) => ops::push_chars_within_capacity(this, &mut chars),
+            };
+            match overflow {
+                Some(ch) => {
+                    self.reserve(ch.len_utf8() + chars.len());
+                    self.push(ch);
+                }
+                None => break,
+            }
+        }
+    }
+
     /// Push a character to the end of the string.
     pub fn push(&mut self, ch: char) {
         string_op_grow!(ops::Push, self, ch)
     }
 
+    /// Push an ASCII character, given as a byte, to the end of the string.
+    ///
+    /// This skips the UTF-8 encoding step [`push()`][SmartString::push] has to
+    /// go through, which makes a difference when pushing one byte at a time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte` isn't ASCII.
+    #[inline]
+    pub fn push_ascii(&mut self, byte: u8) {
+        assert!(
+            byte.is_ascii(),
+            "push_ascii: byte {:#04x} is not ASCII",
+            byte
+        );
+        #[allow(unsafe_code)]
+        unsafe {
+            self.push_byte_unchecked(byte)
+        }
+    }
+
+    /// Push a raw byte to the end of the string.
+    ///
+    /// This lets you build multi-byte UTF-8 sequences one byte at a time.
+    ///
+    /// # Safety
+    ///
+    /// The string must be valid UTF-8 again before it's used in any way other
+    /// than further calls to this method. Pushing the first bytes of a UTF-8
+    /// sequence and never the rest is undefined behaviour.
+    #[allow(unsafe_code)]
+    #[inline]
+    pub unsafe fn push_byte_unchecked(&mut self, byte: u8) {
+        string_op_grow!(ops::PushByte, self, byte)
+    }
+
+    /// Append a line to the string, followed by exactly one `\n`.
+    ///
+    /// Any line endings, `\n` or `\r\n`, already at the end of `line` are
+    /// stripped first, so the string always ends up with a single `\n`. A
+    /// `\r` on its own isn't a line ending, and is kept.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut log = String::new();
+    /// log.push_line("first");
+    /// log.push_line("second\r\n");
+    /// assert_eq!("first\nsecond\n", log);
+    /// ```
+    pub fn push_line(&mut self, line: &str) {
+        let mut line = line;
+        while let Some(rest) = line.strip_suffix('\n') {
+            line = rest.strip_suffix('\r').unwrap_or(rest);
+        }
+        string_op_grow!(ops::PushLine, self, line)
+    }
+
+    /// Copy bytes which must be ASCII onto the end of the string.
+    ///
+    /// Like [`from_ascii()`][SmartString::from_ascii], this skips full UTF-8
+    /// validation. If any of the bytes aren't ASCII, nothing is appended.
+    pub fn push_ascii_bytes(&mut self, bytes: &[u8]) -> Result<(), NotAscii> {
+        ops::check_ascii(bytes)?;
+        #[allow(unsafe_code)]
+        self.push_str(unsafe { core::str::from_utf8_unchecked(bytes) });
+        Ok(())
+    }
+
     /// Copy a string slice onto the end of the string.
     pub fn push_str(&mut self, string: &str) {
         string_op_grow!(ops::PushStr, self, string)
     }
 
+    /// Move the contents of `other` onto the end of the string, leaving `other` empty.
+    ///
+    /// If the string is empty and `other` is heap allocated, this takes over `other`'s
+    /// heap buffer instead of copying its contents.
+    pub fn append(&mut self, other: &mut Self) {
+        if self.is_empty() && !other.is_inline() {
+            core::mem::swap(self, other);
+        } else {
+            let string: &str = other;
+            string_op_grow!(ops::PushStr, self, string);
+        }
+        other.clear();
+    }
+
+    /// Pad the string with `fill` until it's `width` `char`s long.
+    ///
+    /// `align` decides where the string's existing contents end up, in the same way
+    /// as the alignment flags in a [`format!`][alloc::format] string: with
+    /// [`Alignment::Left`] the padding is added at the end, with
+    /// [`Alignment::Right`] it's added at the start, and with [`Alignment::Center`]
+    /// it's split between the two, with any odd `char` going at the end.
+    ///
+    /// If the string is already `width` `char`s or longer, this does nothing.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// use std::fmt::Alignment;
+    ///
+    /// let mut string = String::from("hello");
+    /// string.pad_to(9, '*', Alignment::Center);
+    /// assert_eq!("**hello**", string);
+    /// ```
+    pub fn pad_to(&mut self, width: usize, fill: char, align: Alignment) {
+        string_op_grow!(ops::PadTo, self, width, fill, align)
+    }
+
+    /// Reserve capacity for at least `additional` more bytes.
+    ///
+    /// If the string is inline and the extra bytes would still fit, this does
+    /// nothing. If they wouldn't, the string is moved to the heap, with room for
+    /// at least `additional` more bytes. If the string is already heap
+    /// allocated, this grows its buffer like [`String::reserve()`] does, possibly
+    /// reserving more than requested to avoid frequent reallocations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`, or is larger than the
+    /// largest capacity a string can have, which is 2^46 bytes on 64-bit
+    /// targets, where the rest of the capacity field holds flags and digests.
+    pub fn reserve(&mut self, additional: usize) {
+        let target = self
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        match self.cast_mut() {
+            StringCastMut::Boxed(string) => string.ensure_capacity(target),
+            StringCastMut::Inline(string) => {
+                if target > MAX_INLINE {
+                    let boxed = Self::box_str(string, target);
+                    self.promote_from(boxed);
+                }
+            }
+        }
+    }
+
+    /// Reserve capacity for a string of `total` bytes in all.
+    ///
+    /// This is [`reserve_exact()`][SmartString::reserve_exact] in terms of the
+    /// final length rather than the number of extra bytes, for when you know
+    /// how long the string will end up. It does nothing if the string is
+    /// already `total` bytes or longer, or there's room for `total` bytes
+    /// already, otherwise it moves the string onto the heap or grows its buffer
+    /// in one step.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let parts = ["a string", " made of ", "several parts"];
+    /// let mut string = String::from("prefix: ");
+    /// string.reserve_total(64);
+    /// assert!(string.capacity() >= 64);
+    /// let capacity = string.capacity();
+    /// for part in parts {
+    ///     string.push_str(part);
+    /// }
+    /// assert_eq!(capacity, string.capacity());
+    /// ```
+    pub fn reserve_total(&mut self, total: usize) {
+        self.reserve_exact(total.saturating_sub(self.len()));
+    }
+
+    /// Reserve capacity for exactly `additional` more bytes.
+    ///
+    /// This works like [`reserve()`][SmartString::reserve], except that it
+    /// doesn't deliberately over-allocate when growing a heap allocated string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the new capacity overflows `usize`, or is larger than the
+    /// largest capacity a string can have, which is 2^46 bytes on 64-bit
+    /// targets, where the rest of the capacity field holds flags and digests.
+    pub fn reserve_exact(&mut self, additional: usize) {
+        let target = self
+            .len()
+            .checked_add(additional)
+            .expect("capacity overflow");
+        match self.cast_mut() {
+            StringCastMut::Boxed(string) => string.ensure_capacity_exact(target),
+            StringCastMut::Inline(string) => {
+                if target > MAX_INLINE {
+                    let boxed = BoxedString::from_str(target, string);
+                    self.promote_from(boxed);
+                }
+            }
+        }
+    }
+
+    /// Move the string to the heap, with a capacity of at least `cap` bytes.
+    ///
+    /// Use this before appending a large amount of data to a string which is
+    /// currently inline, to skip the promotion to a boxed string and the
+    /// reallocations which would otherwise happen along the way. If the string
+    /// is already heap allocated with enough capacity, this does nothing.
+    ///
+    /// Unlike [`reserve()`][SmartString::reserve], this promotes an inline
+    /// string even if `cap` would fit inline. A [`Compact`] string moves back
+    /// inline the next time an operation leaves it short enough.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("prefix: ");
+    /// string.make_boxed_with_capacity(4096);
+    /// assert!(!string.is_inline());
+    /// assert!(string.capacity() >= 4096);
+    /// ```
+    pub fn make_boxed_with_capacity(&mut self, cap: usize) {
+        match self.cast_mut() {
+            StringCastMut::Boxed(string) => string.ensure_capacity_exact(cap),
+            StringCastMut::Inline(string) => {
+                // We can't allocate zero bytes.
+                let cap = cap.max(string.len()).max(1);
+                let boxed = BoxedString::from_str(cap, string);
+                self.promote_from(boxed);
+            }
+        }
+    }
+
+    /// Move the string to the heap if it's currently inline.
+    ///
+    /// The buffer is allocated with exactly as much room as the string needs,
+    /// as with [`make_boxed_with_capacity(0)`][SmartString::make_boxed_with_capacity],
+    /// rather than the mode's
+    /// [promotion capacity][SmartStringMode::promotion_capacity]. A boxed
+    /// string's buffer stays put until the string needs to reallocate, so this
+    /// is useful for handing out a pointer to the string's contents which must
+    /// stay valid while the string is moved around. A [`Compact`] string moves
+    /// back inline the next time an operation leaves it short enough.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("pinned");
+    /// string.force_box();
+    /// let ptr = string.as_ptr();
+    /// let moved = string;
+    /// assert!(!moved.is_inline());
+    /// assert_eq!(ptr, moved.as_ptr());
+    /// ```
+    pub fn force_box(&mut self) {
+        self.make_boxed_with_capacity(0);
+    }
+
+    /// Move the string to the heap, and keep it there until it's
+    /// [unpinned][SmartString::unpin].
+    ///
+    /// An inline string's contents move whenever the string does, so a pointer
+    /// to them can't be handed out for longer than a borrow. A pinned string's
+    /// contents stay in its heap buffer, even in a [`Compact`] string which
+    /// would otherwise move back inline as soon as it's short enough, or when
+    /// calling [`shrink_to_fit()`][SmartString::shrink_to_fit] or
+    /// [`try_into_inline()`][SmartString::try_into_inline].
+    ///
+    /// Like any heap buffer, it does still move if it's reallocated, which
+    /// happens if the string grows beyond its capacity. An inline string is
+    /// moved to a buffer with the mode's
+    /// [promotion capacity][SmartStringMode::promotion_capacity], to leave it
+    /// some room to grow.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("a string long enough to need the heap");
+    /// string.pin_boxed();
+    /// let ptr = string.as_ptr();
+    /// string.truncate(8);
+    /// let moved = string;
+    /// assert!(moved.is_pinned());
+    /// assert!(!moved.is_inline());
+    /// assert_eq!(ptr, moved.as_ptr());
+    /// ```
+    pub fn pin_boxed(&mut self) {
+        if let StringCastMut::Inline(string) = self.cast_mut() {
+            let boxed = Self::box_str(string, string.len());
+            self.promote_from(boxed);
+        }
+        if let StringCastMut::Boxed(string) = self.cast_mut() {
+            string.set_pinned(true);
+        }
+    }
+
+    /// Allow a [pinned][SmartString::pin_boxed] string to move back inline
+    /// again.
+    ///
+    /// This doesn't move the string inline by itself: a [`Compact`] string moves
+    /// back inline the next time an operation leaves it short enough.
+    pub fn unpin(&mut self) {
+        if let StringCastMut::Boxed(string) = self.cast_mut() {
+            string.set_pinned(false);
+        }
+    }
+
+    /// Test whether the string is [pinned][SmartString::pin_boxed] to the heap.
+    pub fn is_pinned(&self) -> bool {
+        match self.cast() {
+            StringCast::Boxed(string) => string.is_pinned(),
+            StringCast::Inline(_) => false,
+        }
+    }
+
+    /// Store a digest of a heap allocated string's contents, if its mode has
+    /// [`DIGEST`][SmartStringMode::DIGEST] set.
+    ///
+    /// A string gets a digest when it's moved onto the heap, but modifying it
+    /// discards the digest. Call this once you've finished building a string
+    /// to give it a new one. It does nothing to an inline string.
+    ///
+    /// ```rust
+    /// # use smartstring::{Compact, Digested, SmartString};
+    /// let mut string = SmartString::<Digested<Compact>>::new();
+    /// string.push_str("a string which is too long to be inlined");
+    /// string.store_digest();
+    /// ```
+    pub fn store_digest(&mut self) {
+        if Mode::DIGEST {
+            if let StringCastMut::Boxed(string) = self.cast_mut() {
+                string.store_digest();
+            }
+        }
+    }
+
+    /// Move the string inline if it's short enough, in any mode.
+    ///
+    /// Returns the inline string, or, if it's too long to inline or it's
+    /// [pinned][SmartString::pin_boxed], the string itself as an error, still
+    /// on the heap. This deallocates a boxed
+    /// string's buffer even if the mode is [`LazyCompact`], which would
+    /// otherwise keep it.
+    ///
+    /// ```rust
+    /// # use smartstring::{SmartString, LazyCompact};
+    /// let mut string: SmartString<LazyCompact> = "a string long enough to need the heap".into();
+    /// string.truncate(8);
+    /// assert!(!string.is_inline());
+    /// let string = string.try_into_inline().unwrap();
+    /// assert!(string.is_inline());
+    /// assert_eq!("a string", string);
+    /// ```
+    pub fn try_into_inline(mut self) -> Result<Self, Self> {
+        if self.really_try_demote() {
+            Ok(self)
+        } else {
+            Err(self)
+        }
+    }
+
+    /// Convert the string into a [`StaticSmartString`], the [`Copy`]able
+    /// inline only string, if it's short enough to inline.
+    ///
+    /// Otherwise, the string is given back unchanged as an error. A heap
+    /// allocated string which is short enough is copied into the result, and
+    /// its buffer deallocated.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::String, StaticSmartString};
+    /// let short: StaticSmartString = String::from("archived").try_into_static().unwrap();
+    /// assert_eq!("archived", short);
+    /// let long = String::from("a string much too long to be archived inline");
+    /// assert_eq!(long.clone(), long.try_into_static().unwrap_err());
+    /// ```
+    pub fn try_into_static(self) -> Result<StaticSmartString, Self> {
+        match self.cast() {
+            StringCast::Inline(string) => Ok(*StaticSmartString::from_inline_ref(string)),
+            StringCast::Boxed(string) => StaticSmartString::try_new(string.deref()).ok_or(self),
+        }
+    }
+
+    /// Borrow the string as a [`StaticSmartString`] if it's stored inline.
+    ///
+    /// This returns `None` for a heap allocated string even if it's short
+    /// enough to inline, because there's no inline copy of it to borrow. Use
+    /// [`try_into_static()`][SmartString::try_into_static] to make one.
+    ///
+    /// ```rust
+    /// # use smartstring::{SmartString, LazyCompact};
+    /// let short = SmartString::<LazyCompact>::from("inline");
+    /// assert_eq!(Some("inline"), short.as_static().map(|string| string.as_str()));
+    /// let mut boxed = SmartString::<LazyCompact>::from("a string which needs the heap");
+    /// boxed.truncate(8);
+    /// assert_eq!(None, boxed.as_static());
+    /// ```
+    pub fn as_static(&self) -> Option<&StaticSmartString> {
+        match self.cast() {
+            StringCast::Inline(string) => Some(StaticSmartString::from_inline_ref(string)),
+            StringCast::Boxed(_) => None,
+        }
+    }
+
+    /// Extend the string with the contents of an iterator, reserving room for
+    /// `hint` more bytes first.
+    ///
+    /// When you know roughly how long the result will be, this saves the
+    /// repeated reallocations [`Extend`] would otherwise make as the string
+    /// grows. The hint only affects capacity, so a wrong one costs at worst some
+    /// wasted memory or the reallocations it was meant to save. This is a stable
+    /// stand-in for the unstable `Extend::extend_reserve()`.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let words = ["alpha", "beta", "gamma", "delta", "epsilon"];
+    /// let hint = words.iter().map(|word| word.len()).sum();
+    /// let mut string = String::new();
+    /// string.extend_with_capacity_hint(words, hint);
+    /// assert_eq!("alphabetagammadeltaepsilon", string);
+    /// ```
+    pub fn extend_with_capacity_hint<I, A>(&mut self, iter: I, hint: usize)
+    where
+        I: IntoIterator<Item = A>,
+        Self: Extend<A>,
+    {
+        self.reserve(hint);
+        self.extend(iter);
+    }
+
+    /// Get a [`std::io::Write`] adapter which appends UTF-8 bytes to the string.
+    ///
+    /// See [`SmartStringWriter`].
+    #[cfg(feature = "std")]
+    pub fn writer(&mut self) -> SmartStringWriter<'_, Mode> {
+        SmartStringWriter::new(self)
+    }
+
+    /// Move the string to the heap and append to it through a
+    /// [`BoxedStringHandle`], which skips the representation checks each
+    /// [`SmartString`] operation would make.
+    ///
+    /// The string is moved to the heap once, with room for at least `min_cap`
+    /// bytes, and after `f` returns, a [`Compact`] string is moved back inline
+    /// if it's short enough. This suits builders which append to the same string
+    /// from many places, like a recursive printer.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::CompactString, BoxedStringHandle};
+    /// enum Expr {
+    ///     Num(u32),
+    ///     Add(Box<Expr>, Box<Expr>),
+    /// }
+    ///
+    /// fn print(expr: &Expr, out: &mut BoxedStringHandle<'_>) {
+    ///     match expr {
+    ///         Expr::Num(num) => out.push_str(&num.to_string()),
+    ///         Expr::Add(left, right) => {
+    ///             out.push('(');
+    ///             print(left, out);
+    ///             out.push_str(" + ");
+    ///             print(right, out);
+    ///             out.push(')');
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let expr = Expr::Add(Box::new(Expr::Num(1)), Box::new(Expr::Num(2)));
+    /// let mut string = CompactString::new();
+    /// string.with_promoted(64, |out| print(&expr, out));
+    /// assert_eq!("(1 + 2)", string);
+    /// assert!(string.is_inline());
+    /// ```
+    pub fn with_promoted<R>(
+        &mut self,
+        min_cap: usize,
+        f: impl FnOnce(&mut BoxedStringHandle<'_>) -> R,
+    ) -> R {
+        self.make_boxed_with_capacity(min_cap);
+        let result = match self.cast_mut() {
+            StringCastMut::Boxed(string) => f(&mut BoxedStringHandle::new(string)),
+            StringCastMut::Inline(_) => unreachable!("the string was just moved to the heap"),
+        };
+        self.try_demote();
+        result
+    }
+
+    /// Start a bulk edit of the string, deferring any demotion until it ends.
+    ///
+    /// The returned [`BulkEditGuard`] dereferences to the string, but in a
+    /// mode which never moves it from the heap back inline, however much it
+    /// shrinks. The check happens once, when the guard is dropped, so a
+    /// sequence of edits which take a [`Compact`] string back and forth across
+    /// [`MAX_INLINE`] bytes doesn't copy it between inline and heap storage each
+    /// time. This makes no difference to modes which don't demote strings.
+    pub fn begin_bulk_edit(&mut self) -> BulkEditGuard<'_, Mode> {
+        BulkEditGuard::new(self)
+    }
+
     /// Shrink the capacity of the string to fit its contents exactly.
     ///
     /// This has no effect on inline strings, which always have a fixed capacity.
@@ -449,6 +1675,114 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         self.really_try_demote();
     }
 
+    /// Give back any heap memory the string doesn't need.
+    ///
+    /// This does the same as [`shrink_to_fit()`][SmartString::shrink_to_fit], but
+    /// returns the number of bytes of capacity it reclaimed, for keeping track of
+    /// periodic reclamation sweeps over long lived collections of
+    /// [`LazyCompact`] strings. See also [`compact_if()`][SmartString::compact_if].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("a string long enough to be boxed");
+    /// let capacity = string.capacity();
+    /// string.truncate(6);
+    /// assert_eq!(capacity, string.compact());
+    /// assert!(string.is_inline());
+    /// assert_eq!(0, string.compact());
+    /// ```
+    pub fn compact(&mut self) -> usize {
+        self.compact_if(|_, _| true)
+    }
+
+    /// Give back any heap memory the string doesn't need, if `predicate`
+    /// returns `true`.
+    ///
+    /// If the string is heap allocated, `predicate` is called with its length
+    /// and capacity, and if it returns `true`, the string is
+    /// [compacted][SmartString::compact]. It isn't called for inline strings,
+    /// which have nothing to give back. Returns the number of bytes of capacity
+    /// reclaimed.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("a string long enough to be boxed");
+    /// let capacity = string.capacity();
+    /// assert_eq!(0, string.compact_if(|len, cap| cap > len * 2));
+    /// string.truncate(6);
+    /// assert_eq!(capacity, string.compact_if(|len, cap| cap > len * 2));
+    /// assert!(string.is_inline());
+    /// ```
+    pub fn compact_if<F>(&mut self, predicate: F) -> usize
+    where
+        F: FnOnce(usize, usize) -> bool,
+    {
+        match self.cast() {
+            StringCast::Boxed(string) if predicate(string.len(), string.capacity()) => {
+                let before = string.capacity();
+                self.shrink_to_fit();
+                match self.cast() {
+                    StringCast::Boxed(string) => before - string.capacity(),
+                    StringCast::Inline(_) => before,
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Put the string into its canonical representation.
+    ///
+    /// After calling this, the string is inlined if and only if its length is
+    /// no greater than [`MAX_INLINE`], unless it's
+    /// [pinned][SmartString::pin_boxed]. Unlike
+    /// [`shrink_to_fit()`][SmartString::shrink_to_fit], this leaves the capacity
+    /// of a boxed string alone.
+    ///
+    /// A [`LazyCompact`] string which has been heap allocated and then shrunk
+    /// isn't in canonical form, and neither is a short string of either mode
+    /// which was moved to the heap by [`with_capacity()`][SmartString::with_capacity],
+    /// [`reserve()`][SmartString::reserve],
+    /// [`make_boxed_with_capacity()`][SmartString::make_boxed_with_capacity],
+    /// [`force_box()`][SmartString::force_box] or
+    /// [`with_promoted()`][SmartString::with_promoted]. A [`Compact`] string
+    /// only moves back inline when an operation shortens it. Strings created
+    /// by [`From`] conversions or by deserialisation are always canonical,
+    /// which makes this useful for comparing representation state in tests.
+    pub fn normalize(&mut self) {
+        self.really_try_demote();
+    }
+
+    /// Remove leading and trailing whitespace from the string in place.
+    ///
+    /// Whitespace is defined as for [`str::trim()`]. Unlike assigning the
+    /// trimmed slice to a new string, this moves the remaining text within
+    /// the string's existing buffer, after which a heap allocated [`Compact`]
+    /// string is moved back inline if it's become short enough.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("  hello world\n");
+    /// string.trim_in_place();
+    /// assert_eq!("hello world", string);
+    /// ```
+    pub fn trim_in_place(&mut self) {
+        string_op_shrink!(ops::Trim, self)
+    }
+
+    /// Remove leading whitespace from the string in place.
+    ///
+    /// See [`trim_in_place()`][SmartString::trim_in_place].
+    pub fn trim_start_in_place(&mut self) {
+        string_op_shrink!(ops::TrimStart, self)
+    }
+
+    /// Remove trailing whitespace from the string in place.
+    ///
+    /// See [`trim_in_place()`][SmartString::trim_in_place].
+    pub fn trim_end_in_place(&mut self) {
+        string_op_shrink!(ops::TrimEnd, self)
+    }
+
     /// Truncate the string to `new_len` bytes.
     ///
     /// If `new_len` is larger than the string's current length, this does nothing.
@@ -462,6 +1796,25 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         string_op_shrink!(ops::Pop, self)
     }
 
+    /// Pop up to `count` `char`s off the end of the string, returning them as
+    /// a new string.
+    ///
+    /// If the string has fewer than `count` `char`s, it's emptied and the
+    /// whole of it is returned. Unlike calling [`pop()`][SmartString::pop] in
+    /// a loop, the string is only checked for demotion once.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("hello w\u{f6}rld");
+    /// assert_eq!("w\u{f6}rld", string.pop_n(5));
+    /// assert_eq!("hello ", string);
+    /// assert_eq!("hello ", string.pop_n(10));
+    /// assert!(string.is_empty());
+    /// ```
+    pub fn pop_n(&mut self, count: usize) -> Self {
+        string_op_shrink!(ops::PopN<Mode>, self, count)
+    }
+
     /// Remove a `char` from the string at the given index.
     ///
     /// If the index doesn't fall on a UTF-8 character boundary, this method panics.
@@ -495,9 +1848,15 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
 
     /// Clear the string.
     ///
-    /// This causes any memory reserved by the string to be immediately deallocated.
+    /// This causes any memory reserved by the string to be immediately
+    /// deallocated, unless the string is [pinned][SmartString::pin_boxed], in
+    /// which case it keeps its buffer and stays pinned.
     pub fn clear(&mut self) {
-        *self = Self::new();
+        if self.is_pinned() {
+            self.truncate(0);
+        } else {
+            *self = Self::new();
+        }
     }
 
     /// Filter out `char`s not matching a predicate.
@@ -519,6 +1878,228 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         Drain::new(self, range)
     }
 
+    /// Trim the string and collapse each run of whitespace inside it into a single space.
+    ///
+    /// Whitespace is as defined by [`char::is_whitespace()`]. If the string is already
+    /// normalised, it's returned as is without allocating.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// # use std::borrow::Cow;
+    /// let string = String::from("  hello \t\n  world ");
+    /// assert_eq!("hello world", string.normalize_whitespace());
+    /// let clean = String::from("hello world");
+    /// assert!(matches!(clean.normalize_whitespace(), Cow::Borrowed(_)));
+    /// ```
+    pub fn normalize_whitespace(&self) -> Cow<'_, str> {
+        if ops::NormalizeWhitespace::is_normalized(self) {
+            Cow::Borrowed(self.as_str())
+        } else {
+            let mut out = String::with_capacity(self.len());
+            for word in self.split_whitespace() {
+                if !out.is_empty() {
+                    out.push(' ');
+                }
+                out.push_str(word);
+            }
+            Cow::Owned(out)
+        }
+    }
+
+    /// Trim the string and collapse each run of whitespace inside it into a single
+    /// space, in place.
+    ///
+    /// See [`normalize_whitespace()`][SmartString::normalize_whitespace].
+    pub fn normalize_whitespace_in_place(&mut self) {
+        string_op_shrink!(ops::NormalizeWhitespace, self)
+    }
+
+    /// Append the string to `out`, escaped for use inside a JSON string literal.
+    ///
+    /// Quotes, backslashes and control characters are escaped the way
+    /// `serde_json` escapes them, and everything between them is copied over in
+    /// bulk. The surrounding quotes aren't written.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut out = String::from("\"");
+    /// String::from("say \"hi\"\n").escape_json_into(&mut out);
+    /// out.push('"');
+    /// assert_eq!(r#""say \"hi\"\n""#, out);
+    /// ```
+    pub fn escape_json_into<OutMode: SmartStringMode>(&self, out: &mut SmartString<OutMode>) {
+        const HEX: &[u8; 16] = b"0123456789abcdef";
+        let bytes = self.as_bytes();
+        let mut clean = 0;
+        for (index, &byte) in bytes.iter().enumerate() {
+            let escape = match byte {
+                b'"' => "\\\"",
+                b'\\' => "\\\\",
+                b'\n' => "\\n",
+                b'\r' => "\\r",
+                b'\t' => "\\t",
+                0x08 => "\\b",
+                0x0c => "\\f",
+                0x00..=0x1f => "",
+                _ => continue,
+            };
+            out.push_str(&self[clean..index]);
+            if escape.is_empty() {
+                out.push_str("\\u00");
+                out.push_ascii(HEX[(byte >> 4) as usize]);
+                out.push_ascii(HEX[(byte & 0xf) as usize]);
+            } else {
+                out.push_str(escape);
+            }
+            clean = index + 1;
+        }
+        out.push_str(&self[clean..]);
+    }
+
+    /// Construct an iterator over the lines of the string, as owned strings.
+    ///
+    /// Lines are split like [`str::lines()`] splits them: on `\n` or `\r\n`,
+    /// with the line endings removed, so lines from Windows and Unix sources come
+    /// out the same.
+    pub fn lines_owned(&self) -> LinesOwned<'_, Mode> {
+        LinesOwned::new(self.as_str())
+    }
+
+    /// Construct an iterator over the substrings of the string separated by
+    /// `separator`, as owned strings.
+    ///
+    /// This splits like [`str::split_terminator()`]: a trailing separator
+    /// doesn't produce an empty last segment, so `"a/b/"` splits the same as
+    /// `"a/b"`. Segments short enough to inline don't allocate.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let path = String::from("/users/bodil/");
+    /// let segments: Vec<String> = path.split_terminator_owned('/').collect();
+    /// assert_eq!(vec!["", "users", "bodil"], segments);
+    /// ```
+    pub fn split_terminator_owned(&self, separator: char) -> SplitTerminatorOwned<'_, Mode> {
+        SplitTerminatorOwned::new(self.as_str(), separator)
+    }
+
+    /// Count the segments [`split_terminator_owned()`][SmartString::split_terminator_owned]
+    /// would produce, without producing them.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// assert_eq!(3, String::from("/users/bodil/").segments_count('/'));
+    /// assert_eq!(0, String::new().segments_count('/'));
+    /// ```
+    pub fn segments_count(&self, separator: char) -> usize {
+        if self.is_empty() {
+            return 0;
+        }
+        let separators = if separator.is_ascii() {
+            // Counting bytes is simple enough for the compiler to vectorise.
+            let byte = separator as u8;
+            self.as_bytes().iter().filter(|&&b| b == byte).count()
+        } else {
+            self.matches(separator).count()
+        };
+        separators + 1 - self.ends_with(separator) as usize
+    }
+
+    /// Get a subslice of the string, or `None` if the range is out of bounds or
+    /// doesn't fall on UTF-8 character boundaries.
+    ///
+    /// This is the same as [`str::get()`], which is also available through
+    /// [`Deref`], and is a non-panicking alternative to indexing.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from("héllo");
+    /// assert_eq!(Some("hé"), string.get(..3));
+    /// assert_eq!(None, string.get(..2));
+    /// assert_eq!(None, string.get(..10));
+    /// ```
+    pub fn get<I: SliceIndex<str>>(&self, index: I) -> Option<&I::Output> {
+        self.as_str().get(index)
+    }
+
+    /// Get a mutable subslice of the string, or `None` if the range is out of
+    /// bounds or doesn't fall on UTF-8 character boundaries.
+    ///
+    /// See [`str::get_mut()`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let mut string = String::from("hello world");
+    /// string.get_mut(6..).unwrap().make_ascii_uppercase();
+    /// assert_eq!("hello WORLD", string);
+    /// assert!(string.get_mut(6..20).is_none());
+    /// ```
+    pub fn get_mut<I: SliceIndex<str>>(&mut self, index: I) -> Option<&mut I::Output> {
+        self.as_mut_str().get_mut(index)
+    }
+
+    /// Get a subslice of the string without checking the range.
+    ///
+    /// See [`str::get_unchecked()`].
+    ///
+    /// # Safety
+    ///
+    /// The range must be within the string's bounds, and fall on UTF-8
+    /// character boundaries.
+    #[allow(unsafe_code)]
+    pub unsafe fn get_unchecked<I: SliceIndex<str>>(&self, index: I) -> &I::Output {
+        self.as_str().get_unchecked(index)
+    }
+
+    /// Copy a range of the string into a new string.
+    ///
+    /// This is like `SmartString::from(&string[range])`, but the range is
+    /// validated only once, and the copy is made straight into a string of
+    /// exactly the right size, which is inlined if it fits.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't fall on UTF-8 character
+    /// boundaries. See [`try_slice_owned()`][SmartString::try_slice_owned] for
+    /// a version which doesn't panic.
+    pub fn slice_owned<R>(&self, range: R) -> Self
+    where
+        R: RangeBounds<usize>,
+    {
+        ops::or_panic(self.try_slice_owned(range))
+    }
+
+    /// Copy a range of the string into a new string, returning an error if the
+    /// range is out of bounds or doesn't fall on UTF-8 character boundaries.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::String, OpError};
+    /// let string = String::from("hello world");
+    /// assert_eq!(Ok(String::from("world")), string.try_slice_owned(6..));
+    /// assert_eq!(
+    ///     Err(OpError::OutOfBounds { index: 12, len: 11 }),
+    ///     string.try_slice_owned(..12)
+    /// );
+    /// ```
+    pub fn try_slice_owned<R>(&self, range: R) -> Result<Self, OpError>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = ops::check_range(self, &range)?;
+        let bytes = &self.as_bytes()[start..end];
+        // The range has been checked to fall on char boundaries.
+        #[allow(unsafe_code)]
+        Ok(unsafe { Self::from_utf8_fill(bytes.len(), |buffer| buffer.copy_from_slice(bytes)) })
+    }
+
+    /// Construct an iterator over the string in inlined chunks.
+    ///
+    /// Each chunk is as long as possible without exceeding [`MAX_INLINE`] bytes
+    /// or splitting a `char`, so every chunk is guaranteed to be inline.
+    /// Concatenating the chunks gives back the original string.
+    pub fn inline_chunks(&self) -> InlineChunks<'_, Mode> {
+        InlineChunks::new(self.as_str())
+    }
+
     /// Replaces a range with the contents of a string slice.
     pub fn replace_range<R>(&mut self, range: R, replace_with: &str)
     where
@@ -527,6 +2108,424 @@ impl<Mode: SmartStringMode> SmartString<Mode> {
         string_op_grow!(ops::ReplaceRange, self, &range, replace_with);
         self.try_demote();
     }
+
+    /// Push a character to the end of the string, returning an error if
+    /// memory allocation fails.
+    pub fn try_push(&mut self, ch: char) -> Result<(), TryReserveError> {
+        string_op_try_grow!(ops::Push, self, ch)
+    }
+
+    /// Copy a string slice onto the end of the string, returning an error if
+    /// memory allocation fails.
+    pub fn try_push_str(&mut self, string: &str) -> Result<(), TryReserveError> {
+        string_op_try_grow!(ops::PushStr, self, string)
+    }
+
+    /// Insert a `char` into the string at the given index, returning an error if
+    /// memory allocation fails.
+    ///
+    /// If the index doesn't fall on a UTF-8 character boundary, this method panics.
+    pub fn try_insert(&mut self, index: usize, ch: char) -> Result<(), TryReserveError> {
+        string_op_try_grow!(ops::Insert, self, index, ch)
+    }
+
+    /// Insert a string slice into the string at the given index, returning an error
+    /// if memory allocation fails.
+    ///
+    /// If the index doesn't fall on a UTF-8 character boundary, this method panics.
+    pub fn try_insert_str(&mut self, index: usize, string: &str) -> Result<(), TryReserveError> {
+        string_op_try_grow!(ops::InsertStr, self, index, string)
+    }
+
+    /// Replaces a range with the contents of a string slice, returning an error if
+    /// memory allocation fails.
+    pub fn try_replace_range<R>(
+        &mut self,
+        range: R,
+        replace_with: &str,
+    ) -> Result<(), TryReserveError>
+    where
+        R: RangeBounds<usize>,
+    {
+        let result: Result<(), TryReserveError> =
+            string_op_try_grow!(ops::ReplaceRange, self, &range, replace_with);
+        self.try_demote();
+        result
+    }
+
+    /// Truncate the string to `new_len` bytes, returning an error instead of
+    /// panicking if `new_len` isn't on a UTF-8 character boundary.
+    ///
+    /// See [`truncate()`][SmartString::truncate].
+    pub fn checked_truncate(&mut self, new_len: usize) -> Result<(), OpError> {
+        if new_len < self.len() {
+            ops::check_index(self, new_len)?;
+        }
+        self.truncate(new_len);
+        Ok(())
+    }
+
+    /// Remove a `char` from the string at the given index, returning an error
+    /// instead of panicking if there's no `char` starting there.
+    ///
+    /// See [`remove()`][SmartString::remove].
+    pub fn checked_remove(&mut self, index: usize) -> Result<char, OpError> {
+        ops::check_char_index(self, index)?;
+        Ok(self.remove(index))
+    }
+
+    /// Insert a `char` into the string at the given index, returning an error
+    /// instead of panicking if the index isn't on a UTF-8 character boundary.
+    ///
+    /// See [`insert()`][SmartString::insert].
+    pub fn checked_insert(&mut self, index: usize, ch: char) -> Result<(), OpError> {
+        ops::check_index(self, index)?;
+        self.insert(index, ch);
+        Ok(())
+    }
+
+    /// Insert a string slice into the string at the given index, returning an
+    /// error instead of panicking if the index isn't on a UTF-8 character
+    /// boundary.
+    ///
+    /// See [`insert_str()`][SmartString::insert_str].
+    pub fn checked_insert_str(&mut self, index: usize, string: &str) -> Result<(), OpError> {
+        ops::check_index(self, index)?;
+        self.insert_str(index, string);
+        Ok(())
+    }
+
+    /// Split the string into two at the given index, returning an error
+    /// instead of panicking if the index isn't on a UTF-8 character boundary.
+    ///
+    /// See [`split_off()`][SmartString::split_off].
+    pub fn checked_split_off(&mut self, index: usize) -> Result<Self, OpError> {
+        ops::check_index(self, index)?;
+        Ok(self.split_off(index))
+    }
+
+    /// Construct a draining iterator over a given range, returning an error
+    /// instead of panicking if the range is invalid.
+    ///
+    /// See [`drain()`][SmartString::drain].
+    pub fn checked_drain<R>(&mut self, range: R) -> Result<Drain<'_, Mode>, OpError>
+    where
+        R: RangeBounds<usize>,
+    {
+        ops::check_range(self, &range)?;
+        Ok(self.drain(range))
+    }
+
+    /// Replace a range with the contents of a string slice, returning an error
+    /// instead of panicking if the range is invalid.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::String, OpError};
+    /// let mut string = String::from("h\u{e9}llo");
+    /// assert_eq!(
+    ///     Err(OpError::NotCharBoundary { index: 2 }),
+    ///     string.checked_replace_range(0..2, "j")
+    /// );
+    /// assert_eq!(Ok(()), string.checked_replace_range(0..3, "j"));
+    /// assert_eq!("jllo", string);
+    /// ```
+    ///
+    /// See [`replace_range()`][SmartString::replace_range].
+    pub fn checked_replace_range<R>(&mut self, range: R, replace_with: &str) -> Result<(), OpError>
+    where
+        R: RangeBounds<usize>,
+    {
+        ops::check_range(self, &range)?;
+        self.replace_range(range, replace_with);
+        Ok(())
+    }
+
+    /// Find the length in bytes of the longest common prefix of the string and
+    /// `other`.
+    ///
+    /// The strings are compared a machine word at a time, and the result is
+    /// backed off to a `char` boundary, so it can be used to slice either string.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from("autocomplete");
+    /// assert_eq!(4, string.common_prefix_len("automatic"));
+    /// // The strings share the first byte of their last `char`s.
+    /// assert_eq!(1, String::from("h\u{e9}").common_prefix_len("h\u{e8}"));
+    /// ```
+    pub fn common_prefix_len(&self, other: &str) -> usize {
+        const WORD: usize = core::mem::size_of::<usize>();
+        let (left, right) = (self.as_bytes(), other.as_bytes());
+        let len = left.len().min(right.len());
+        let mut index = 0;
+        while index + WORD <= len {
+            let mut left_word = [0; WORD];
+            let mut right_word = [0; WORD];
+            left_word.copy_from_slice(&left[index..index + WORD]);
+            right_word.copy_from_slice(&right[index..index + WORD]);
+            let diff = usize::from_le_bytes(left_word) ^ usize::from_le_bytes(right_word);
+            if diff != 0 {
+                index += diff.trailing_zeros() as usize / 8;
+                break;
+            }
+            index += WORD;
+        }
+        if index + WORD > len {
+            while index < len && left[index] == right[index] {
+                index += 1;
+            }
+        }
+        // The bytes before `index` are the same in both strings, so a `char`
+        // boundary in one is a boundary in the other.
+        while !self.is_char_boundary(index) {
+            index -= 1;
+        }
+        index
+    }
+
+    /// Test whether the first `n` bytes of the string are equal to the first
+    /// `n` bytes of `other`, like a `strncmp()` returning zero.
+    ///
+    /// If either string is shorter than `n` bytes, both strings must be the
+    /// same length and equal.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from("autocomplete");
+    /// assert!(string.eq_str_prefix(4, "automatic"));
+    /// assert!(!string.eq_str_prefix(5, "automatic"));
+    /// assert!(!string.eq_str_prefix(5, "auto"));
+    /// ```
+    pub fn eq_str_prefix(&self, n: usize, other: &str) -> bool {
+        let (left, right) = (self.as_bytes(), other.as_bytes());
+        left.get(..n).unwrap_or(left) == right.get(..n).unwrap_or(right)
+    }
+
+    /// Replace all matches of a pattern with another string, returning the
+    /// result as a new [`SmartString`].
+    ///
+    /// This works like [`str::replace()`], but builds the result directly in a
+    /// [`SmartString`], which stays inline if it fits. It has its own name so
+    /// that `replace()` keeps resolving to the [`str`] method, which returns a
+    /// [`String`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from("this is old");
+    /// let replaced: String = string.replace_smart("old", "new");
+    /// assert_eq!("this is new", replaced);
+    /// assert!(replaced.is_inline());
+    /// assert_eq!("th1s 1s old", string.replace_smart('i', "1"));
+    /// ```
+    pub fn replace_smart<P: StrPattern>(&self, from: P, to: &str) -> Self {
+        from.replacen_in(self, to, usize::MAX)
+    }
+
+    /// Replace the first `count` matches of a pattern with another string,
+    /// returning the result as a new [`SmartString`].
+    ///
+    /// This works like [`str::replacen()`], but builds the result directly in a
+    /// [`SmartString`], which stays inline if it fits. See
+    /// [`replace_smart()`][SmartString::replace_smart].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from("foo foo 123 foo");
+    /// assert_eq!("new new 123 foo", string.replacen_smart("foo", "new", 2));
+    /// assert_eq!("faa foo 123 foo", string.replacen_smart(|c: char| c == 'o', "a", 2));
+    /// ```
+    pub fn replacen_smart<P: StrPattern>(&self, from: P, to: &str, count: usize) -> Self {
+        from.replacen_in(self, to, count)
+    }
+
+    /// Return an uppercase copy of the string as a new [`SmartString`].
+    ///
+    /// This works like [`str::to_uppercase()`], but builds the result directly
+    /// in a [`SmartString`], which stays inline if it fits. It has its own name
+    /// so that `to_uppercase()` keeps resolving to the [`str`] method, which
+    /// returns a [`String`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from("Grüße");
+    /// let upper: String = string.to_uppercase_smart();
+    /// assert_eq!("GRÜSSE", upper);
+    /// assert!(upper.is_inline());
+    /// ```
+    pub fn to_uppercase_smart(&self) -> Self {
+        if self.is_ascii() {
+            let mut out = self.clone();
+            out.make_ascii_uppercase();
+            return out;
+        }
+        let mut out = Self::new();
+        out.extend(self.chars().flat_map(char::to_uppercase));
+        out
+    }
+
+    /// Return a lowercase copy of the string as a new [`SmartString`].
+    ///
+    /// This works like [`str::to_lowercase()`], but builds the result directly
+    /// in a [`SmartString`], which stays inline if it fits. It has its own name
+    /// so that `to_lowercase()` keeps resolving to the [`str`] method, which
+    /// returns a [`String`].
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let string = String::from("ΟΔΥΣΣΕΥΣ");
+    /// assert_eq!("οδυσσευς", string.to_lowercase_smart());
+    /// ```
+    pub fn to_lowercase_smart(&self) -> Self {
+        if self.is_ascii() {
+            let mut out = self.clone();
+            out.make_ascii_lowercase();
+            return out;
+        }
+        if self.contains('Σ') {
+            // A capital sigma lowercases differently at the end of a word, which
+            // `str::to_lowercase()` already knows how to handle.
+            return self.as_str().to_lowercase().into();
+        }
+        let mut out = Self::new();
+        out.extend(self.chars().flat_map(char::to_lowercase));
+        out
+    }
+
+    /// Convert the string to its ASCII upper case equivalent in place.
+    ///
+    /// Non-ASCII characters are left unchanged, so the length of the string
+    /// never changes. See [`str::make_ascii_uppercase()`].
+    pub fn make_ascii_uppercase(&mut self) {
+        self.deref_mut().make_ascii_uppercase()
+    }
+
+    /// Convert the string to its ASCII lower case equivalent in place.
+    ///
+    /// Non-ASCII characters are left unchanged, so the length of the string
+    /// never changes. See [`str::make_ascii_lowercase()`].
+    pub fn make_ascii_lowercase(&mut self) {
+        self.deref_mut().make_ascii_lowercase()
+    }
+
+    /// Test whether the string is equal to a [`SmartString`] of any mode.
+    ///
+    /// [`PartialEq`] is only implemented between strings of the same mode,
+    /// because comparing with a string of any mode would leave the compiler
+    /// unable to infer the mode of eg. `string == SmartString::from("x")`.
+    ///
+    /// ```rust
+    /// # use smartstring::{Compact, LazyCompact, SmartString};
+    /// let compact = SmartString::<Compact>::from("hello");
+    /// let lazy = SmartString::<LazyCompact>::from("hello");
+    /// assert!(compact.eq_any_mode(&lazy));
+    /// ```
+    pub fn eq_any_mode<OtherMode: SmartStringMode>(&self, other: &SmartString<OtherMode>) -> bool {
+        match (self.cast(), other.cast()) {
+            (StringCast::Inline(this), StringCast::Inline(that)) => return this.eq_words(that),
+            (StringCast::Boxed(this), StringCast::Boxed(that))
+                if Mode::DIGEST || OtherMode::DIGEST =>
+            {
+                if let (Some(left), Some(right)) = (this.digest(), that.digest()) {
+                    if left != right {
+                        return false;
+                    }
+                }
+            }
+            _ => {}
+        }
+        self.as_str() == other.as_str()
+    }
+
+    /// Compare the string with a [`SmartString`] of any mode, in the same
+    /// order as [`Ord`]. See [`eq_any_mode()`][SmartString::eq_any_mode].
+    ///
+    /// ```rust
+    /// # use smartstring::{Compact, LazyCompact, SmartString};
+    /// # use std::cmp::Ordering;
+    /// let compact = SmartString::<Compact>::from("apple");
+    /// let lazy = SmartString::<LazyCompact>::from("banana");
+    /// assert_eq!(Ordering::Less, compact.cmp_any_mode(&lazy));
+    /// ```
+    pub fn cmp_any_mode<OtherMode: SmartStringMode>(
+        &self,
+        other: &SmartString<OtherMode>,
+    ) -> Ordering {
+        if let (StringCast::Inline(this), StringCast::Inline(that)) = (self.cast(), other.cast()) {
+            return this.cmp_words(that);
+        }
+        self.as_str().cmp(other.as_str())
+    }
+
+    /// Compare the string with another bytewise.
+    ///
+    /// This is the lexicographical order of the strings' UTF-8 bytes, which is
+    /// also the order of their `char`s' code points. It doesn't depend on the
+    /// locale, or any other notion of collation, and it's guaranteed never to
+    /// change in future versions of the crate, so it's safe to rely on for data
+    /// which outlives the program, like the ordering of an on-disk index. The
+    /// [`Ord`] implementation currently gives the same result.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// # use std::cmp::Ordering;
+    /// let string = String::from("Zebra");
+    /// assert_eq!(Ordering::Less, string.cmp_bytes("apple"));
+    /// assert_eq!(Ordering::Greater, string.cmp_bytes("Zeb"));
+    /// ```
+    pub fn cmp_bytes(&self, other: &str) -> Ordering {
+        self.as_bytes().cmp(other.as_bytes())
+    }
+
+    /// Compute a 64-bit fingerprint of the string's contents.
+    ///
+    /// Unlike the [`Hash`] implementation, which depends on the hasher it's fed
+    /// to, this always uses the same algorithm, 64-bit [FNV-1a] over the UTF-8
+    /// bytes of the string, so the result is the same across processes,
+    /// platforms and string modes. The algorithm is part of the API and won't
+    /// change in future versions of the crate.
+    ///
+    /// FNV-1a is fast for short strings but isn't collision resistant, so don't
+    /// use this where an adversary controls the input.
+    ///
+    /// [FNV-1a]: http://www.isthe.com/chongo/tech/comp/fnv/index.html
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// assert_eq!(0x85944171f73967e8, String::from("foobar").fingerprint());
+    /// ```
+    pub fn fingerprint(&self) -> u64 {
+        ops::fnv1a(self.as_bytes())
+    }
+
+    /// Test the string against a list of prefixes.
+    ///
+    /// Returns the index into `needles` of the first needle which is a prefix
+    /// of the string, or `None` if none of them are.
+    ///
+    /// Needles which don't start with the string's first byte are skipped
+    /// without looking any further. The string is then read once, a byte at a
+    /// time, comparing each byte against every remaining needle which has
+    /// matched so far, which makes this cheap to use on dispatch tables with
+    /// many short prefixes. The remaining needles are compared 64 at a time,
+    /// so the string is only read more than once if more than 64 needles
+    /// share its first byte and the first 64 of them don't match.
+    pub fn starts_with_any(&self, needles: &[&str]) -> Option<usize> {
+        ops::first_affix(self.as_bytes(), needles, |bytes, index| bytes[index])
+    }
+
+    /// Test the string against a list of suffixes.
+    ///
+    /// Returns the index into `needles` of the first needle which is a suffix
+    /// of the string, or `None` if none of them are.
+    ///
+    /// Like [`starts_with_any()`][SmartString::starts_with_any], this filters
+    /// the needles by the string's last byte, then reads the string from the
+    /// end.
+    pub fn ends_with_any(&self, needles: &[&str]) -> Option<usize> {
+        ops::first_affix(self.as_bytes(), needles, |bytes, index| {
+            bytes[bytes.len() - 1 - index]
+        })
+    }
 }
 
 impl<Mode: SmartStringMode> Default for SmartString<Mode> {
@@ -646,7 +2645,7 @@ impl<Mode: SmartStringMode> IndexMut<RangeToInclusive<usize>> for SmartString<Mo
 impl<Mode: SmartStringMode> From<&'_ str> for SmartString<Mode> {
     fn from(string: &'_ str) -> Self {
         if string.len() > MAX_INLINE {
-            Self::from_boxed(string.to_string().into())
+            Self::from_boxed(Self::box_str(string, string.len()))
         } else {
             Self::from_inline(string.into())
         }
@@ -656,7 +2655,7 @@ impl<Mode: SmartStringMode> From<&'_ str> for SmartString<Mode> {
 impl<Mode: SmartStringMode> From<&'_ mut str> for SmartString<Mode> {
     fn from(string: &'_ mut str) -> Self {
         if string.len() > MAX_INLINE {
-            Self::from_boxed(string.to_string().into())
+            Self::from_boxed(Self::box_str(string, string.len()))
         } else {
             Self::from_inline(string.deref().into())
         }
@@ -666,7 +2665,7 @@ impl<Mode: SmartStringMode> From<&'_ mut str> for SmartString<Mode> {
 impl<Mode: SmartStringMode> From<&'_ String> for SmartString<Mode> {
     fn from(string: &'_ String) -> Self {
         if string.len() > MAX_INLINE {
-            Self::from_boxed(string.clone().into())
+            Self::from_boxed(Self::box_str(string, string.len()))
         } else {
             Self::from_inline(string.deref().into())
         }
@@ -676,7 +2675,10 @@ impl<Mode: SmartStringMode> From<&'_ String> for SmartString<Mode> {
 impl<Mode: SmartStringMode> From<String> for SmartString<Mode> {
     fn from(string: String) -> Self {
         if string.len() > MAX_INLINE {
-            Self::from_boxed(string.into())
+            // If the buffer can't be taken over, it's copied like any other
+            // promotion.
+            let cap = Self::promotion_capacity(string.capacity());
+            Self::from_boxed(BoxedString::from_string(string, cap))
         } else {
             Self::from_inline(string.deref().into())
         }
@@ -706,56 +2708,71 @@ impl<Mode: SmartStringMode> From<Cow<'_, str>> for SmartString<Mode> {
 
 impl<'a, Mode: SmartStringMode> Extend<&'a str> for SmartString<Mode> {
     fn extend<I: IntoIterator<Item = &'a str>>(&mut self, iter: I) {
+        let mut this = self.begin_bulk_edit();
         for item in iter {
-            self.push_str(item);
+            this.push_str(item);
         }
     }
 }
 
 impl<'a, Mode: SmartStringMode> Extend<&'a char> for SmartString<Mode> {
     fn extend<I: IntoIterator<Item = &'a char>>(&mut self, iter: I) {
+        let mut this = self.begin_bulk_edit();
         for item in iter {
-            self.push(*item);
+            this.push(*item);
         }
     }
 }
 
 impl<Mode: SmartStringMode> Extend<char> for SmartString<Mode> {
+    /// Extend the string with characters from an iterator.
+    ///
+    /// This reserves room for the lower bound of the iterator's
+    /// [`size_hint()`][Iterator::size_hint] up front, counting a byte per
+    /// character, so collecting a long iterator into a string moves it onto
+    /// the heap once with room to spare, instead of a character at a time.
     fn extend<I: IntoIterator<Item = char>>(&mut self, iter: I) {
+        let iter = iter.into_iter();
+        self.reserve(iter.size_hint().0);
+        let mut this = self.begin_bulk_edit();
         for item in iter {
-            self.push(item);
+            this.push(item);
         }
     }
 }
 
 impl<Mode: SmartStringMode> Extend<SmartString<Mode>> for SmartString<Mode> {
     fn extend<I: IntoIterator<Item = SmartString<Mode>>>(&mut self, iter: I) {
+        let mut this = self.begin_bulk_edit();
         for item in iter {
-            self.push_str(&item);
+            this.push_str(&item);
         }
     }
 }
 
 impl<Mode: SmartStringMode> Extend<String> for SmartString<Mode> {
     fn extend<I: IntoIterator<Item = String>>(&mut self, iter: I) {
+        let mut this = self.begin_bulk_edit();
         for item in iter {
-            self.push_str(&item);
+            this.push_str(&item);
         }
     }
 }
 
 impl<'a, Mode: SmartStringMode + 'a> Extend<&'a SmartString<Mode>> for SmartString<Mode> {
     fn extend<I: IntoIterator<Item = &'a SmartString<Mode>>>(&mut self, iter: I) {
+        let mut this = self.begin_bulk_edit();
         for item in iter {
-            self.push_str(item);
+            this.push_str(item);
         }
     }
 }
 
 impl<'a, Mode: SmartStringMode> Extend<&'a String> for SmartString<Mode> {
     fn extend<I: IntoIterator<Item = &'a String>>(&mut self, iter: I) {
+        let mut this = self.begin_bulk_edit();
         for item in iter {
-            self.push_str(item);
+            this.push_str(item);
         }
     }
 }
@@ -800,6 +2817,14 @@ impl<Mode: SmartStringMode> Add<String> for SmartString<Mode> {
     }
 }
 
+impl<Mode: SmartStringMode> Add<char> for SmartString<Mode> {
+    type Output = Self;
+    fn add(mut self, rhs: char) -> Self::Output {
+        self.push(rhs);
+        self
+    }
+}
+
 impl<Mode: SmartStringMode> Add<SmartString<Mode>> for String {
     type Output = Self;
     fn add(mut self, rhs: SmartString<Mode>) -> Self::Output {
@@ -808,10 +2833,46 @@ impl<Mode: SmartStringMode> Add<SmartString<Mode>> for String {
     }
 }
 
+impl<Mode: SmartStringMode> AddAssign<Self> for SmartString<Mode> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.push_str(&rhs);
+    }
+}
+
+impl<Mode: SmartStringMode> AddAssign<&'_ Self> for SmartString<Mode> {
+    fn add_assign(&mut self, rhs: &'_ Self) {
+        self.push_str(rhs);
+    }
+}
+
+impl<Mode: SmartStringMode> AddAssign<&'_ str> for SmartString<Mode> {
+    fn add_assign(&mut self, rhs: &'_ str) {
+        self.push_str(rhs);
+    }
+}
+
+impl<Mode: SmartStringMode> AddAssign<&'_ String> for SmartString<Mode> {
+    fn add_assign(&mut self, rhs: &'_ String) {
+        self.push_str(rhs);
+    }
+}
+
+impl<Mode: SmartStringMode> AddAssign<String> for SmartString<Mode> {
+    fn add_assign(&mut self, rhs: String) {
+        self.push_str(&rhs);
+    }
+}
+
+impl<Mode: SmartStringMode> AddAssign<char> for SmartString<Mode> {
+    fn add_assign(&mut self, rhs: char) {
+        self.push(rhs);
+    }
+}
+
 impl<Mode: SmartStringMode> FromIterator<Self> for SmartString<Mode> {
     fn from_iter<I: IntoIterator<Item = Self>>(iter: I) -> Self {
         let mut out = Self::new();
-        out.extend(iter.into_iter());
+        out.extend(iter);
         out
     }
 }
@@ -819,7 +2880,7 @@ impl<Mode: SmartStringMode> FromIterator<Self> for SmartString<Mode> {
 impl<Mode: SmartStringMode> FromIterator<String> for SmartString<Mode> {
     fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
         let mut out = Self::new();
-        out.extend(iter.into_iter());
+        out.extend(iter);
         out
     }
 }
@@ -827,7 +2888,7 @@ impl<Mode: SmartStringMode> FromIterator<String> for SmartString<Mode> {
 impl<'a, Mode: SmartStringMode + 'a> FromIterator<&'a Self> for SmartString<Mode> {
     fn from_iter<I: IntoIterator<Item = &'a Self>>(iter: I) -> Self {
         let mut out = Self::new();
-        out.extend(iter.into_iter());
+        out.extend(iter);
         out
     }
 }
@@ -835,7 +2896,7 @@ impl<'a, Mode: SmartStringMode + 'a> FromIterator<&'a Self> for SmartString<Mode
 impl<'a, Mode: SmartStringMode> FromIterator<&'a str> for SmartString<Mode> {
     fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
         let mut out = Self::new();
-        out.extend(iter.into_iter());
+        out.extend(iter);
         out
     }
 }
@@ -843,7 +2904,7 @@ impl<'a, Mode: SmartStringMode> FromIterator<&'a str> for SmartString<Mode> {
 impl<'a, Mode: SmartStringMode> FromIterator<&'a String> for SmartString<Mode> {
     fn from_iter<I: IntoIterator<Item = &'a String>>(iter: I) -> Self {
         let mut out = Self::new();
-        out.extend(iter.into_iter());
+        out.extend(iter);
         out
     }
 }
@@ -851,9 +2912,7 @@ impl<'a, Mode: SmartStringMode> FromIterator<&'a String> for SmartString<Mode> {
 impl<Mode: SmartStringMode> FromIterator<char> for SmartString<Mode> {
     fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
         let mut out = Self::new();
-        for ch in iter {
-            out.push(ch);
-        }
+        out.extend(iter);
         out
     }
 }
@@ -865,6 +2924,22 @@ impl<Mode: SmartStringMode> FromStr for SmartString<Mode> {
     }
 }
 
+impl From<SmartString<Compact>> for SmartString<LazyCompact> {
+    /// Convert a [`Compact`] string into a [`LazyCompact`] one, keeping its
+    /// heap buffer if it has one.
+    fn from(string: SmartString<Compact>) -> Self {
+        string.convert()
+    }
+}
+
+impl From<SmartString<LazyCompact>> for SmartString<Compact> {
+    /// Convert a [`LazyCompact`] string into a [`Compact`] one, keeping its
+    /// heap buffer if it has one, unless it's short enough to move inline.
+    fn from(string: SmartString<LazyCompact>) -> Self {
+        string.convert()
+    }
+}
+
 impl<Mode: SmartStringMode> From<SmartString<Mode>> for String {
     /// Unwrap a boxed [`String`][String], or copy an inline string into a new [`String`][String].
     ///
@@ -877,6 +2952,42 @@ impl<Mode: SmartStringMode> From<SmartString<Mode>> for String {
     }
 }
 
+#[cfg(feature = "std")]
+impl<Mode: SmartStringMode> From<SmartString<Mode>> for PathBuf {
+    /// Convert a [`SmartString`] into a [`PathBuf`].
+    ///
+    /// This reuses the heap buffer of a boxed string where the conversion into
+    /// [`String`] can.
+    fn from(s: SmartString<Mode>) -> Self {
+        String::from(s).into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Mode: SmartStringMode> From<SmartString<Mode>> for OsString {
+    /// Convert a [`SmartString`] into an [`OsString`].
+    ///
+    /// This reuses the heap buffer of a boxed string where the conversion into
+    /// [`String`] can.
+    fn from(s: SmartString<Mode>) -> Self {
+        String::from(s).into()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Mode: SmartStringMode> AsRef<Path> for SmartString<Mode> {
+    fn as_ref(&self) -> &Path {
+        Path::new(self.as_str())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<Mode: SmartStringMode> AsRef<OsStr> for SmartString<Mode> {
+    fn as_ref(&self) -> &OsStr {
+        OsStr::new(self.as_str())
+    }
+}
+
 impl<Mode: SmartStringMode> PartialEq<str> for SmartString<Mode> {
     fn eq(&self, other: &str) -> bool {
         self.as_str() == other
@@ -915,7 +3026,7 @@ impl<Mode: SmartStringMode> PartialEq<SmartString<Mode>> for String {
 
 impl<Mode: SmartStringMode> PartialEq for SmartString<Mode> {
     fn eq(&self, other: &Self) -> bool {
-        self.as_str() == other.as_str()
+        self.eq_any_mode(other)
     }
 }
 
@@ -929,17 +3040,26 @@ impl<Mode: SmartStringMode> PartialOrd<str> for SmartString<Mode> {
 
 impl<Mode: SmartStringMode> PartialOrd for SmartString<Mode> {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        self.partial_cmp(other.as_str())
+        Some(self.cmp(other))
     }
 }
 
 impl<Mode: SmartStringMode> Ord for SmartString<Mode> {
+    /// Compare two [`SmartString`]s bytewise, in the same order as
+    /// [`cmp_bytes()`][SmartString::cmp_bytes].
     fn cmp(&self, other: &Self) -> Ordering {
-        self.as_str().cmp(other.as_str())
+        self.cmp_any_mode(other)
     }
 }
 
 impl<Mode: SmartStringMode> Hash for SmartString<Mode> {
+    /// Hash a [`SmartString`].
+    ///
+    /// This is guaranteed to produce the same hash as the equivalent [`str`],
+    /// regardless of mode or representation, so a [`SmartString`] keyed map can
+    /// always be queried using a `&str`. If you probe a map with the same key
+    /// repeatedly, consider [`HashedSmartString`], and for maps keyed by lots
+    /// of short strings, consider [`WordKeyed`].
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.as_str().hash(state)
     }
@@ -964,6 +3084,29 @@ impl<Mode: SmartStringMode> Write for SmartString<Mode> {
     }
 }
 
+/// A trait for converting a value to a [`SmartString`].
+///
+/// This is the [`SmartString`] equivalent of [`ToString`], and is implemented
+/// for every type which implements [`Display`], using
+/// [`SmartString::from_display()`].
+///
+/// ```rust
+/// # use smartstring::{ToSmartString, alias::String};
+/// let string: String = 1337.to_smart_string();
+/// assert_eq!("1337", string);
+/// assert!(string.is_inline());
+/// ```
+pub trait ToSmartString {
+    /// Convert a value to a [`SmartString`].
+    fn to_smart_string<Mode: SmartStringMode>(&self) -> SmartString<Mode>;
+}
+
+impl<T: Display + ?Sized> ToSmartString for T {
+    fn to_smart_string<Mode: SmartStringMode>(&self) -> SmartString<Mode> {
+        SmartString::from_display(self)
+    }
+}
+
 #[cfg(any(test, feature = "test"))]
 #[allow(missing_docs)]
 pub mod test;
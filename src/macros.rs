@@ -0,0 +1,267 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+/// Write formatted text to a [`SmartString`][crate::SmartString], reserving
+/// room for all of it first.
+///
+/// This takes the same arguments as [`write!`], but formats the text twice: once
+/// to measure it, and then again to write it, after
+/// [reserving][crate::SmartString::reserve] exactly enough room for it. That's
+/// cheaper than reallocating as the string grows when the arguments are quick
+/// to format, and the output is too long to inline. It evaluates to a
+/// [`fmt::Result`][core::fmt::Result], which is an error if any of the
+/// arguments' formatting implementations return one.
+///
+/// See [`SmartString::write_fmt_sized()`][crate::SmartString::write_fmt_sized].
+///
+/// ```rust
+/// # use smartstring::{alias::String, write_smart};
+/// let mut line = String::new();
+/// write_smart!(line, "{}={} ", "status", 200).unwrap();
+/// write_smart!(&mut line, "path={:?}", "/some/fairly/long/request/path").unwrap();
+/// assert_eq!("status=200 path=\"/some/fairly/long/request/path\"", line);
+/// ```
+#[macro_export]
+macro_rules! write_smart {
+    ($dst:expr, $($arg:tt)*) => {
+        $dst.write_fmt_sized(::core::format_args!($($arg)*))
+    };
+}
+
+/// Make an inline [`SmartString`][crate::SmartString] from a string literal,
+/// failing to compile if the literal is too long to be inlined.
+///
+/// The limit is [`MAX_INLINE`][crate::MAX_INLINE] for the target you're
+/// compiling for, which is 23 bytes on 64-bit targets but only 11 bytes on
+/// 32-bit targets like `wasm32`. Write `inline_str!(portable: "...")` to hold
+/// the literal to the 32-bit limit on every target, so that a string which
+/// inlines on your machine doesn't quietly allocate on another.
+///
+/// ```rust
+/// # use smartstring::{inline_str, alias::CompactString};
+/// let greeting: CompactString = inline_str!("hello");
+/// assert!(greeting.is_inline());
+/// let status: CompactString = inline_str!(portable: "not found");
+/// assert_eq!("not found", status);
+/// ```
+///
+/// A literal which doesn't fit is a compile error:
+///
+/// ```compile_fail
+/// # use smartstring::{inline_str, alias::CompactString};
+/// let string: CompactString = inline_str!("a literal much too long to inline");
+/// ```
+///
+/// ```compile_fail
+/// # use smartstring::{inline_str, alias::CompactString};
+/// let string: CompactString = inline_str!(portable: "twelve bytes");
+/// ```
+#[macro_export]
+macro_rules! inline_str {
+    (portable: $string:expr) => {{
+        const STRING: &str = $string;
+        const _: () = ::core::assert!(
+            STRING.len() <= $crate::__private::PORTABLE_MAX_INLINE,
+            "string literal is too long to be inlined on every target"
+        );
+        $crate::SmartString::from(STRING)
+    }};
+    ($string:expr) => {{
+        const STRING: &str = $string;
+        const _: () = ::core::assert!(
+            STRING.len() <= $crate::MAX_INLINE,
+            "string literal is too long to be inlined on this target"
+        );
+        $crate::SmartString::from(STRING)
+    }};
+}
+
+/// Define a newtype wrapper around a [`SmartString`][crate::SmartString].
+///
+/// ```rust
+/// # use smartstring::{newtype, LazyCompact, SmartString};
+/// newtype! {
+///     /// A user's unique name.
+///     pub struct UserId(SmartString<LazyCompact>);
+/// }
+///
+/// let string: SmartString<LazyCompact> = "bodil".into();
+/// let id: &UserId = UserId::from_ref(&string);
+/// assert_eq!("bodil", id);
+/// assert_eq!(UserId::from("bodil"), *id);
+/// assert_eq!(5, id.len());
+/// ```
+///
+/// This declares the struct as `#[repr(transparent)]`, so a reference to the
+/// string can be converted into a reference to the newtype for free, with
+/// `from_ref()` and `from_mut()`, and implements for it:
+///
+/// * [`Clone`], [`Default`], [`PartialEq`], [`Eq`], [`PartialOrd`], [`Ord`] and
+///   [`Hash`][core::hash::Hash], all by way of the string, so the newtype
+///   compares and hashes like a [`str`] the same way a `SmartString` does;
+/// * `PartialEq<str>` and `PartialEq<&str>`;
+/// * [`Deref`][core::ops::Deref] to the string, but not `DerefMut`, so the only
+///   ways to modify the contents are ones you add yourself;
+/// * [`AsRef<str>`][AsRef] and [`Borrow<str>`][core::borrow::Borrow], so the
+///   newtype can be looked up by `&str` in maps and sets;
+/// * [`From`] the string, a `&str` and a [`String`], and into the string;
+/// * [`Display`][core::fmt::Display] and [`Debug`][core::fmt::Debug], which
+///   format just the string;
+/// * if this crate's `serde` feature is enabled, `Serialize` and `Deserialize`,
+///   as the string.
+///
+/// The conversions between references use `unsafe` code, so the macro can't
+/// be used in a crate which forbids it.
+#[macro_export]
+macro_rules! newtype {
+    ($(#[$attr:meta])* $vis:vis struct $name:ident($inner_vis:vis $inner:ty);) => {
+        $(#[$attr])*
+        #[repr(transparent)]
+        #[derive(Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        $vis struct $name($inner_vis $inner);
+
+        impl $name {
+            /// Convert a reference to the string into a reference to the newtype.
+            #[allow(dead_code)]
+            $vis fn from_ref(string: &$inner) -> &Self {
+                // The newtype is `repr(transparent)`.
+                #[allow(unsafe_code)]
+                unsafe {
+                    &*(string as *const $inner as *const Self)
+                }
+            }
+
+            /// Convert a mutable reference to the string into a mutable
+            /// reference to the newtype.
+            #[allow(dead_code)]
+            $vis fn from_mut(string: &mut $inner) -> &mut Self {
+                #[allow(unsafe_code)]
+                unsafe {
+                    &mut *(string as *mut $inner as *mut Self)
+                }
+            }
+
+            /// Unwrap the string.
+            #[allow(dead_code)]
+            $vis fn into_inner(self) -> $inner {
+                self.0
+            }
+        }
+
+        impl ::core::ops::Deref for $name {
+            type Target = $inner;
+
+            fn deref(&self) -> &Self::Target {
+                &self.0
+            }
+        }
+
+        impl ::core::convert::AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        impl ::core::borrow::Borrow<str> for $name {
+            fn borrow(&self) -> &str {
+                self.0.as_str()
+            }
+        }
+
+        impl ::core::convert::From<$inner> for $name {
+            fn from(string: $inner) -> Self {
+                Self(string)
+            }
+        }
+
+        impl ::core::convert::From<&str> for $name {
+            fn from(string: &str) -> Self {
+                Self(string.into())
+            }
+        }
+
+        impl ::core::convert::From<$crate::__private::String> for $name {
+            fn from(string: $crate::__private::String) -> Self {
+                Self(string.into())
+            }
+        }
+
+        impl ::core::convert::From<$name> for $inner {
+            fn from(newtype: $name) -> Self {
+                newtype.0
+            }
+        }
+
+        impl ::core::cmp::PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0.as_str() == other
+            }
+        }
+
+        impl ::core::cmp::PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0.as_str() == *other
+            }
+        }
+
+        impl ::core::cmp::PartialEq<$name> for str {
+            fn eq(&self, other: &$name) -> bool {
+                self == other.0.as_str()
+            }
+        }
+
+        impl ::core::cmp::PartialEq<$name> for &str {
+            fn eq(&self, other: &$name) -> bool {
+                *self == other.0.as_str()
+            }
+        }
+
+        impl ::core::fmt::Display for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Display::fmt(&self.0, f)
+            }
+        }
+
+        impl ::core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                ::core::fmt::Debug::fmt(&self.0, f)
+            }
+        }
+
+        $crate::__newtype_serde!($name, $inner);
+    };
+}
+
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __newtype_serde {
+    ($name:ident, $inner:ty) => {
+        impl $crate::__private::serde::Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::serde::Serializer,
+            {
+                $crate::__private::serde::Serialize::serialize(&self.0, serializer)
+            }
+        }
+
+        impl<'de> $crate::__private::serde::Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::serde::Deserializer<'de>,
+            {
+                <$inner as $crate::__private::serde::Deserialize<'de>>::deserialize(deserializer)
+                    .map(Self)
+            }
+        }
+    };
+}
+
+#[cfg(not(feature = "serde"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __newtype_serde {
+    ($name:ident, $inner:ty) => {};
+}
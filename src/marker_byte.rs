@@ -30,12 +30,33 @@ impl Discriminant {
 #[derive(Clone, Copy, Debug)]
 pub(crate) struct Marker(u8);
 
+#[cfg(feature = "debug-canary")]
+const DATA_MASK: u8 = 0x1f;
+
+#[cfg(not(feature = "debug-canary"))]
+const DATA_MASK: u8 = 0x7f;
+
+/// With `debug-canary` enabled, the length only gets the low five bits of the
+/// marker's data, and the remaining two bits hold the length folded over itself
+/// and inverted, so that a zeroed or overwritten marker is likely to be caught.
+#[cfg(feature = "debug-canary")]
+const fn canary(data: u8) -> u8 {
+    !(data ^ (data >> 2) ^ (data >> 4)) & 0x03
+}
+
 impl Marker {
+    #[cfg(not(feature = "debug-canary"))]
     #[inline(always)]
     const fn assemble(discriminant: Discriminant, data: u8) -> u8 {
         data << 1 | discriminant.bit()
     }
 
+    #[cfg(feature = "debug-canary")]
+    #[inline(always)]
+    const fn assemble(discriminant: Discriminant, data: u8) -> u8 {
+        (canary(data) << 5 | data) << 1 | discriminant.bit()
+    }
+
     #[inline(always)]
     pub(crate) const fn empty() -> Self {
         Self(Self::assemble(Discriminant::Inline, 0))
@@ -43,7 +64,7 @@ impl Marker {
 
     #[inline(always)]
     pub(crate) const fn new_inline(data: u8) -> Self {
-        debug_assert!(data < 0x80);
+        debug_assert!(data <= DATA_MASK);
         Self(Self::assemble(Discriminant::Inline, data))
     }
 
@@ -54,12 +75,17 @@ impl Marker {
 
     #[inline(always)]
     pub(crate) const fn data(self) -> u8 {
-        self.0 >> 1
+        (self.0 >> 1) & DATA_MASK
+    }
+
+    #[cfg(feature = "debug-canary")]
+    pub(crate) const fn canary_holds(self) -> bool {
+        self.0 >> 6 == canary(self.data())
     }
 
     #[inline(always)]
     pub(crate) fn set_data(&mut self, byte: u8) {
-        debug_assert!(byte < 0x80);
+        debug_assert!(byte <= DATA_MASK);
         self.0 = Self::assemble(self.discriminant(), byte);
     }
 }
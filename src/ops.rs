@@ -8,11 +8,15 @@
 //! string, and should have a `cap` method which will return the new
 //! minimum required capacity.
 //!
+//! `string_op_try_grow` is the same as `string_op_grow`, except it returns
+//! a `Result` with a `TryReserveError` rather than aborting if allocation fails.
+//!
 //! `string_op_shrink` is for ops which may shrinl but not grow the target
 //! string. They don't need a `cap` method, and will try to demote the
 //! string as appropriate after calling `op`.
 
 use core::{
+    fmt::{Alignment, Display, Formatter},
     marker::PhantomData,
     ops::{Bound, Deref, DerefMut, RangeBounds},
 };
@@ -32,7 +36,7 @@ macro_rules! string_op_grow {
             StringCastMut::Inline(this) => {
                 let new_size = <$action>::cap(this,$($arg),*);
                 if new_size > MAX_INLINE {
-                    let mut new_str = BoxedString::from_str(new_size, this);
+                    let mut new_str = Self::box_str(this, new_size);
                     let result = <$action>::op(&mut new_str, $($arg),*);
                     $target.promote_from(new_str);
                     result
@@ -45,6 +49,29 @@ macro_rules! string_op_grow {
 }
 pub(crate) use string_op_grow;
 
+macro_rules! string_op_try_grow {
+    ($action:ty, $target:ident, $($arg:expr),*) => {
+        match $target.cast_mut() {
+            StringCastMut::Boxed(this) => {
+                this.try_ensure_capacity(<$action>::cap(this, $($arg),*))?;
+                Ok(<$action>::op(this, $($arg),*))
+            }
+            StringCastMut::Inline(this) => {
+                let new_size = <$action>::cap(this,$($arg),*);
+                if new_size > MAX_INLINE {
+                    let mut new_str = BoxedString::try_from_str(Self::promotion_capacity(new_size), this)?;
+                    let result = <$action>::op(&mut new_str, $($arg),*);
+                    $target.promote_from(new_str);
+                    Ok(result)
+                } else {
+                    Ok(<$action>::op(this, $($arg),*))
+                }
+            }
+        }
+    };
+}
+pub(crate) use string_op_try_grow;
+
 macro_rules! string_op_shrink {
     ($action:ty, $target:ident, $($arg:expr),*) => {{
         let result = match $target.cast_mut() {
@@ -67,17 +94,261 @@ pub(crate) use string_op_shrink;
 
 use crate::{SmartString, SmartStringMode};
 
+/// The error returned by the `checked_*` methods on [`SmartString`] when
+/// they're given an invalid index or range.
+///
+/// These are the conditions their unchecked counterparts panic on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OpError {
+    /// An index was past the end of the string.
+    OutOfBounds {
+        /// The offending index.
+        index: usize,
+        /// The length of the string.
+        len: usize,
+    },
+    /// An index fell inside a UTF-8 encoded `char`.
+    NotCharBoundary {
+        /// The offending index.
+        index: usize,
+    },
+    /// A range started after it ended.
+    InvalidRange {
+        /// The start of the range.
+        start: usize,
+        /// The end of the range.
+        end: usize,
+    },
+}
+
+impl Display for OpError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            Self::OutOfBounds { index, len } => write!(
+                f,
+                "byte index {} is out of bounds for a string of length {}",
+                index, len
+            ),
+            Self::NotCharBoundary { index } => {
+                write!(f, "byte index {} is not a char boundary", index)
+            }
+            Self::InvalidRange { start, end } => {
+                write!(f, "slice index starts at {} but ends at {}", start, end)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for OpError {}
+
+/// The error returned by [`SmartString::from_ascii()`] and
+/// [`SmartString::push_ascii_bytes()`] when they're given bytes which aren't
+/// ASCII.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NotAscii {
+    pub(crate) valid_up_to: usize,
+}
+
+impl NotAscii {
+    /// The index of the first byte which isn't ASCII.
+    pub fn valid_up_to(&self) -> usize {
+        self.valid_up_to
+    }
+}
+
+impl Display for NotAscii {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "byte at index {} is not ASCII", self.valid_up_to)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NotAscii {}
+
+/// 64-bit FNV-1a, as used by [`SmartString::fingerprint()`][crate::SmartString::fingerprint].
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+/// Find the index of the first of `needles` which matches the start or end of
+/// `haystack`, depending on whether `byte(bytes, index)` counts its `index`
+/// from the start or the end of `bytes`.
+///
+/// Needles whose first byte doesn't match the haystack's are filtered out up
+/// front. The haystack is then walked once per block of 64 remaining
+/// candidates, keeping a bit for each candidate in the block which has matched
+/// so far. Once a candidate matches in full, the ones after it can't be the
+/// first match any more, so they're dropped too.
+pub(crate) fn first_affix(
+    haystack: &[u8],
+    needles: &[&str],
+    byte: impl Fn(&[u8], usize) -> u8,
+) -> Option<usize> {
+    const BLOCK: usize = u64::BITS as usize;
+    let first = haystack.first().map(|_| byte(haystack, 0));
+    let mut candidates = needles
+        .iter()
+        .map(|needle| needle.as_bytes())
+        .enumerate()
+        .filter(|(_, needle)| needle.is_empty() || Some(byte(needle, 0)) == first);
+    let mut block: [(usize, &[u8]); BLOCK] = [(0, &[]); BLOCK];
+    loop {
+        let mut count = 0;
+        for candidate in candidates.by_ref().take(BLOCK) {
+            block[count] = candidate;
+            count += 1;
+        }
+        if count == 0 {
+            return None;
+        }
+        let mut live = u64::MAX >> (BLOCK - count);
+        let mut found = None;
+        let mut index = 0;
+        while live != 0 {
+            let mut rest = live;
+            while rest != 0 {
+                let candidate = rest.trailing_zeros() as usize;
+                rest &= rest - 1;
+                let bytes = block[candidate].1;
+                if bytes.len() == index {
+                    found = Some(candidate);
+                    live &= (1 << candidate) - 1;
+                    break;
+                }
+                if index == haystack.len() || byte(bytes, index) != byte(haystack, index) {
+                    live &= !(1 << candidate);
+                }
+            }
+            index += 1;
+        }
+        if let Some(candidate) = found {
+            return Some(block[candidate].0);
+        }
+    }
+}
+
+/// Check that `bytes` are all ASCII, and so valid UTF-8.
+pub(crate) fn check_ascii(bytes: &[u8]) -> Result<(), NotAscii> {
+    if bytes.is_ascii() {
+        Ok(())
+    } else {
+        Err(NotAscii {
+            valid_up_to: bytes.iter().position(|byte| !byte.is_ascii()).unwrap(),
+        })
+    }
+}
+
+/// Unwrap the result of a validation, panicking with the error's description
+/// if it failed. This is how the panicking ops share their validation with the
+/// `checked_*` methods.
+#[inline]
+pub(crate) fn or_panic<A>(result: Result<A, OpError>) -> A {
+    match result {
+        Ok(value) => value,
+        Err(error) => fail(error),
+    }
+}
+
+/// Panic with the description of `error`, kept out of line so that every
+/// caller doesn't carry its own copy of the formatting code.
+#[cold]
+#[inline(never)]
+fn fail(error: OpError) -> ! {
+    panic!("{}", error)
+}
+
+/// Unwrap the result of [`check_range()`], panicking with the same message as
+/// `String` does for a range past the end of the string.
+pub(crate) fn range_or_panic(result: Result<(usize, usize), OpError>) -> (usize, usize) {
+    match result {
+        Ok(bounds) => bounds,
+        Err(OpError::OutOfBounds { index, len }) => panic!(
+            "range end index {} out of range for slice of length {}",
+            index, len
+        ),
+        Err(error) => fail(error),
+    }
+}
+
+/// Check that `index` is a char boundary within `string`, which includes its end.
+#[inline]
+pub(crate) fn check_index(string: &str, index: usize) -> Result<(), OpError> {
+    if index > string.len() {
+        Err(OpError::OutOfBounds {
+            index,
+            len: string.len(),
+        })
+    } else if !string.is_char_boundary(index) {
+        Err(OpError::NotCharBoundary { index })
+    } else {
+        Ok(())
+    }
+}
+
+/// Check that `index` is the start of a `char` in `string`, which excludes its end.
+#[inline]
+pub(crate) fn check_char_index(string: &str, index: usize) -> Result<(), OpError> {
+    if index == string.len() {
+        Err(OpError::OutOfBounds {
+            index,
+            len: string.len(),
+        })
+    } else {
+        check_index(string, index)
+    }
+}
+
+/// Check that `range` is a valid range of `string`, returning its start and end.
+pub(crate) fn check_range<R>(string: &str, range: &R) -> Result<(usize, usize), OpError>
+where
+    R: RangeBounds<usize>,
+{
+    let len = string.len();
+    let overflow = OpError::OutOfBounds {
+        index: usize::MAX,
+        len,
+    };
+    let start = match range.start_bound() {
+        Bound::Included(&n) => n,
+        Bound::Excluded(&n) => n.checked_add(1).ok_or(overflow)?,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&n) => n.checked_add(1).ok_or(overflow)?,
+        Bound::Excluded(&n) => n,
+        Bound::Unbounded => len,
+    };
+    if end > len {
+        return Err(OpError::OutOfBounds { index: end, len });
+    }
+    if start > end {
+        return Err(OpError::InvalidRange { start, end });
+    }
+    check_index(string, start)?;
+    check_index(string, end)?;
+    Ok((start, end))
+}
+
 pub(crate) fn bounds_for<R>(range: &R, max_len: usize) -> (usize, usize)
 where
     R: RangeBounds<usize>,
 {
     let start = match range.start_bound() {
         Bound::Included(&n) => n,
-        Bound::Excluded(&n) => n.checked_add(1).unwrap(),
+        Bound::Excluded(&n) => n
+            .checked_add(1)
+            .expect("attempted to index string from after maximum usize"),
         Bound::Unbounded => 0,
     };
     let end = match range.end_bound() {
-        Bound::Included(&n) => n.checked_add(1).unwrap(),
+        Bound::Included(&n) => n
+            .checked_add(1)
+            .expect("attempted to index string up to maximum usize"),
         Bound::Excluded(&n) => n,
         Bound::Unbounded => max_len,
     };
@@ -96,12 +367,36 @@ fn insert_bytes<S: GenericString>(this: &mut S, index: usize, src: &[u8]) {
     }
 }
 
+/// Encode `chars` onto the end of the string until its capacity runs out,
+/// returning the first char which didn't fit, if any.
+pub(crate) fn push_chars_within_capacity<S: GenericString>(
+    this: &mut S,
+    chars: &mut impl Iterator<Item = char>,
+) -> Option<char> {
+    let mut len = this.len();
+    let buf = this.as_mut_capacity_slice();
+    let mut overflow = None;
+    for ch in chars {
+        let end = len + ch.len_utf8();
+        if end > buf.len() {
+            overflow = Some(ch);
+            break;
+        }
+        ch.encode_utf8(&mut buf[len..end]);
+        len = end;
+    }
+    this.set_size(len);
+    overflow
+}
+
 pub(crate) struct PushStr;
 impl PushStr {
+    #[inline]
     pub(crate) fn cap<S: GenericString>(this: &S, string: &str) -> usize {
         this.len() + string.len()
     }
 
+    #[inline]
     pub(crate) fn op<S: GenericString>(this: &mut S, string: &str) {
         let len = this.len();
         let new_len = len + string.len();
@@ -112,10 +407,12 @@ impl PushStr {
 
 pub(crate) struct Push;
 impl Push {
+    #[inline]
     pub(crate) fn cap<S: GenericString>(this: &S, ch: char) -> usize {
         this.len() + ch.len_utf8()
     }
 
+    #[inline]
     pub(crate) fn op<S: GenericString>(this: &mut S, ch: char) {
         let len = this.len();
         let written = ch
@@ -125,11 +422,41 @@ impl Push {
     }
 }
 
+pub(crate) struct PushLine;
+impl PushLine {
+    #[inline]
+    pub(crate) fn cap<S: GenericString>(this: &S, line: &str) -> usize {
+        this.len() + line.len() + 1
+    }
+
+    #[inline]
+    pub(crate) fn op<S: GenericString>(this: &mut S, line: &str) {
+        PushStr::op(this, line);
+        PushByte::op(this, b'\n');
+    }
+}
+
+pub(crate) struct PushByte;
+impl PushByte {
+    #[inline]
+    pub(crate) fn cap<S: GenericString>(this: &S, _byte: u8) -> usize {
+        this.len() + 1
+    }
+
+    #[inline]
+    pub(crate) fn op<S: GenericString>(this: &mut S, byte: u8) {
+        let len = this.len();
+        this.as_mut_capacity_slice()[len] = byte;
+        this.set_size(len + 1);
+    }
+}
+
 pub(crate) struct Truncate;
 impl Truncate {
+    #[inline]
     pub(crate) fn op<S: GenericString>(this: &mut S, new_len: usize) {
         if new_len < this.len() {
-            assert!(this.deref().is_char_boundary(new_len));
+            or_panic(check_index(this, new_len));
             this.set_size(new_len)
         }
     }
@@ -137,20 +464,70 @@ impl Truncate {
 
 pub(crate) struct Pop;
 impl Pop {
+    #[inline]
     pub(crate) fn op<S: GenericString>(this: &mut S) -> Option<char> {
-        let ch = this.deref().chars().rev().next()?;
+        let ch = this.deref().chars().next_back()?;
         this.set_size(this.len() - ch.len_utf8());
         Some(ch)
     }
 }
 
+pub(crate) struct TrimStart;
+impl TrimStart {
+    pub(crate) fn op<S: GenericString>(this: &mut S) {
+        let len = this.len();
+        let new_len = this.deref().trim_start().len();
+        if new_len < len {
+            this.as_mut_capacity_slice()
+                .copy_within(len - new_len..len, 0);
+            this.set_size(new_len);
+        }
+    }
+}
+
+pub(crate) struct TrimEnd;
+impl TrimEnd {
+    #[inline]
+    pub(crate) fn op<S: GenericString>(this: &mut S) {
+        let new_len = this.deref().trim_end().len();
+        this.set_size(new_len);
+    }
+}
+
+pub(crate) struct Trim;
+impl Trim {
+    #[inline]
+    pub(crate) fn op<S: GenericString>(this: &mut S) {
+        TrimEnd::op(this);
+        TrimStart::op(this);
+    }
+}
+
+pub(crate) struct PopN<Mode: SmartStringMode>(PhantomData<Mode>);
+impl<Mode: SmartStringMode> PopN<Mode> {
+    pub(crate) fn op<S: GenericString>(this: &mut S, count: usize) -> SmartString<Mode> {
+        let index = match count.checked_sub(1) {
+            None => this.len(),
+            Some(skip) => this
+                .deref()
+                .char_indices()
+                .nth_back(skip)
+                .map_or(0, |(index, _)| index),
+        };
+        let result = this.deref()[index..].into();
+        this.set_size(index);
+        result
+    }
+}
+
 pub(crate) struct Remove;
 impl Remove {
     pub(crate) fn op<S: GenericString>(this: &mut S, index: usize) -> char {
-        let ch = match this.deref()[index..].chars().next() {
-            Some(ch) => ch,
-            None => panic!("cannot remove a char from the end of a string"),
-        };
+        if index == this.len() {
+            panic!("cannot remove a char from the end of a string");
+        }
+        or_panic(check_char_index(this, index));
+        let ch = this.deref()[index..].chars().next().unwrap();
         let next = index + ch.len_utf8();
         let len = this.len();
         let tail_len = len - next;
@@ -164,11 +541,13 @@ impl Remove {
 
 pub(crate) struct Insert;
 impl Insert {
+    #[inline]
     pub(crate) fn cap<S: GenericString>(this: &S, index: usize, ch: char) -> usize {
-        assert!(this.deref().is_char_boundary(index));
+        or_panic(check_index(this, index));
         this.len() + ch.len_utf8()
     }
 
+    #[inline]
     pub(crate) fn op<S: GenericString>(this: &mut S, index: usize, ch: char) {
         let mut buffer = [0; 4];
         let buffer = ch.encode_utf8(&mut buffer).as_bytes();
@@ -178,11 +557,13 @@ impl Insert {
 
 pub(crate) struct InsertStr;
 impl InsertStr {
+    #[inline]
     pub(crate) fn cap<S: GenericString>(this: &S, index: usize, string: &str) -> usize {
-        assert!(this.deref().is_char_boundary(index));
+        or_panic(check_index(this, index));
         this.len() + string.len()
     }
 
+    #[inline]
     pub(crate) fn op<S: GenericString>(this: &mut S, index: usize, string: &str) {
         insert_bytes(this, index, string.as_bytes());
     }
@@ -191,13 +572,28 @@ impl InsertStr {
 pub(crate) struct SplitOff<Mode: SmartStringMode>(PhantomData<Mode>);
 impl<Mode: SmartStringMode> SplitOff<Mode> {
     pub(crate) fn op<S: GenericString>(this: &mut S, index: usize) -> SmartString<Mode> {
-        assert!(this.deref().is_char_boundary(index));
+        or_panic(check_index(this, index));
         let result = this.deref()[index..].into();
         this.set_size(index);
         result
     }
 }
 
+/// Decode the `char` starting at `index` in a buffer which may not be valid UTF-8
+/// outside of that `char`.
+fn char_at(buf: &[u8], index: usize) -> char {
+    let width = match buf[index] {
+        0x00..=0x7f => 1,
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    };
+    core::str::from_utf8(&buf[index..index + width])
+        .ok()
+        .and_then(|s| s.chars().next())
+        .unwrap()
+}
+
 pub(crate) struct Retain;
 impl Retain {
     pub(crate) fn op<F, S>(this: &mut S, mut f: F)
@@ -242,11 +638,17 @@ impl ReplaceRange {
         S: GenericString,
     {
         let len = this.len();
-        let (start, end) = bounds_for(range, len);
-        assert!(end >= start);
-        assert!(end <= len);
-        assert!(this.deref().is_char_boundary(start));
-        assert!(this.deref().is_char_boundary(end));
+        let (start, end) = match check_range(this, range) {
+            // `String::replace_range()` words its boundary failures differently
+            // from slicing. The bounds are valid by the time they're checked.
+            Err(OpError::NotCharBoundary { index }) if index == bounds_for(range, len).0 => {
+                panic!("start of range should be a character boundary")
+            }
+            Err(OpError::NotCharBoundary { .. }) => {
+                panic!("end of range should be a character boundary")
+            }
+            result => range_or_panic(result),
+        };
         let replace_len = replace_with.len();
         let end_size = len - end;
         start + replace_len + end_size
@@ -269,3 +671,88 @@ impl ReplaceRange {
         this.set_size(start + replace_len + end_size);
     }
 }
+
+pub(crate) struct PadTo;
+impl PadTo {
+    fn padding<S: GenericString>(this: &S, width: usize) -> usize {
+        width.saturating_sub(this.deref().chars().count())
+    }
+
+    pub(crate) fn cap<S: GenericString>(
+        this: &S,
+        width: usize,
+        fill: char,
+        _align: Alignment,
+    ) -> usize {
+        this.len() + Self::padding(this, width) * fill.len_utf8()
+    }
+
+    pub(crate) fn op<S: GenericString>(this: &mut S, width: usize, fill: char, align: Alignment) {
+        let padding = Self::padding(this, width);
+        if padding == 0 {
+            return;
+        }
+        let left = match align {
+            Alignment::Left => 0,
+            Alignment::Right => padding,
+            Alignment::Center => padding / 2,
+        };
+        let mut buffer = [0; 4];
+        let fill = fill.encode_utf8(&mut buffer).as_bytes();
+        let len = this.len();
+        let left_len = left * fill.len();
+        let new_len = len + padding * fill.len();
+        let buf = this.as_mut_capacity_slice();
+        buf.copy_within(0..len, left_len);
+        for chunk in buf[..left_len].chunks_exact_mut(fill.len()) {
+            chunk.copy_from_slice(fill);
+        }
+        for chunk in buf[left_len + len..new_len].chunks_exact_mut(fill.len()) {
+            chunk.copy_from_slice(fill);
+        }
+        this.set_size(new_len);
+    }
+}
+
+pub(crate) struct NormalizeWhitespace;
+impl NormalizeWhitespace {
+    pub(crate) fn is_normalized(string: &str) -> bool {
+        let mut last_was_space = true;
+        for ch in string.chars() {
+            if ch.is_whitespace() {
+                if last_was_space || ch != ' ' {
+                    return false;
+                }
+                last_was_space = true;
+            } else {
+                last_was_space = false;
+            }
+        }
+        !last_was_space || string.is_empty()
+    }
+
+    pub(crate) fn op<S: GenericString>(this: &mut S) {
+        let len = this.len();
+        let buf = this.as_mut_capacity_slice();
+        let mut read = 0;
+        let mut write = 0;
+        let mut pending_space = false;
+        while read < len {
+            let ch = char_at(buf, read);
+            let ch_len = ch.len_utf8();
+            if ch.is_whitespace() {
+                pending_space = write > 0;
+            } else {
+                if pending_space {
+                    buf[write] = b' ';
+                    write += 1;
+                    pending_space = false;
+                }
+                buf.copy_within(read..read + ch_len, write);
+                write += ch_len;
+            }
+            read += ch_len;
+        }
+        this.set_size(write);
+    }
+}
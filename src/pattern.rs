@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use alloc::string::String;
+
+/// A pattern which [`SmartString::replace_smart()`] and
+/// [`SmartString::replacen_smart()`] can look for.
+///
+/// This stands in for the standard library's `Pattern` trait, which can't be
+/// used outside of it yet, and is implemented for the same pattern types: `char`,
+/// `&str`, `&String`, `&SmartString`, `char` arrays and slices, and closures
+/// which test a `char`.
+///
+/// This trait is sealed, and can't be implemented outside of this crate.
+pub trait StrPattern: Sealed + Sized {
+    #[doc(hidden)]
+    fn replacen_in<Mode: SmartStringMode>(
+        self,
+        haystack: &str,
+        to: &str,
+        count: usize,
+    ) -> SmartString<Mode>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+use sealed::Sealed;
+
+/// Copy `haystack` into a new string, replacing the first `count` of the
+/// `matches` in it with `to`.
+fn replace_matches<'a, Mode: SmartStringMode>(
+    haystack: &'a str,
+    matches: impl Iterator<Item = (usize, &'a str)>,
+    to: &str,
+    count: usize,
+) -> SmartString<Mode> {
+    let mut out = SmartString::new();
+    let mut last = 0;
+    for (index, found) in matches.take(count) {
+        out.push_str(&haystack[last..index]);
+        out.push_str(to);
+        last = index + found.len();
+    }
+    out.push_str(&haystack[last..]);
+    out
+}
+
+macro_rules! impl_str_pattern {
+    ($(impl$([$($generics:tt)*])? for $type:ty $(where [$($bounds:tt)*])? => |$this:ident| $pattern:expr;)*) => {
+        $(
+            impl$(<$($generics)*>)? Sealed for $type $(where $($bounds)*)? {}
+
+            impl$(<$($generics)*>)? StrPattern for $type $(where $($bounds)*)? {
+                fn replacen_in<Mode: SmartStringMode>(
+                    self,
+                    haystack: &str,
+                    to: &str,
+                    count: usize,
+                ) -> SmartString<Mode> {
+                    let $this = self;
+                    replace_matches(haystack, haystack.match_indices($pattern), to, count)
+                }
+            }
+        )*
+    };
+}
+
+impl_str_pattern! {
+    impl for char => |this| this;
+    impl['a] for &'a str => |this| this;
+    impl['a, 'b] for &'a &'b str => |this| *this;
+    impl['a] for &'a String => |this| this.as_str();
+    impl['a, M: SmartStringMode] for &'a SmartString<M> => |this| this.as_str();
+    impl['a] for &'a [char] => |this| this;
+    impl[const N: usize] for [char; N] => |this| this;
+    impl['a, const N: usize] for &'a [char; N] => |this| this;
+    impl[F] for F where [F: FnMut(char) -> bool] => |this| this;
+}
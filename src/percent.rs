@@ -0,0 +1,73 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{inline::InlineString, SmartString, SmartStringMode, MAX_INLINE};
+use alloc::vec::Vec;
+use core::{
+    fmt::{Display, Formatter},
+    str::Utf8Error,
+};
+use percent_encoding::percent_decode_str;
+
+/// The error returned when a percent-decoded string isn't valid UTF-8.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeError(Utf8Error);
+
+impl DecodeError {
+    /// Get the underlying [`Utf8Error`], which describes where in the decoded
+    /// bytes the invalid sequence was found.
+    pub fn utf8_error(&self) -> Utf8Error {
+        self.0
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "percent-decoded string is not valid UTF-8: {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Decode a percent-encoded string into a [`SmartString`].
+    ///
+    /// Decoding never makes a string longer, so if `input` fits inline, it's
+    /// decoded straight into an inline string without allocating. Longer
+    /// inputs are decoded into a buffer which the result takes ownership of.
+    ///
+    /// Returns a [`DecodeError`] if the decoded bytes aren't valid UTF-8.
+    ///
+    /// ```rust
+    /// # use smartstring::alias::String;
+    /// let segment = String::from_percent_encoded("hello%20w%C3%B6rld").unwrap();
+    /// assert_eq!("hello wörld", segment);
+    /// assert!(segment.is_inline());
+    /// assert!(String::from_percent_encoded("%FF").is_err());
+    /// ```
+    pub fn from_percent_encoded(input: &str) -> Result<Self, DecodeError> {
+        let decoder = percent_decode_str(input);
+        if input.len() <= MAX_INLINE {
+            let mut buffer = [0; MAX_INLINE];
+            let mut len = 0;
+            for byte in decoder {
+                buffer[len] = byte;
+                len += 1;
+            }
+            let string = crate::utf8::from_utf8(&buffer[..len]).map_err(DecodeError)?;
+            Ok(Self::from_inline(InlineString::from(string)))
+        } else {
+            let mut buffer = Vec::with_capacity(input.len());
+            buffer.extend(decoder);
+            crate::utf8::string_from_utf8(buffer)
+                .map(Self::from)
+                .map_err(|error| DecodeError(error.utf8_error()))
+        }
+    }
+}
@@ -0,0 +1,81 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{boxed::BoxedString, ops};
+use core::{
+    fmt::{self, Debug, Formatter, Write},
+    ops::Deref,
+};
+
+/// An append-only handle to a heap allocated [`SmartString`][crate::SmartString].
+///
+/// This is passed to the closure given to
+/// [`SmartString::with_promoted()`][crate::SmartString::with_promoted]. The
+/// string is known to be on the heap for as long as the handle exists, so
+/// appending to it skips the check of whether the string is inline which every
+/// [`SmartString`][crate::SmartString] operation has to make, and it never
+/// needs to be promoted. It dereferences to the contents as a [`str`], and
+/// implements [`fmt::Write`][Write].
+pub struct BoxedStringHandle<'a> {
+    string: &'a mut BoxedString,
+}
+
+impl<'a> BoxedStringHandle<'a> {
+    pub(crate) fn new(string: &'a mut BoxedString) -> Self {
+        Self { string }
+    }
+
+    /// Append a string slice to the end of the string.
+    #[inline]
+    pub fn push_str(&mut self, string: &str) {
+        self.string
+            .ensure_capacity(ops::PushStr::cap(self.string, string));
+        ops::PushStr::op(self.string, string)
+    }
+
+    /// Append a `char` to the end of the string.
+    #[inline]
+    pub fn push(&mut self, ch: char) {
+        self.string.ensure_capacity(ops::Push::cap(self.string, ch));
+        ops::Push::op(self.string, ch)
+    }
+
+    /// Make sure there's room for at least `additional` more bytes.
+    pub fn reserve(&mut self, additional: usize) {
+        self.string.ensure_capacity(self.string.len() + additional);
+    }
+
+    /// Return the current capacity of the string's buffer.
+    pub fn capacity(&self) -> usize {
+        self.string.capacity()
+    }
+
+    /// Get the contents as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.string
+    }
+}
+
+impl<'a> Deref for BoxedStringHandle<'a> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.string
+    }
+}
+
+impl<'a> Write for BoxedStringHandle<'a> {
+    fn write_str(&mut self, string: &str) -> fmt::Result {
+        self.push_str(string);
+        Ok(())
+    }
+}
+
+impl<'a> Debug for BoxedStringHandle<'a> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BoxedStringHandle")
+            .field(&self.as_str())
+            .finish()
+    }
+}
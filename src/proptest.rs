@@ -4,26 +4,119 @@
 
 //! `proptest` strategies (requires the `proptest` feature flag).
 
-use crate::{SmartString, SmartStringMode};
+use crate::{SmartString, SmartStringMode, MAX_INLINE};
 use proptest::proptest;
 use proptest::strategy::{BoxedStrategy, Strategy};
 use proptest::string::Error;
 
+#[cfg(any(test, feature = "test"))]
+use crate::test::{Action, Constructor};
+#[cfg(any(test, feature = "test"))]
+use alloc::vec::Vec;
+
 /// Creates a strategy which generates [`SmartString`][SmartString]s matching the given regular expression.
 ///
 /// [SmartString]: ../struct.SmartString.html
-pub fn string_regex<Mode: SmartStringMode>(
-    regex: &str,
-) -> Result<BoxedStrategy<SmartString<Mode>>, Error>
+#[allow(clippy::result_large_err)]
+pub fn string_regex<Mode>(regex: &str) -> Result<BoxedStrategy<SmartString<Mode>>, Error>
 where
-    Mode: 'static,
+    Mode: SmartStringMode + 'static,
 {
     proptest::string::string_regex(regex).map(|g| g.prop_map(SmartString::from).boxed())
 }
 
+/// Creates a strategy which generates [`SmartString`][SmartString]s with
+/// lengths clustered around [`MAX_INLINE`][MAX_INLINE].
+///
+/// Most of the strings are within a few bytes either side of the inline
+/// capacity, where the bugs in code handling both representations tend to
+/// be, and the rest are anywhere from empty to a few times as long. They mix
+/// characters of every UTF-8 width, so a multi-byte character often straddles
+/// the boundary, but each string is filled out to exactly the length chosen
+/// for it. Short strings are sometimes moved onto the heap with
+/// [`force_box()`][SmartString::force_box], so both representations are
+/// generated at every length up to the boundary.
+///
+/// Lengths shrink towards the bottom of the cluster rather than towards the
+/// empty string, so a failing case stays near the boundary as it shrinks.
+///
+/// [SmartString]: ../struct.SmartString.html
+/// [MAX_INLINE]: ../constant.MAX_INLINE.html
+pub fn boundary_strings<Mode>() -> BoxedStrategy<SmartString<Mode>>
+where
+    Mode: SmartStringMode + 'static,
+{
+    let len = proptest::prop_oneof![
+        4 => MAX_INLINE - 3..=MAX_INLINE + 3,
+        1 => 0..=MAX_INLINE * 4,
+    ];
+    let chars = proptest::collection::vec(proptest::char::any(), 0..=MAX_INLINE * 4);
+    (len, chars, proptest::bool::ANY)
+        .prop_map(|(len, chars, boxed)| {
+            let mut string = SmartString::<Mode>::new();
+            let mut chars = chars.into_iter();
+            while string.len() < len {
+                match chars.next() {
+                    Some(ch) if string.len() + ch.len_utf8() <= len => string.push(ch),
+                    _ => string.push('x'),
+                }
+            }
+            if boxed {
+                string.force_box();
+            }
+            string
+        })
+        .boxed()
+}
+
+/// Creates a strategy which generates a [`Constructor`][Constructor] and a sequence
+/// of [`Action`][Action]s to run through [`test_everything()`][test_everything].
+///
+/// The actions exercise every transition between inline and boxed strings, so
+/// this is a good way to property test your own wrappers around
+/// [`SmartString`][SmartString], by running the same actions against them. It's
+/// only available with the `test` feature flag.
+///
+/// [SmartString]: ../struct.SmartString.html
+/// [Constructor]: ../test/enum.Constructor.html
+/// [Action]: ../test/enum.Action.html
+/// [test_everything]: ../test/fn.test_everything.html
+#[cfg(any(test, feature = "test"))]
+pub fn actions() -> BoxedStrategy<(Constructor, Vec<Action>)> {
+    #[cfg(test)]
+    {
+        proptest::arbitrary::any::<(Constructor, Vec<Action>)>().boxed()
+    }
+    // Outside of our own tests, the test types implement `arbitrary` rather than
+    // `proptest`'s `Arbitrary`, so we generate them from random bytes.
+    #[cfg(not(test))]
+    {
+        use arbitrary::{Arbitrary, Unstructured};
+        proptest::collection::vec(proptest::arbitrary::any::<u8>(), 0..4096)
+            .prop_map(|data| {
+                Arbitrary::arbitrary(&mut Unstructured::new(&data))
+                    .unwrap_or_else(|_| (Constructor::New, Vec::new()))
+            })
+            .boxed()
+    }
+}
+
 proptest! {
     #[test]
     fn strategy(string in string_regex(".+").unwrap()) {
         assert!(!SmartString::<crate::LazyCompact>::is_empty(&string));
     }
+
+    #[test]
+    fn boundary_strategy(string in boundary_strings::<crate::LazyCompact>()) {
+        assert!(string.len() <= MAX_INLINE * 4);
+        if string.len() > MAX_INLINE {
+            assert!(!string.is_inline());
+        }
+    }
+
+    #[test]
+    fn actions_strategy((constructor, actions) in actions()) {
+        crate::test::test_everything::<crate::Compact>(constructor, actions);
+    }
 }
@@ -0,0 +1,145 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use unicode_normalization::char::{decompose_canonical, is_combining_mark};
+
+/// The normalisations [`SmartString::to_search_key()`] applies.
+///
+/// The default lowercases and collapses whitespace, but doesn't strip accents.
+///
+/// ```rust
+/// # use smartstring::SearchKeyOptions;
+/// let options = SearchKeyOptions::new().strip_accents(true);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SearchKeyOptions {
+    lowercase: bool,
+    collapse_whitespace: bool,
+    strip_accents: bool,
+}
+
+impl SearchKeyOptions {
+    /// Construct the default options.
+    pub const fn new() -> Self {
+        Self {
+            lowercase: true,
+            collapse_whitespace: true,
+            strip_accents: false,
+        }
+    }
+
+    /// Set whether to lowercase the key.
+    ///
+    /// Each `char` is lowercased on its own, using [`char::to_lowercase()`],
+    /// so context dependent rules like the final form of a Greek sigma aren't
+    /// applied.
+    pub const fn lowercase(mut self, lowercase: bool) -> Self {
+        self.lowercase = lowercase;
+        self
+    }
+
+    /// Set whether to trim whitespace from both ends of the key, and replace
+    /// each run of whitespace inside it with a single space.
+    pub const fn collapse_whitespace(mut self, collapse_whitespace: bool) -> Self {
+        self.collapse_whitespace = collapse_whitespace;
+        self
+    }
+
+    /// Set whether to strip accents from the key, by decomposing each `char`
+    /// and leaving out any combining marks.
+    pub const fn strip_accents(mut self, strip_accents: bool) -> Self {
+        self.strip_accents = strip_accents;
+        self
+    }
+}
+
+impl Default for SearchKeyOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Make a normalised key for searching or indexing the string.
+    ///
+    /// This applies every normalisation selected in `options` in a single pass
+    /// over the string, writing the result directly into a new [`SmartString`],
+    /// which stays inline if it fits.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::String, SearchKeyOptions};
+    /// let title = String::from("  Crème   Brûlée\n");
+    /// assert_eq!("crème brûlée", title.to_search_key(SearchKeyOptions::new()));
+    /// let options = SearchKeyOptions::new().strip_accents(true);
+    /// assert_eq!("creme brulee", title.to_search_key(options));
+    /// ```
+    pub fn to_search_key(&self, options: SearchKeyOptions) -> Self {
+        let mut out = Self::new();
+        let mut pending_space = false;
+        let push = |out: &mut Self, ch: char| {
+            if options.lowercase {
+                out.extend(ch.to_lowercase());
+            } else {
+                out.push(ch);
+            }
+        };
+        for ch in self.chars() {
+            if options.collapse_whitespace {
+                if ch.is_whitespace() {
+                    pending_space = !out.is_empty();
+                    continue;
+                }
+                if pending_space {
+                    out.push(' ');
+                    pending_space = false;
+                }
+            }
+            if options.strip_accents {
+                decompose_canonical(ch, |part| {
+                    if !is_combining_mark(part) {
+                        push(&mut out, part);
+                    }
+                });
+            } else {
+                push(&mut out, ch);
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SearchKeyOptions;
+    use crate::{Compact, SmartString, MAX_INLINE};
+
+    #[test]
+    fn search_key() {
+        let string = SmartString::<Compact>::from("\t Ångström   UNITS \u{3000}");
+        let defaults = SearchKeyOptions::new();
+        assert_eq!("ångström units", string.to_search_key(defaults));
+        assert_eq!(
+            "angstrom units",
+            string.to_search_key(defaults.strip_accents(true))
+        );
+        assert_eq!(
+            "\t Ångström   UNITS \u{3000}",
+            string.to_search_key(defaults.lowercase(false).collapse_whitespace(false))
+        );
+        assert_eq!(
+            "Ångström UNITS",
+            string.to_search_key(defaults.lowercase(false))
+        );
+        assert_eq!(
+            "",
+            SmartString::<Compact>::from("   ").to_search_key(defaults)
+        );
+
+        let key = string.to_search_key(defaults);
+        assert!(key.len() <= MAX_INLINE && key.is_inline());
+        let decomposed = SmartString::<Compact>::from("A\u{30a}");
+        assert_eq!("a", decomposed.to_search_key(defaults.strip_accents(true)));
+    }
+}
@@ -0,0 +1,170 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{config::MAX_INLINE, SmartString, SmartStringMode};
+use alloc::vec::Vec;
+use core::{
+    fmt::{Debug, Error, Formatter},
+    ops::{Bound, Deref, RangeBounds},
+};
+
+/// Where the `char` boundaries of a [`SegmentedSmartString`] are.
+#[derive(Clone)]
+enum Boundaries {
+    /// Every byte is a `char`, so the boundaries are just the byte offsets.
+    Ascii,
+    /// The byte offsets of a string no longer than [`MAX_INLINE`], which all
+    /// fit in a byte, kept inline rather than on the heap. Only the first
+    /// `count` of them are used.
+    Short {
+        count: u8,
+        offsets: [u8; MAX_INLINE + 1],
+    },
+    /// The byte offset of each `char`, followed by the length of the string.
+    Offsets(Vec<usize>),
+}
+
+/// A [`SmartString`] which can be sliced by `char` index in constant time.
+///
+/// Slicing a string at its `n`th `char` means scanning it from the start to
+/// find where that `char` is. This works out the byte offset of every `char`
+/// once, when it's constructed, and keeps them around for repeated slicing.
+/// An ASCII string needs no extra storage at all, as its `char` and byte
+/// offsets are the same, and a string short enough to be inlined keeps its
+/// offsets inline too, so only a long string with non-ASCII text allocates.
+///
+/// The string can only be modified through
+/// [`modify()`][SegmentedSmartString::modify], which recomputes the offsets
+/// afterwards.
+///
+/// ```rust
+/// # use smartstring::{SegmentedSmartString, alias::String};
+/// let string = SegmentedSmartString::new(String::from("h\u{e9}llo w\u{f6}rld"));
+/// assert_eq!(11, string.segment_count());
+/// assert_eq!(Some("\u{e9}"), string.segment(1));
+/// assert_eq!(Some("w\u{f6}rld"), string.slice(6..));
+/// assert_eq!(None, string.slice(6..12));
+/// ```
+pub struct SegmentedSmartString<Mode: SmartStringMode> {
+    string: SmartString<Mode>,
+    boundaries: Boundaries,
+}
+
+impl<Mode: SmartStringMode> SegmentedSmartString<Mode> {
+    /// Wrap a [`SmartString`], computing its `char` boundaries.
+    pub fn new(string: SmartString<Mode>) -> Self {
+        let boundaries = Self::boundaries(&string);
+        Self { string, boundaries }
+    }
+
+    fn boundaries(string: &str) -> Boundaries {
+        if string.is_ascii() {
+            Boundaries::Ascii
+        } else if string.len() <= MAX_INLINE {
+            let mut offsets = [0; MAX_INLINE + 1];
+            let mut count = 0;
+            for (index, _) in string.char_indices() {
+                offsets[count] = index as u8;
+                count += 1;
+            }
+            offsets[count] = string.len() as u8;
+            Boundaries::Short {
+                count: count as u8 + 1,
+                offsets,
+            }
+        } else {
+            let mut offsets: Vec<usize> = string.char_indices().map(|(index, _)| index).collect();
+            offsets.push(string.len());
+            Boundaries::Offsets(offsets)
+        }
+    }
+
+    /// The byte offset of the `index`th `char` boundary, where the boundary
+    /// after the last `char` is the length of the string.
+    fn offset(&self, index: usize) -> Option<usize> {
+        match &self.boundaries {
+            Boundaries::Ascii if index <= self.string.len() => Some(index),
+            Boundaries::Ascii => None,
+            Boundaries::Short { count, offsets } => offsets[..*count as usize]
+                .get(index)
+                .map(|&offset| offset as usize),
+            Boundaries::Offsets(offsets) => offsets.get(index).copied(),
+        }
+    }
+
+    /// The number of `char`s in the string.
+    pub fn segment_count(&self) -> usize {
+        match &self.boundaries {
+            Boundaries::Ascii => self.string.len(),
+            Boundaries::Short { count, .. } => *count as usize - 1,
+            Boundaries::Offsets(offsets) => offsets.len() - 1,
+        }
+    }
+
+    /// Get the `index`th `char` of the string as a string slice, or `None` if
+    /// it's out of bounds.
+    pub fn segment(&self, index: usize) -> Option<&str> {
+        self.slice(index..index.checked_add(1)?)
+    }
+
+    /// Get the slice of the string covering the given range of `char`s, or
+    /// `None` if it's out of bounds.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Option<&str> {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.checked_add(1)?,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.checked_add(1)?,
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => self.segment_count(),
+        };
+        if start > end {
+            return None;
+        }
+        Some(&self.string[self.offset(start)?..self.offset(end)?])
+    }
+
+    /// Modify the string, recomputing its `char` boundaries afterwards.
+    pub fn modify<R>(&mut self, f: impl FnOnce(&mut SmartString<Mode>) -> R) -> R {
+        let result = f(&mut self.string);
+        self.boundaries = Self::boundaries(&self.string);
+        result
+    }
+
+    /// Unwrap the [`SmartString`].
+    pub fn into_inner(self) -> SmartString<Mode> {
+        self.string
+    }
+}
+
+impl<Mode: SmartStringMode> Clone for SegmentedSmartString<Mode> {
+    fn clone(&self) -> Self {
+        Self {
+            string: self.string.clone(),
+            boundaries: self.boundaries.clone(),
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> Deref for SegmentedSmartString<Mode> {
+    type Target = SmartString<Mode>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.string
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartString<Mode>> for SegmentedSmartString<Mode> {
+    fn from(string: SmartString<Mode>) -> Self {
+        Self::new(string)
+    }
+}
+
+impl<Mode: SmartStringMode> Debug for SegmentedSmartString<Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(&self.string, f)
+    }
+}
@@ -2,12 +2,26 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
-use crate::{SmartString, SmartStringMode};
+//! [Serde] support for [`SmartString`].
+//!
+//! [`SmartString`] implements [`Serialize`] and [`Deserialize`] as a string.
+//! This module provides adapters for serialising it in other ways, for use
+//! with serde's `with` attribute.
+//!
+//! None of this needs `std`: with the `std` feature turned off, it builds
+//! against serde's `alloc` support, and works with `no_std` deserialisers like
+//! `serde-json-core`. Deserialising from a borrowed or transient string copies
+//! it straight into the new [`SmartString`], which is inline if it fits, so
+//! short strings never allocate.
+//!
+//! [Serde]: https://serde.rs/
+
+use crate::{LazyCompact, SmartString, SmartStringMode};
 use alloc::string::String;
-use core::{fmt, marker::PhantomData};
+use core::{fmt, marker::PhantomData, ops::Deref};
 
 use serde::{
-    de::{Error, Visitor},
+    de::{DeserializeSeed, Error, Visitor},
     Deserialize, Deserializer, Serialize, Serializer,
 };
 
@@ -21,16 +35,29 @@ impl<T: SmartStringMode> Serialize for SmartString<T> {
 }
 
 impl<'de, T: SmartStringMode> Deserialize<'de> for SmartString<T> {
+    /// Deserialize a [`SmartString`].
+    ///
+    /// The result is always in canonical form: it's inlined if and only if its
+    /// length is no greater than [`MAX_INLINE`][crate::MAX_INLINE], regardless of
+    /// the representation of the string that was serialised.
+    /// See [`SmartString::normalize()`].
+    ///
+    /// This asks the deserialiser for a `&str` rather than a [`String`], so a
+    /// format which would otherwise allocate a [`String`] to hand over, eg. for
+    /// each key of a map of `SmartString`s, can lend out its own buffer
+    /// instead, and a string short enough to be inlined never touches the
+    /// heap. A format which has a [`String`] to hand anyway can still pass it
+    /// on.
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        deserializer
-            .deserialize_string(SmartStringVisitor(PhantomData))
-            .map(SmartString::from)
+        deserializer.deserialize_str(SmartStringVisitor(PhantomData))
     }
 }
 
+/// Builds a [`SmartString`] from whichever form of string the deserialiser has
+/// to hand, without ever going through an intermediate [`String`].
 struct SmartStringVisitor<T: SmartStringMode>(PhantomData<*const T>);
 
 impl<'de, T: SmartStringMode> Visitor<'de> for SmartStringVisitor<T> {
@@ -40,6 +67,11 @@ impl<'de, T: SmartStringMode> Visitor<'de> for SmartStringVisitor<T> {
         formatter.write_str("a string")
     }
 
+    /// Take ownership of a deserialised [`String`].
+    ///
+    /// A short string is copied inline and the [`String`] dropped. A long one
+    /// keeps the [`String`]'s buffer where the allocator API makes that
+    /// possible, and is copied into a new buffer otherwise.
     fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
     where
         E: Error,
@@ -47,6 +79,8 @@ impl<'de, T: SmartStringMode> Visitor<'de> for SmartStringVisitor<T> {
         Ok(SmartString::from(v))
     }
 
+    /// Copy a borrowed or transient string, straight into the inline buffer if
+    /// it fits, so only long strings allocate.
     fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
     where
         E: Error,
@@ -55,6 +89,444 @@ impl<'de, T: SmartStringMode> Visitor<'de> for SmartStringVisitor<T> {
     }
 }
 
+/// A string deserialised by borrowing from the input where possible, and
+/// turned into a [`SmartString`] only when it has to be.
+///
+/// Formats which deserialise from a buffer in memory, like
+/// `serde_json::from_str()`, can often hand out strings borrowed straight from
+/// the input. This keeps such a string borrowed rather than copying it, and
+/// only copies strings the format can't lend out, eg. because they contained
+/// escape sequences. Call [`into_owned()`][BorrowedSmartString::into_owned] or
+/// [`to_mut()`][BorrowedSmartString::to_mut] to get a [`SmartString`] when you
+/// need one.
+///
+/// As with [`Cow`][alloc::borrow::Cow], serde's derive macro needs a
+/// `#[serde(borrow)]` attribute on the field to borrow from the input.
+///
+/// ```rust
+/// # use serde::Deserialize;
+/// # use smartstring::serde::BorrowedSmartString;
+/// #[derive(Deserialize)]
+/// struct Record<'a> {
+///     #[serde(borrow)]
+///     name: BorrowedSmartString<'a>,
+/// }
+///
+/// let input = r#"{"name": "a value long enough that we'd rather not copy it"}"#;
+/// let record: Record<'_> = serde_json::from_str(input).unwrap();
+/// assert!(record.name.is_borrowed());
+/// assert_eq!("a value long enough that we'd rather not copy it", record.name.as_str());
+/// ```
+pub enum BorrowedSmartString<'de, Mode: SmartStringMode = LazyCompact> {
+    /// A string borrowed from the deserialiser's input.
+    Borrowed(&'de str),
+    /// A string which couldn't be borrowed.
+    Owned(SmartString<Mode>),
+}
+
+impl<'de, Mode: SmartStringMode> BorrowedSmartString<'de, Mode> {
+    /// Test whether the string is borrowed from the input.
+    pub fn is_borrowed(&self) -> bool {
+        matches!(self, Self::Borrowed(_))
+    }
+
+    /// Get the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Borrowed(string) => string,
+            Self::Owned(string) => string,
+        }
+    }
+
+    /// Get a mutable reference to the string, copying it into a
+    /// [`SmartString`] first if it's borrowed.
+    pub fn to_mut(&mut self) -> &mut SmartString<Mode> {
+        if let Self::Borrowed(string) = *self {
+            *self = Self::Owned(string.into());
+        }
+        match self {
+            Self::Owned(string) => string,
+            Self::Borrowed(_) => unreachable!(),
+        }
+    }
+
+    /// Turn the string into a [`SmartString`], copying it if it's borrowed.
+    pub fn into_owned(self) -> SmartString<Mode> {
+        match self {
+            Self::Borrowed(string) => string.into(),
+            Self::Owned(string) => string,
+        }
+    }
+}
+
+impl<'de, Mode: SmartStringMode> Deref for BorrowedSmartString<'de, Mode> {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl<'de, Mode: SmartStringMode> fmt::Debug for BorrowedSmartString<'de, Mode> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl<'de, Mode: SmartStringMode> PartialEq for BorrowedSmartString<'de, Mode> {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl<'de, Mode: SmartStringMode> Eq for BorrowedSmartString<'de, Mode> {}
+
+impl<'de, Mode: SmartStringMode> Serialize for BorrowedSmartString<'de, Mode> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self)
+    }
+}
+
+impl<'de: 'a, 'a, Mode: SmartStringMode> Deserialize<'de> for BorrowedSmartString<'a, Mode> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(BorrowedVisitor(PhantomData))
+    }
+}
+
+struct BorrowedVisitor<'a, T: SmartStringMode>(PhantomData<(&'a str, *const T)>);
+
+impl<'de: 'a, 'a, T: SmartStringMode> Visitor<'de> for BorrowedVisitor<'a, T> {
+    type Value = BorrowedSmartString<'a, T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(BorrowedSmartString::Borrowed(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(BorrowedSmartString::Owned(v.into()))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(BorrowedSmartString::Owned(v.into()))
+    }
+}
+
+/// A [`DeserializeSeed`] which deserialises a [`SmartString`] with room for at
+/// least a given number of bytes.
+///
+/// If you know how long strings are going to get once they've been deserialised,
+/// eg. from a schema, this allocates the capacity they need up front, rather
+/// than growing them one step at a time as they're appended to afterwards. As
+/// with [`SmartString::with_capacity()`], a capacity which fits inline doesn't
+/// allocate.
+///
+/// ```rust
+/// # use serde::de::DeserializeSeed;
+/// # use smartstring::serde::WithCapacity;
+/// let mut deserializer = serde_json::Deserializer::from_str(r#""short""#);
+/// let string = WithCapacity::<smartstring::LazyCompact>::new(256)
+///     .deserialize(&mut deserializer)
+///     .unwrap();
+/// assert_eq!("short", string);
+/// assert!(string.capacity() >= 256);
+/// ```
+pub struct WithCapacity<Mode: SmartStringMode = LazyCompact> {
+    capacity: usize,
+    mode: PhantomData<Mode>,
+}
+
+impl<Mode: SmartStringMode> WithCapacity<Mode> {
+    /// Construct a seed for strings with room for at least `capacity` bytes.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            mode: PhantomData,
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> Clone for WithCapacity<Mode> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<Mode: SmartStringMode> Copy for WithCapacity<Mode> {}
+
+impl<Mode: SmartStringMode> fmt::Debug for WithCapacity<Mode> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("WithCapacity").field(&self.capacity).finish()
+    }
+}
+
+impl<'de, Mode: SmartStringMode> DeserializeSeed<'de> for WithCapacity<Mode> {
+    type Value = SmartString<Mode>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de, Mode: SmartStringMode> Visitor<'de> for WithCapacity<Mode> {
+    type Value = SmartString<Mode>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        let mut string = SmartString::with_capacity(self.capacity.max(v.len()));
+        string.push_str(v);
+        Ok(string)
+    }
+}
+
+/// Serialise a [`SmartString`] as bytes.
+///
+/// Some formats or schemas store text as byte strings. Use this module with
+/// serde's `with` attribute to serialise a [`SmartString`] field as bytes, and
+/// deserialise it from bytes, which are validated as UTF-8. Strings are also
+/// accepted when deserialising.
+///
+/// ```rust
+/// # use serde::{Deserialize, Serialize};
+/// # use smartstring::alias::String;
+/// #[derive(Serialize, Deserialize)]
+/// struct Record {
+///     #[serde(with = "smartstring::serde::bytes")]
+///     name: String,
+/// }
+/// ```
+pub mod bytes {
+    use crate::{SmartString, SmartStringMode};
+    use alloc::{string::String, vec::Vec};
+    use core::{fmt, marker::PhantomData};
+    use serde::{
+        de::{Error, SeqAccess, Unexpected, Visitor},
+        Deserializer, Serializer,
+    };
+
+    /// Serialise a [`SmartString`] as bytes.
+    pub fn serialize<Mode, S>(string: &SmartString<Mode>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        Mode: SmartStringMode,
+        S: Serializer,
+    {
+        serializer.serialize_bytes(string.as_bytes())
+    }
+
+    /// Deserialise a [`SmartString`] from bytes, which must be valid UTF-8.
+    pub fn deserialize<'de, Mode, D>(deserializer: D) -> Result<SmartString<Mode>, D::Error>
+    where
+        Mode: SmartStringMode,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_byte_buf(BytesVisitor(PhantomData))
+    }
+
+    struct BytesVisitor<T: SmartStringMode>(PhantomData<*const T>);
+
+    impl<'de, T: SmartStringMode> Visitor<'de> for BytesVisitor<T> {
+        type Value = SmartString<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("UTF-8 encoded bytes")
+        }
+
+        fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            crate::utf8::from_utf8(v)
+                .map(SmartString::from)
+                .map_err(|_| E::invalid_value(Unexpected::Bytes(v), &self))
+        }
+
+        fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            crate::utf8::string_from_utf8(v)
+                .map(SmartString::from)
+                .map_err(|error| E::invalid_value(Unexpected::Bytes(error.as_bytes()), &self))
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(SmartString::from(v))
+        }
+
+        fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            Ok(SmartString::from(v))
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            // The size hint comes from the input, so don't trust it with more
+            // than a modest preallocation, as serde's own impls don't either.
+            let hint = seq.size_hint().unwrap_or(0).min(4096);
+            let mut bytes = Vec::with_capacity(hint);
+            while let Some(byte) = seq.next_element()? {
+                bytes.push(byte);
+            }
+            self.visit_byte_buf(bytes)
+        }
+    }
+}
+
+/// Serialise a [`SmartString`] as a sequence of strings of bounded length.
+///
+/// Serde has no way to serialise a string a piece at a time, so a serialiser
+/// which escapes or encodes a string into a buffer of its own before writing
+/// it out needs a buffer as large as the whole string. This adapter splits the
+/// string at character boundaries into pieces of at most
+/// [`CHUNK_SIZE`](chunked::CHUNK_SIZE) bytes, and serialises them as a sequence
+/// of strings, so that such a serialiser only ever needs to buffer one piece.
+/// Use it with the `with` attribute on fields which occasionally hold very long
+/// strings:
+///
+/// ```rust
+/// # use serde::{Deserialize, Serialize};
+/// # use smartstring::alias::String;
+/// #[derive(Serialize, Deserialize)]
+/// struct Document {
+///     #[serde(with = "smartstring::serde::chunked")]
+///     body: String,
+/// }
+/// ```
+///
+/// This changes the serialised form: the string becomes an array of strings,
+/// which a plain [`SmartString`] or [`String`] can't be deserialised from.
+/// It doesn't help with serialisers which write the whole output into memory
+/// anyway, such as `serde_json::to_string()`, only with ones which stream
+/// their output, such as `serde_json::to_writer()` writing to a file or
+/// socket, which already write a string out as they escape it, but may
+/// buffer a whole string in other formats, such as some binary encodings.
+pub mod chunked {
+    use crate::{SmartString, SmartStringMode};
+    use core::{fmt, marker::PhantomData};
+    use serde::{
+        de::{DeserializeSeed, Error, SeqAccess, Visitor},
+        ser::SerializeSeq,
+        Deserializer, Serializer,
+    };
+
+    /// The maximum length of a piece of the string, in bytes.
+    pub const CHUNK_SIZE: usize = 8 * 1024;
+
+    /// Serialise a [`SmartString`] as a sequence of strings of at most
+    /// [`CHUNK_SIZE`] bytes each.
+    pub fn serialize<Mode, S>(string: &SmartString<Mode>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        Mode: SmartStringMode,
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(None)?;
+        let mut remaining = string.as_str();
+        while !remaining.is_empty() {
+            let mut end = remaining.len().min(CHUNK_SIZE);
+            while !remaining.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (chunk, rest) = remaining.split_at(end);
+            seq.serialize_element(chunk)?;
+            remaining = rest;
+        }
+        seq.end()
+    }
+
+    /// Deserialise a [`SmartString`] from a sequence of strings, appending
+    /// each one to the result as it's deserialised.
+    pub fn deserialize<'de, Mode, D>(deserializer: D) -> Result<SmartString<Mode>, D::Error>
+    where
+        Mode: SmartStringMode,
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_seq(ChunksVisitor(PhantomData))
+    }
+
+    struct ChunksVisitor<T: SmartStringMode>(PhantomData<*const T>);
+
+    impl<'de, T: SmartStringMode> Visitor<'de> for ChunksVisitor<T> {
+        type Value = SmartString<T>;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a sequence of strings")
+        }
+
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+        where
+            A: SeqAccess<'de>,
+        {
+            let mut string = SmartString::new();
+            while seq.next_element_seed(AppendChunk(&mut string))?.is_some() {}
+            Ok(string)
+        }
+    }
+
+    /// Appends a string to a [`SmartString`] without allocating one of its own.
+    struct AppendChunk<'a, T: SmartStringMode>(&'a mut SmartString<T>);
+
+    impl<'de, 'a, T: SmartStringMode> DeserializeSeed<'de> for AppendChunk<'a, T> {
+        type Value = ();
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_str(self)
+        }
+    }
+
+    impl<'de, 'a, T: SmartStringMode> Visitor<'de> for AppendChunk<'a, T> {
+        type Value = ();
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a string")
+        }
+
+        fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+        where
+            E: Error,
+        {
+            self.0.push_str(v);
+            Ok(())
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -75,4 +547,139 @@ mod test {
             assert_tokens(&value, &[Token::String(string)]);
         }
     }
+
+    #[test]
+    fn visitor_representation() {
+        let short = "short key";
+        let long = "a value much too long to be stored inline";
+        for value in [
+            SmartStringVisitor::<Compact>(PhantomData).visit_str::<serde::de::value::Error>(short),
+            SmartStringVisitor(PhantomData).visit_string(short.into()),
+        ] {
+            let value = value.unwrap();
+            assert_eq!(short, value);
+            assert!(value.is_inline());
+        }
+        let owned = String::from(long);
+        #[cfg(has_allocator)]
+        let buffer = owned.as_ptr();
+        let value = SmartStringVisitor::<Compact>(PhantomData)
+            .visit_string::<serde::de::value::Error>(owned)
+            .unwrap();
+        assert_eq!(long, value);
+        assert!(!value.is_inline());
+        #[cfg(has_allocator)]
+        assert_eq!(buffer, value.as_ptr());
+    }
+
+    #[test]
+    fn borrowed_smart_string() {
+        use serde_test::{assert_de_tokens, assert_ser_tokens, Token};
+
+        let long = "longer than inline string for serde testing";
+        let borrowed = BorrowedSmartString::<Compact>::Borrowed(long);
+        assert_de_tokens(&borrowed, &[Token::BorrowedStr(long)]);
+        assert_ser_tokens(&borrowed, &[Token::Str(long)]);
+
+        let input = format!("\"{}\"", long);
+        let value: BorrowedSmartString<'_, Compact> = serde_json::from_str(&input).unwrap();
+        assert!(value.is_borrowed());
+        let mut value: BorrowedSmartString<'_, Compact> =
+            serde_json::from_str("\"an \\\"escaped\\\" string\"").unwrap();
+        assert!(!value.is_borrowed());
+        assert_eq!("an \"escaped\" string", value.as_str());
+
+        let mut borrowed = BorrowedSmartString::<Compact>::Borrowed(long);
+        borrowed.to_mut().push('!');
+        value.to_mut().push('!');
+        assert_eq!(format!("{}!", long), borrowed.into_owned());
+        assert_eq!("an \"escaped\" string!", value.into_owned());
+    }
+
+    #[test]
+    fn with_capacity() {
+        let long = "longer than inline string for serde testing";
+        for (input, capacity) in [("short", 0), ("short", 100), (long, 0), (long, 100)] {
+            let json = serde_json::to_string(input).unwrap();
+            let mut deserializer = serde_json::Deserializer::from_str(&json);
+            let string = WithCapacity::<Compact>::new(capacity)
+                .deserialize(&mut deserializer)
+                .unwrap();
+            assert_eq!(input, string);
+            assert!(string.capacity() >= capacity);
+            assert_eq!(
+                input.len().max(capacity) <= crate::MAX_INLINE,
+                string.is_inline()
+            );
+        }
+    }
+
+    #[test]
+    fn de_borrowed() {
+        use serde_test::{assert_de_tokens, Token};
+
+        for &string in ["small test", "longer than inline string for serde testing"].iter() {
+            let value = SmartString::<Compact>::from(string);
+            assert_de_tokens(&value, &[Token::BorrowedStr(string)]);
+            assert_de_tokens(&value, &[Token::Str(string)]);
+        }
+    }
+
+    #[test]
+    fn deserialize_is_canonical() {
+        use crate::LazyCompact;
+        use serde::de::{value::Error, IntoDeserializer};
+
+        let mut value =
+            SmartString::<LazyCompact>::from("longer than inline string for serde testing");
+        value.truncate(4);
+        assert!(!value.is_inline());
+        let de = SmartString::<LazyCompact>::deserialize(value.as_str().into_deserializer())
+            .map_err(|e: Error| e)
+            .unwrap();
+        assert_eq!(value, de);
+        assert!(de.is_inline());
+        value.normalize();
+        assert!(value.is_inline());
+    }
+
+    #[test]
+    fn deserialize_without_allocating() {
+        use crate::test::counting_allocator::assert_no_alloc;
+        use serde::{de::Visitor, forward_to_deserialize_any};
+
+        /// A deserialiser which allocates a `String` only when asked for one.
+        struct Input(&'static str);
+
+        impl<'de> Deserializer<'de> for Input {
+            type Error = serde::de::value::Error;
+
+            fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.visit_str(self.0)
+            }
+
+            fn deserialize_string<V: Visitor<'de>>(
+                self,
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                visitor.visit_string(self.0.into())
+            }
+
+            forward_to_deserialize_any! {
+                bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str
+                bytes byte_buf option unit unit_struct newtype_struct seq tuple
+                tuple_struct map struct enum identifier ignored_any
+            }
+        }
+
+        let key = assert_no_alloc(|| SmartString::<Compact>::deserialize(Input("short key")));
+        assert_eq!("short key", key.unwrap());
+        let long = "a key much too long to be stored inline";
+        let key = SmartString::<Compact>::deserialize(Input(long));
+        assert_eq!(long, key.unwrap());
+
+        let map: std::collections::BTreeMap<SmartString<Compact>, u32> =
+            serde_json::from_str(r#"{"one": 1, "two\u0021": 2}"#).unwrap();
+        assert_eq!(Some(&2), map.get("two!"));
+    }
 }
@@ -0,0 +1,206 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use alloc::vec::Vec;
+use core::{
+    fmt::{Debug, Error, Formatter},
+    iter::{FromIterator, FusedIterator},
+    slice::Iter,
+};
+
+/// A set of [`SmartString`]s for when there are only a handful of them.
+///
+/// The strings are kept in a sorted [`Vec`], so the whole set is a single
+/// allocation, and short strings live inline in it with no further
+/// indirection. Lookups are a binary search over that one contiguous buffer,
+/// which for sets of up to a dozen or so short strings beats hashing them, and
+/// an empty set doesn't allocate at all. Insertion and removal are linear in
+/// the size of the set, so this isn't the right choice for large sets.
+///
+/// Iteration is in sorted order.
+///
+/// ```rust
+/// # use smartstring::{SmallStringSet, LazyCompact};
+/// let mut tags: SmallStringSet<LazyCompact> = SmallStringSet::new();
+/// assert!(tags.insert("urgent".into()));
+/// assert!(tags.insert("billing".into()));
+/// assert!(!tags.insert("urgent".into()));
+/// assert!(tags.contains("billing"));
+/// assert_eq!(vec!["billing", "urgent"], tags.iter().collect::<Vec<_>>());
+/// ```
+pub struct SmallStringSet<Mode: SmartStringMode> {
+    items: Vec<SmartString<Mode>>,
+}
+
+impl<Mode: SmartStringMode> SmallStringSet<Mode> {
+    /// Construct an empty set.
+    pub const fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    /// Construct an empty set with room for `capacity` strings.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            items: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Return the number of strings in the set.
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// Test whether the set is empty.
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    fn find(&self, value: &str) -> Result<usize, usize> {
+        self.items.binary_search_by(|item| item.as_str().cmp(value))
+    }
+
+    /// Add a string to the set.
+    ///
+    /// Returns `true` if it wasn't already present.
+    pub fn insert(&mut self, value: SmartString<Mode>) -> bool {
+        match self.find(&value) {
+            Ok(_) => false,
+            Err(index) => {
+                self.items.insert(index, value);
+                true
+            }
+        }
+    }
+
+    /// Test whether the set contains a string.
+    pub fn contains(&self, value: &str) -> bool {
+        self.find(value).is_ok()
+    }
+
+    /// Remove a string from the set, returning it if it was present.
+    pub fn remove(&mut self, value: &str) -> Option<SmartString<Mode>> {
+        self.find(value).ok().map(|index| self.items.remove(index))
+    }
+
+    /// Remove every string from the set.
+    pub fn clear(&mut self) {
+        self.items.clear()
+    }
+
+    /// Construct an iterator over the strings in the set, in sorted order.
+    pub fn iter(&self) -> SmallStringSetIter<'_, Mode> {
+        SmallStringSetIter {
+            iter: self.items.iter(),
+        }
+    }
+
+    /// Get the strings in the set as a sorted slice.
+    pub fn as_slice(&self) -> &[SmartString<Mode>] {
+        &self.items
+    }
+
+    /// Unwrap the set into a sorted [`Vec`].
+    pub fn into_vec(self) -> Vec<SmartString<Mode>> {
+        self.items
+    }
+}
+
+impl<Mode: SmartStringMode> Default for SmallStringSet<Mode> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Mode: SmartStringMode> Clone for SmallStringSet<Mode> {
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items.clone(),
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> PartialEq for SmallStringSet<Mode> {
+    fn eq(&self, other: &Self) -> bool {
+        self.items == other.items
+    }
+}
+
+impl<Mode: SmartStringMode> Eq for SmallStringSet<Mode> {}
+
+impl<Mode: SmartStringMode> Debug for SmallStringSet<Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.debug_set().entries(self.items.iter()).finish()
+    }
+}
+
+impl<Mode: SmartStringMode> Extend<SmartString<Mode>> for SmallStringSet<Mode> {
+    fn extend<I: IntoIterator<Item = SmartString<Mode>>>(&mut self, iter: I) {
+        for value in iter {
+            self.insert(value);
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> FromIterator<SmartString<Mode>> for SmallStringSet<Mode> {
+    fn from_iter<I: IntoIterator<Item = SmartString<Mode>>>(iter: I) -> Self {
+        let mut items: Vec<SmartString<Mode>> = iter.into_iter().collect();
+        items.sort_unstable();
+        items.dedup();
+        Self { items }
+    }
+}
+
+impl<'a, Mode: SmartStringMode> IntoIterator for &'a SmallStringSet<Mode> {
+    type Item = &'a str;
+    type IntoIter = SmallStringSetIter<'a, Mode>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<Mode: SmartStringMode> IntoIterator for SmallStringSet<Mode> {
+    type Item = SmartString<Mode>;
+    type IntoIter = alloc::vec::IntoIter<SmartString<Mode>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.items.into_iter()
+    }
+}
+
+/// An iterator over the strings in a [`SmallStringSet`].
+///
+/// See [`SmallStringSet::iter()`].
+pub struct SmallStringSetIter<'a, Mode: SmartStringMode> {
+    iter: Iter<'a, SmartString<Mode>>,
+}
+
+impl<'a, Mode: SmartStringMode> Iterator for SmallStringSetIter<'a, Mode> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next().map(SmartString::as_str)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, Mode: SmartStringMode> DoubleEndedIterator for SmallStringSetIter<'a, Mode> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back().map(SmartString::as_str)
+    }
+}
+
+impl<'a, Mode: SmartStringMode> ExactSizeIterator for SmallStringSetIter<'a, Mode> {}
+
+impl<'a, Mode: SmartStringMode> FusedIterator for SmallStringSetIter<'a, Mode> {}
+
+impl<'a, Mode: SmartStringMode> Debug for SmallStringSetIter<'a, Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        f.pad("SmallStringSetIter { ... }")
+    }
+}
@@ -0,0 +1,229 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{
+    arc::Shared,
+    inline::InlineString,
+    ops::{check_range, range_or_panic},
+    SmartArcString, SmartString, SmartStringMode, MAX_INLINE,
+};
+use alloc::sync::Arc;
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{Debug, Display, Error, Formatter},
+    hash::{Hash, Hasher},
+    ops::{Deref, RangeBounds},
+};
+
+#[derive(Clone)]
+enum Inner {
+    Inline(InlineString),
+    Shared {
+        string: Shared,
+        start: usize,
+        end: usize,
+    },
+}
+
+/// An immutable substring which shares its parent's heap buffer.
+///
+/// A `SmartSlice` is made by slicing a [`SmartArcString`], or another
+/// `SmartSlice`. A slice short enough to inline is copied inline, like a
+/// [`SmartString`]; a longer one keeps a reference to the parent's buffer
+/// instead of copying it, so a parser can take as many substrings of a large
+/// input as it likes without allocating for any of them.
+///
+/// A [`SmartString`]'s heap buffer belongs to it alone, so it can't be shared:
+/// convert the input into a [`SmartArcString`] first, which moves it into an
+/// [`Arc`] without copying it.
+///
+/// Keeping a slice alive keeps the whole of the parent's buffer alive, and
+/// modifying the parent while it does copies the buffer first. Convert the
+/// slice into a [`SmartString`] if you need to keep it around for longer than
+/// the parent.
+///
+/// ```rust
+/// # use smartstring::SmartArcString;
+/// let input = SmartArcString::new("let answer = 42; // a comment long enough to be boxed");
+/// let name = input.slice(4..10);
+/// assert_eq!("answer", name);
+/// assert!(name.is_inline());
+/// let comment = input.slice(17..);
+/// assert_eq!("// a comment long enough to be boxed", comment);
+/// assert!(!comment.is_inline());
+/// assert_eq!("comment", comment.slice(5..12));
+/// ```
+#[derive(Clone)]
+pub struct SmartSlice {
+    inner: Inner,
+}
+
+impl SmartSlice {
+    pub(crate) fn new(string: &Shared, start: usize, end: usize) -> Self {
+        let inner = if end - start > MAX_INLINE {
+            Inner::Shared {
+                string: Arc::clone(string),
+                start,
+                end,
+            }
+        } else {
+            Inner::Inline(string[start..end].into())
+        };
+        Self { inner }
+    }
+
+    pub(crate) fn from_inline(string: &str) -> Self {
+        Self {
+            inner: Inner::Inline(string.into()),
+        }
+    }
+
+    /// Test whether the slice is stored inline, rather than sharing its
+    /// parent's buffer.
+    pub fn is_inline(&self) -> bool {
+        matches!(self.inner, Inner::Inline(_))
+    }
+
+    /// Get the slice as a string slice.
+    pub fn as_str(&self) -> &str {
+        match &self.inner {
+            Inner::Inline(string) => string,
+            Inner::Shared { string, start, end } => &string[*start..*end],
+        }
+    }
+
+    /// Take a slice of this slice, sharing the same buffer if it's too long to
+    /// inline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't fall on UTF-8 character
+    /// boundaries.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> Self {
+        let (from, to) = range_or_panic(check_range(self, &range));
+        match &self.inner {
+            Inner::Inline(string) => Self::from_inline(&string[from..to]),
+            Inner::Shared { string, start, .. } => Self::new(string, start + from, start + to),
+        }
+    }
+
+    /// Copy the slice into a [`SmartString`].
+    pub fn to_smart_string<Mode: SmartStringMode>(&self) -> SmartString<Mode> {
+        self.as_str().into()
+    }
+}
+
+impl SmartArcString {
+    /// Take a slice of the string, sharing its buffer if the slice is too long
+    /// to inline.
+    ///
+    /// See [`SmartSlice`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds or doesn't fall on UTF-8 character
+    /// boundaries.
+    pub fn slice(&self, range: impl RangeBounds<usize>) -> SmartSlice {
+        let (start, end) = range_or_panic(check_range(self, &range));
+        match self.to_shared() {
+            Some(string) => SmartSlice::new(&string, start, end),
+            None => SmartSlice::from_inline(&self[start..end]),
+        }
+    }
+}
+
+impl Default for SmartSlice {
+    fn default() -> Self {
+        Self::from_inline("")
+    }
+}
+
+impl Deref for SmartSlice {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SmartSlice {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmartSlice {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartSlice> for SmartString<Mode> {
+    fn from(slice: SmartSlice) -> Self {
+        slice.to_smart_string()
+    }
+}
+
+impl PartialEq for SmartSlice {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_str() == other.as_str()
+    }
+}
+
+impl Eq for SmartSlice {}
+
+impl PartialEq<str> for SmartSlice {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmartSlice {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<SmartSlice> for &str {
+    fn eq(&self, other: &SmartSlice) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<SmartSlice> for str {
+    fn eq(&self, other: &SmartSlice) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialOrd for SmartSlice {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmartSlice {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for SmartSlice {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl Debug for SmartSlice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for SmartSlice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Display::fmt(self.as_str(), f)
+    }
+}
@@ -0,0 +1,203 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{boxed::BoxedString, Compact, SmartString, SmartStringMode, MAX_INLINE};
+use alloc::{boxed::Box, string::String};
+use core::{
+    borrow::Borrow,
+    cmp::Ordering,
+    fmt::{Debug, Display, Error, Formatter},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+/// An immutable string, which is to [`SmartString`] what [`Box<str>`][Box] is
+/// to [`String`].
+///
+/// Strings of up to [`MAX_INLINE`] bytes are stored inline,
+/// exactly like a [`SmartString`]. Longer strings are stored on the heap in an
+/// allocation of exactly the right size, so a `SmartStr` never holds on to
+/// capacity it doesn't use, which makes it a good fit for map keys and other
+/// long lived strings that won't change.
+///
+/// Converting between `SmartStr` and [`SmartString`] reuses the heap
+/// allocation, if there is one. Converting into a `SmartStr` shrinks it to fit,
+/// which the allocator can usually do in place.
+///
+/// A `SmartStr` is still the same size as a [`SmartString`]: it keeps the
+/// capacity word, which always matches the length, so that it can hand its
+/// allocation to a [`SmartString`] without copying.
+///
+/// ```rust
+/// # use smartstring::{SmartStr, alias::String};
+/// let mut string = String::with_capacity(100);
+/// string.push_str("a string long enough to need the heap");
+/// let frozen = SmartStr::from(string);
+/// assert_eq!("a string long enough to need the heap", frozen);
+///
+/// let mut thawed: String = frozen.into();
+/// assert_eq!(thawed.len(), thawed.capacity());
+/// thawed.push('!');
+/// ```
+#[derive(Default)]
+pub struct SmartStr {
+    // Always inline if it fits, and otherwise boxed with no spare capacity.
+    string: SmartString<Compact>,
+}
+
+impl SmartStr {
+    /// Construct a `SmartStr` holding a copy of `string`.
+    pub fn new(string: &str) -> Self {
+        let string = if string.len() > MAX_INLINE {
+            SmartString::from_boxed(BoxedString::from_str(string.len(), string))
+        } else {
+            SmartString::from_inline(string.into())
+        };
+        Self { string }
+    }
+
+    /// Test whether the string is stored inline.
+    pub fn is_inline(&self) -> bool {
+        self.string.is_inline()
+    }
+
+    /// Get the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.string.as_str()
+    }
+}
+
+impl Clone for SmartStr {
+    fn clone(&self) -> Self {
+        Self::new(self.as_str())
+    }
+}
+
+impl Deref for SmartStr {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for SmartStr {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl Borrow<str> for SmartStr {
+    fn borrow(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl From<&str> for SmartStr {
+    fn from(string: &str) -> Self {
+        Self::new(string)
+    }
+}
+
+impl From<String> for SmartStr {
+    fn from(string: String) -> Self {
+        SmartString::<Compact>::from(string).into()
+    }
+}
+
+impl From<Box<str>> for SmartStr {
+    fn from(string: Box<str>) -> Self {
+        String::from(string).into()
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartString<Mode>> for SmartStr {
+    /// Freeze a [`SmartString`], inlining it if it fits, or otherwise giving
+    /// its spare capacity back to the allocator.
+    fn from(string: SmartString<Mode>) -> Self {
+        let mut string = string.convert::<Compact>();
+        // A frozen string never moves, so it has no use for a pin, which would
+        // otherwise keep a short string on the heap.
+        string.unpin();
+        if !string.try_demote() && string.capacity() != string.len() {
+            string.shrink_to_fit();
+        }
+        Self { string }
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartStr> for SmartString<Mode> {
+    /// Thaw a `SmartStr`, reusing its heap allocation if it has one.
+    fn from(string: SmartStr) -> Self {
+        string.string.convert()
+    }
+}
+
+impl From<SmartStr> for String {
+    fn from(string: SmartStr) -> Self {
+        string.string.into()
+    }
+}
+
+impl PartialEq for SmartStr {
+    fn eq(&self, other: &Self) -> bool {
+        self.string == other.string
+    }
+}
+
+impl Eq for SmartStr {}
+
+impl PartialEq<str> for SmartStr {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for SmartStr {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<SmartStr> for &str {
+    fn eq(&self, other: &SmartStr) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<SmartStr> for str {
+    fn eq(&self, other: &SmartStr) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialOrd for SmartStr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SmartStr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for SmartStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.string.hash(state)
+    }
+}
+
+impl Debug for SmartStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for SmartStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Display::fmt(self.as_str(), f)
+    }
+}
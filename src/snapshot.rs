@@ -0,0 +1,338 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{
+    boxed::BoxedString, inline::InlineString, SmartString, SmartStringMode, StringCast, MAX_INLINE,
+};
+use alloc::vec::Vec;
+
+/// The representation a [`SmartString`] was in when it was snapshotted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ReprKind {
+    /// The string was stored inline.
+    Inline,
+    /// The string was stored on the heap.
+    Boxed,
+}
+
+/// A snapshot of the internal state of a [`SmartString`].
+///
+/// See [`SmartString::debug_snapshot()`] and [`SmartString::from_snapshot()`].
+/// With the `serde` feature enabled, it can be serialised, so it can be
+/// written out along with other diagnostics, and read back in by a test.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SmartStringSnapshot {
+    /// The representation the string was in.
+    pub kind: ReprKind,
+    /// The length of the string, in bytes.
+    pub len: usize,
+    /// The capacity of the string's buffer, in bytes.
+    pub capacity: usize,
+    /// The contents of the string.
+    pub bytes: Vec<u8>,
+}
+
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Take a snapshot of the string's internal state, for debugging.
+    pub fn debug_snapshot(&self) -> SmartStringSnapshot {
+        let (kind, capacity) = match self.cast() {
+            StringCast::Boxed(string) => (ReprKind::Boxed, string.capacity()),
+            StringCast::Inline(_) => (ReprKind::Inline, MAX_INLINE),
+        };
+        SmartStringSnapshot {
+            kind,
+            len: self.len(),
+            capacity,
+            bytes: self.as_bytes().to_vec(),
+        }
+    }
+
+    /// Reconstruct a string in exactly the state described by a snapshot.
+    ///
+    /// The string isn't normalised, so this can recreate states that the
+    /// string's [`SmartStringMode`] would usually avoid, like a short boxed
+    /// string in [`Compact`][crate::Compact] mode.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::String, ReprKind};
+    /// let mut string = String::from("a string long enough to be boxed");
+    /// string.truncate(8);
+    /// let snapshot = string.debug_snapshot();
+    /// assert_eq!(ReprKind::Boxed, snapshot.kind);
+    /// let restored = String::from_snapshot(&snapshot);
+    /// assert_eq!(snapshot, restored.debug_snapshot());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot is inconsistent: if `len` doesn't match the
+    /// length of `bytes`, `bytes` isn't valid UTF-8, or `capacity` can't hold
+    /// the string in the given representation.
+    pub fn from_snapshot(snapshot: &SmartStringSnapshot) -> Self {
+        assert_eq!(
+            snapshot.len,
+            snapshot.bytes.len(),
+            "snapshot length doesn't match its contents"
+        );
+        let string = core::str::from_utf8(&snapshot.bytes).expect("snapshot isn't valid UTF-8");
+        match snapshot.kind {
+            ReprKind::Inline => {
+                assert!(
+                    snapshot.len <= MAX_INLINE && snapshot.capacity == MAX_INLINE,
+                    "inline snapshot doesn't fit inline"
+                );
+                Self::from_inline(InlineString::from(string))
+            }
+            ReprKind::Boxed => {
+                assert!(
+                    snapshot.capacity > 0 && snapshot.capacity >= snapshot.len,
+                    "boxed snapshot has an invalid capacity"
+                );
+                Self::from_boxed(BoxedString::from_str(snapshot.capacity, string))
+            }
+        }
+    }
+}
+
+/// The serde impls are written out by hand, in the same shape `derive` would
+/// give them, because the derived ones carry `allow` attributes which clash
+/// with the crate's `forbid(rust_2018_idioms)`.
+#[cfg(feature = "serde")]
+mod serde_impls {
+    use super::{ReprKind, SmartStringSnapshot};
+    use alloc::vec::Vec;
+    use core::fmt;
+    use serde::{
+        de::{EnumAccess, Error, MapAccess, SeqAccess, VariantAccess, Visitor},
+        ser::SerializeStruct,
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    const VARIANTS: &[&str] = &["Inline", "Boxed"];
+    const FIELDS: &[&str] = &["kind", "len", "capacity", "bytes"];
+
+    impl Serialize for ReprKind {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            match self {
+                Self::Inline => serializer.serialize_unit_variant("ReprKind", 0, "Inline"),
+                Self::Boxed => serializer.serialize_unit_variant("ReprKind", 1, "Boxed"),
+            }
+        }
+    }
+
+    /// Identifies a variant of [`ReprKind`] by name or index.
+    struct VariantVisitor;
+
+    impl<'de> Visitor<'de> for VariantVisitor {
+        type Value = ReprKind;
+
+        fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+            formatter.write_str("a variant identifier")
+        }
+
+        fn visit_u64<E: Error>(self, value: u64) -> Result<Self::Value, E> {
+            match value {
+                0 => Ok(ReprKind::Inline),
+                1 => Ok(ReprKind::Boxed),
+                _ => Err(E::invalid_value(
+                    serde::de::Unexpected::Unsigned(value),
+                    &"variant index 0 <= i < 2",
+                )),
+            }
+        }
+
+        fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+            match value {
+                "Inline" => Ok(ReprKind::Inline),
+                "Boxed" => Ok(ReprKind::Boxed),
+                _ => Err(E::unknown_variant(value, VARIANTS)),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ReprKind {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct KindVisitor;
+
+            impl<'de> Visitor<'de> for KindVisitor {
+                type Value = ReprKind;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    formatter.write_str("enum ReprKind")
+                }
+
+                fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+                where
+                    A: EnumAccess<'de>,
+                {
+                    let (kind, variant) = data.variant_seed(VariantSeed)?;
+                    variant.unit_variant()?;
+                    Ok(kind)
+                }
+            }
+
+            deserializer.deserialize_enum("ReprKind", VARIANTS, KindVisitor)
+        }
+    }
+
+    /// Reads a variant identifier through [`VariantVisitor`].
+    struct VariantSeed;
+
+    impl<'de> serde::de::DeserializeSeed<'de> for VariantSeed {
+        type Value = ReprKind;
+
+        fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            deserializer.deserialize_identifier(VariantVisitor)
+        }
+    }
+
+    impl Serialize for SmartStringSnapshot {
+        fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            let mut state = serializer.serialize_struct("SmartStringSnapshot", FIELDS.len())?;
+            state.serialize_field("kind", &self.kind)?;
+            state.serialize_field("len", &self.len)?;
+            state.serialize_field("capacity", &self.capacity)?;
+            state.serialize_field("bytes", &self.bytes)?;
+            state.end()
+        }
+    }
+
+    /// The fields of a [`SmartStringSnapshot`], with anything else ignored.
+    enum Field {
+        Kind,
+        Len,
+        Capacity,
+        Bytes,
+        Other,
+    }
+
+    impl<'de> Deserialize<'de> for Field {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct FieldVisitor;
+
+            impl<'de> Visitor<'de> for FieldVisitor {
+                type Value = Field;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    formatter.write_str("a field identifier")
+                }
+
+                fn visit_u64<E: Error>(self, value: u64) -> Result<Self::Value, E> {
+                    Ok(match value {
+                        0 => Field::Kind,
+                        1 => Field::Len,
+                        2 => Field::Capacity,
+                        3 => Field::Bytes,
+                        _ => Field::Other,
+                    })
+                }
+
+                fn visit_str<E: Error>(self, value: &str) -> Result<Self::Value, E> {
+                    Ok(match value {
+                        "kind" => Field::Kind,
+                        "len" => Field::Len,
+                        "capacity" => Field::Capacity,
+                        "bytes" => Field::Bytes,
+                        _ => Field::Other,
+                    })
+                }
+            }
+
+            deserializer.deserialize_identifier(FieldVisitor)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SmartStringSnapshot {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct SnapshotVisitor;
+
+            impl<'de> Visitor<'de> for SnapshotVisitor {
+                type Value = SmartStringSnapshot;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+                    formatter.write_str("struct SmartStringSnapshot")
+                }
+
+                fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+                where
+                    A: SeqAccess<'de>,
+                {
+                    Ok(SmartStringSnapshot {
+                        kind: seq
+                            .next_element()?
+                            .ok_or_else(|| Error::invalid_length(0, &self))?,
+                        len: seq
+                            .next_element()?
+                            .ok_or_else(|| Error::invalid_length(1, &self))?,
+                        capacity: seq
+                            .next_element()?
+                            .ok_or_else(|| Error::invalid_length(2, &self))?,
+                        bytes: seq
+                            .next_element()?
+                            .ok_or_else(|| Error::invalid_length(3, &self))?,
+                    })
+                }
+
+                fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+                where
+                    A: MapAccess<'de>,
+                {
+                    let mut kind = None;
+                    let mut len = None;
+                    let mut capacity = None;
+                    let mut bytes: Option<Vec<u8>> = None;
+                    while let Some(field) = map.next_key()? {
+                        match field {
+                            Field::Kind if kind.is_some() => {
+                                return Err(Error::duplicate_field("kind"))
+                            }
+                            Field::Kind => kind = Some(map.next_value()?),
+                            Field::Len if len.is_some() => {
+                                return Err(Error::duplicate_field("len"))
+                            }
+                            Field::Len => len = Some(map.next_value()?),
+                            Field::Capacity if capacity.is_some() => {
+                                return Err(Error::duplicate_field("capacity"))
+                            }
+                            Field::Capacity => capacity = Some(map.next_value()?),
+                            Field::Bytes if bytes.is_some() => {
+                                return Err(Error::duplicate_field("bytes"))
+                            }
+                            Field::Bytes => bytes = Some(map.next_value()?),
+                            Field::Other => {
+                                map.next_value::<serde::de::IgnoredAny>()?;
+                            }
+                        }
+                    }
+                    Ok(SmartStringSnapshot {
+                        kind: kind.ok_or_else(|| Error::missing_field("kind"))?,
+                        len: len.ok_or_else(|| Error::missing_field("len"))?,
+                        capacity: capacity.ok_or_else(|| Error::missing_field("capacity"))?,
+                        bytes: bytes.ok_or_else(|| Error::missing_field("bytes"))?,
+                    })
+                }
+            }
+
+            deserializer.deserialize_struct("SmartStringSnapshot", FIELDS, SnapshotVisitor)
+        }
+    }
+}
@@ -0,0 +1,187 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{inline::InlineString, SmartString, SmartStringMode, MAX_INLINE};
+use core::{
+    cmp::Ordering,
+    fmt::{Debug, Display, Error, Formatter},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+/// An immutable inline string which can be constructed in a const context.
+///
+/// This holds up to [`MAX_INLINE`] bytes, with the same layout as an inline
+/// [`SmartString`], and never allocates. It has no interior mutability and
+/// is [`Copy`], [`Send`] and [`Sync`], so it can live in a `static` on any
+/// target, with or without `std`, and be turned into a [`SmartString`]
+/// without copying the string data anywhere but the stack.
+///
+/// [`SmartString`] itself is [`Send`] and [`Sync`] too, and
+/// [`SmartString::new_const()`] can initialise an empty one in a `static`, but a
+/// [`SmartString`] can't be built from string data in a const context.
+///
+/// ```rust
+/// # use smartstring::{StaticSmartString, alias::String};
+/// static GREETING: StaticSmartString = StaticSmartString::new("hello");
+///
+/// assert_eq!("hello", GREETING);
+/// let mut string: String = GREETING.into();
+/// string.push_str(" world");
+/// assert_eq!("hello world", string);
+/// ```
+///
+/// A string that's too long to inline fails to compile when used in a const
+/// context:
+///
+/// ```compile_fail
+/// # use smartstring::StaticSmartString;
+/// static TOO_LONG: StaticSmartString =
+///     StaticSmartString::new("a string much too long to be stored inline");
+/// ```
+#[derive(Clone, Copy)]
+#[repr(transparent)]
+pub struct StaticSmartString {
+    inline: InlineString,
+}
+
+impl StaticSmartString {
+    /// Construct a string holding a copy of `string`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `string` is longer than [`MAX_INLINE`] bytes, which in a const
+    /// context is a compile error.
+    pub const fn new(string: &str) -> Self {
+        Self {
+            inline: InlineString::from_str_const(string),
+        }
+    }
+
+    /// Construct a string holding a copy of `string`, or return `None` if it's
+    /// longer than [`MAX_INLINE`] bytes.
+    pub fn try_new(string: &str) -> Option<Self> {
+        if string.len() <= MAX_INLINE {
+            Some(Self {
+                inline: string.into(),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Construct an empty string.
+    pub const fn empty() -> Self {
+        Self {
+            inline: InlineString::new(),
+        }
+    }
+
+    /// Get the string as a string slice.
+    pub fn as_str(&self) -> &str {
+        self.inline.deref()
+    }
+
+    /// View an inline string as a `StaticSmartString`, which has the same
+    /// layout.
+    pub(crate) fn from_inline_ref(inline: &InlineString) -> &Self {
+        #[allow(unsafe_code)]
+        unsafe {
+            &*(inline as *const InlineString).cast()
+        }
+    }
+
+    /// Copy the string into a [`SmartString`], which will be inline.
+    pub fn to_smart_string<Mode: SmartStringMode>(&self) -> SmartString<Mode> {
+        SmartString::from_inline(self.inline)
+    }
+}
+
+impl Default for StaticSmartString {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Deref for StaticSmartString {
+    type Target = str;
+
+    fn deref(&self) -> &Self::Target {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for StaticSmartString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl<Mode: SmartStringMode> From<StaticSmartString> for SmartString<Mode> {
+    fn from(string: StaticSmartString) -> Self {
+        string.to_smart_string()
+    }
+}
+
+impl PartialEq for StaticSmartString {
+    fn eq(&self, other: &Self) -> bool {
+        self.inline.eq_words(&other.inline)
+    }
+}
+
+impl Eq for StaticSmartString {}
+
+impl PartialEq<str> for StaticSmartString {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl PartialEq<&str> for StaticSmartString {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<StaticSmartString> for &str {
+    fn eq(&self, other: &StaticSmartString) -> bool {
+        *self == other.as_str()
+    }
+}
+
+impl PartialEq<StaticSmartString> for str {
+    fn eq(&self, other: &StaticSmartString) -> bool {
+        self == other.as_str()
+    }
+}
+
+impl PartialOrd for StaticSmartString {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for StaticSmartString {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_str().cmp(other.as_str())
+    }
+}
+
+impl Hash for StaticSmartString {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_str().hash(state)
+    }
+}
+
+impl Debug for StaticSmartString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl Display for StaticSmartString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Display::fmt(self.as_str(), f)
+    }
+}
@@ -2,15 +2,24 @@
 // License, v. 2.0. If a copy of the MPL was not distributed with this
 // file, You can obtain one at http://mozilla.org/MPL/2.0/.
 
+#![allow(clippy::legacy_numeric_constants)]
+
 use crate::{config::MAX_INLINE, SmartString, SmartStringMode};
 use std::{
     cmp::Ordering,
     fmt::Debug,
     iter::FromIterator,
-    ops::{Index, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive},
+    ops::{Bound, Index, Range, RangeFrom, RangeFull, RangeInclusive, RangeTo, RangeToInclusive},
     panic::{catch_unwind, set_hook, take_hook, AssertUnwindSafe},
 };
 
+pub mod counting_allocator;
+
+#[cfg(test)]
+#[global_allocator]
+static ALLOCATOR: counting_allocator::CountingAllocator =
+    counting_allocator::CountingAllocator::new();
+
 #[cfg(not(test))]
 use arbitrary::Arbitrary;
 #[cfg(test)]
@@ -32,6 +41,29 @@ where
     );
 }
 
+/// Run an action which should panic, and return its panic message.
+///
+/// This leaves the panic hook alone, because swapping it races with other
+/// tests, so the panic is still reported on stderr.
+#[cfg(test)]
+pub(crate) fn panic_message<A, F>(f: F) -> String
+where
+    F: FnOnce() -> A,
+{
+    let result = catch_unwind(AssertUnwindSafe(f));
+    let payload = match result {
+        Ok(_) => panic!("action that should have panicked didn't panic"),
+        Err(payload) => payload,
+    };
+    match payload.downcast::<String>() {
+        Ok(message) => *message,
+        Err(payload) => payload
+            .downcast_ref::<&str>()
+            .map(|message| message.to_string())
+            .unwrap_or_default(),
+    }
+}
+
 #[derive(Arbitrary, Debug, Clone)]
 pub enum Constructor {
     New,
@@ -94,6 +126,17 @@ impl TestBounds {
         }
     }
 
+    fn bounds(&self) -> (Bound<usize>, Bound<usize>) {
+        match *self {
+            Self::Range(start, end) => (Bound::Included(start), Bound::Excluded(end)),
+            Self::From(start) => (Bound::Included(start), Bound::Unbounded),
+            Self::To(end) => (Bound::Unbounded, Bound::Excluded(end)),
+            Self::Full => (Bound::Unbounded, Bound::Unbounded),
+            Self::Inclusive(start, end) => (Bound::Included(start), Bound::Included(end)),
+            Self::ToInclusive(end) => (Bound::Unbounded, Bound::Included(end)),
+        }
+    }
+
     fn assert_range<A, B>(&self, control: &A, subject: &B)
     where
         A: Index<Range<usize>>,
@@ -154,6 +197,7 @@ pub enum Action {
     Retain(String),
     Drain(TestBounds),
     ReplaceRange(TestBounds, String),
+    ReplaceRangeNear(usize, usize, String),
 }
 
 impl Action {
@@ -285,62 +329,22 @@ impl Action {
                     TestBounds::ToInclusive(end) if end == usize::max_value() => return,
                     _ => {}
                 }
+                let bounds = range.bounds();
                 if range.should_panic(control) {
-                    assert_panic(|| match range {
-                        TestBounds::Range(start, end) => {
-                            control.replace_range(start..end, &string);
-                            subject.replace_range(start..end, &string);
-                        }
-                        TestBounds::From(start) => {
-                            control.replace_range(start.., &string);
-                            subject.replace_range(start.., &string);
-                        }
-                        TestBounds::To(end) => {
-                            control.replace_range(..end, &string);
-                            subject.replace_range(..end, &string);
-                        }
-                        TestBounds::Full => {
-                            control.replace_range(.., &string);
-                            subject.replace_range(.., &string);
-                        }
-                        TestBounds::Inclusive(start, end) => {
-                            control.replace_range(start..=end, &string);
-                            subject.replace_range(start..=end, &string);
-                        }
-                        TestBounds::ToInclusive(end) => {
-                            control.replace_range(..=end, &string);
-                            subject.replace_range(..=end, &string);
-                        }
-                    })
+                    assert_panic(|| control.replace_range(bounds, &string));
+                    assert_panic(|| subject.replace_range(bounds, &string));
                 } else {
-                    match range {
-                        TestBounds::Range(start, end) => {
-                            control.replace_range(start..end, &string);
-                            subject.replace_range(start..end, &string);
-                        }
-                        TestBounds::From(start) => {
-                            control.replace_range(start.., &string);
-                            subject.replace_range(start.., &string);
-                        }
-                        TestBounds::To(end) => {
-                            control.replace_range(..end, &string);
-                            subject.replace_range(..end, &string);
-                        }
-                        TestBounds::Full => {
-                            control.replace_range(.., &string);
-                            subject.replace_range(.., &string);
-                        }
-                        TestBounds::Inclusive(start, end) => {
-                            control.replace_range(start..=end, &string);
-                            subject.replace_range(start..=end, &string);
-                        }
-                        TestBounds::ToInclusive(end) => {
-                            control.replace_range(..=end, &string);
-                            subject.replace_range(..=end, &string);
-                        }
-                    }
+                    control.replace_range(bounds, &string);
+                    subject.replace_range(bounds, &string);
                 }
             }
+            Self::ReplaceRangeNear(start, end, string) => {
+                // Keep the indices within two bytes past the end of the string, to
+                // make degenerate and inverted ranges around the end likely.
+                let len = control.len() + 2;
+                Self::ReplaceRange(TestBounds::Range(start % len, end % len), string)
+                    .perform(control, subject)
+            }
         }
     }
 }
@@ -380,11 +384,30 @@ pub fn test_ordering<Mode: SmartStringMode>(left: String, right: String) {
     assert_eq!(left.cmp(&right), smart_left.cmp(&smart_right));
 }
 
+/// Check equality against `str`, including strings with leftover bytes
+/// beyond their end.
+pub fn test_eq<Mode: SmartStringMode>(left: String, right: String) {
+    let smart_left = SmartString::<Mode>::from(&left);
+    let smart_right = SmartString::<Mode>::from(&right);
+    assert_eq!(left == right, smart_left == smart_right);
+    let mut dirty_left = SmartString::<Mode>::from(format!("{}{}", left, right));
+    dirty_left.truncate(left.len());
+    let mut dirty_right = SmartString::<Mode>::from(format!("{}{}", right, left));
+    dirty_right.truncate(right.len());
+    assert_eq!(left == right, dirty_left == dirty_right);
+    assert_eq!(left == right, smart_left == dirty_right);
+    assert!(dirty_left == smart_left);
+}
+
 #[cfg(test)]
 mod tests {
     use super::{Action::*, Constructor::*, TestBounds::*, *};
 
-    use crate::{Compact, LazyCompact};
+    #[cfg(target_has_atomic = "ptr")]
+    use crate::SmartArcString;
+    use crate::{Compact, LazyCompact, SegmentedSmartString, SmartStr, StaticSmartString};
+
+    const LONG: &str = "a string long enough to need the heap";
 
     proptest! {
         #[test]
@@ -407,6 +430,12 @@ mod tests {
             test_ordering::<LazyCompact>(left,right)
         }
 
+        #[test]
+        fn proptest_eq_inline(left in ".{0,12}", right in ".{0,12}") {
+            test_eq::<Compact>(left.clone(), right.clone());
+            test_eq::<LazyCompact>(left, right);
+        }
+
         #[test]
         fn proptest_eq(left: String, right: String) {
             fn test_eq<Mode: SmartStringMode>(left: &str, right: &str) {
@@ -563,4 +592,2183 @@ mod tests {
         assert_eq!(std_s, unsmart_s);
         // This test exists just to provoke a Miri problem when dropping a string created by SmartString::into::<String>() (#28)
     }
+
+    #[test]
+    fn starts_and_ends_with_any() {
+        let s = SmartString::<Compact>::from("/api/v1/users");
+        assert_eq!(Some(1), s.starts_with_any(&["/static", "/api", "/"]));
+        assert_eq!(None, s.starts_with_any(&["/static", "api"]));
+        assert_eq!(Some(0), s.starts_with_any(&[""]));
+        assert_eq!(Some(2), s.ends_with_any(&["/posts", "user", "users"]));
+        assert_eq!(None, SmartString::<Compact>::new().ends_with_any(&["x"]));
+        assert_eq!(Some(0), s.starts_with_any(&["/api/v1", "/api"]));
+        assert_eq!(Some(0), s.starts_with_any(&["/api", "/api/v1"]));
+        assert_eq!(Some(1), s.ends_with_any(&["/api/v1/users/", "/users", "s"]));
+
+        // Needles are matched 64 at a time, so check either side of that.
+        let many: Vec<String> = (0..130).map(|index| format!("/{}", index)).collect();
+        for index in [0, 63, 64, 65, 127, 128, 129] {
+            let path = SmartString::<Compact>::from(format!("/{}", index));
+            let needles: Vec<&str> = many.iter().map(String::as_str).collect();
+            let expected = needles.iter().position(|needle| path.starts_with(needle));
+            assert_eq!(expected, path.starts_with_any(&needles));
+            let expected = needles.iter().position(|needle| path.ends_with(needle));
+            assert_eq!(expected, path.ends_with_any(&needles));
+        }
+
+        // Needles are filtered by their first byte before any of that.
+        let bytes: Vec<String> = (0..130u8).map(|byte| (byte as char).to_string()).collect();
+        let needles: Vec<&str> = bytes.iter().map(String::as_str).collect();
+        assert_eq!(
+            Some(129),
+            SmartString::<Compact>::from("\u{81}").starts_with_any(&needles)
+        );
+        assert_eq!(Some(1), s.starts_with_any(&["x", "", "/"]));
+        assert_eq!(
+            Some(1),
+            SmartString::<Compact>::new().ends_with_any(&["x", "", "/"])
+        );
+        assert_eq!(Some(b's' as usize), s.ends_with_any(&needles));
+        assert_eq!(Some(b'/' as usize), s.starts_with_any(&needles));
+    }
+
+    #[test]
+    fn byte_keyed_lookup() {
+        use crate::ByteKeyed;
+        use std::collections::{BTreeSet, HashSet};
+
+        let short = SmartString::<Compact>::from("short");
+        let long = SmartString::<Compact>::from("a string long enough to be boxed");
+        let hashed: HashSet<_> = [short.clone(), long.clone()]
+            .into_iter()
+            .map(ByteKeyed::new)
+            .collect();
+        let sorted: BTreeSet<_> = [short, long].into_iter().map(ByteKeyed::new).collect();
+        for key in [&b"short"[..], b"a string long enough to be boxed"] {
+            assert!(hashed.contains(key));
+            assert!(sorted.contains(key));
+        }
+        assert!(!hashed.contains(&b"shor"[..]));
+    }
+
+    #[test]
+    fn append_steals_boxed_buffer() {
+        let long = "a string long enough to be boxed";
+        let mut left = SmartString::<Compact>::new();
+        let mut right = SmartString::<Compact>::from(long);
+        let ptr = right.as_ptr();
+        left.append(&mut right);
+        assert_eq!(long, left);
+        assert_eq!(ptr, left.as_ptr());
+        assert!(right.is_empty());
+        assert!(right.is_inline());
+
+        let mut right = SmartString::<Compact>::from("!");
+        left.append(&mut right);
+        assert_eq!("a string long enough to be boxed!", left);
+        assert!(right.is_empty());
+    }
+
+    #[test]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn panic_messages_match_string() {
+        let control = || String::from("h\u{e9}llo");
+        let subject = || SmartString::<Compact>::from("h\u{e9}llo");
+        assert_eq!(
+            panic_message(|| control().drain(3..1).count()),
+            panic_message(|| subject().drain(3..1).count())
+        );
+        assert_eq!(
+            panic_message(|| control().drain(1..20).count()),
+            panic_message(|| subject().drain(1..20).count())
+        );
+        assert_eq!(
+            panic_message(|| control().replace_range(3..1, "")),
+            panic_message(|| subject().replace_range(3..1, ""))
+        );
+        assert_eq!(
+            panic_message(|| control().replace_range(1..20, "")),
+            panic_message(|| subject().replace_range(1..20, ""))
+        );
+        assert_eq!(
+            panic_message(|| control().replace_range(2..3, "")),
+            panic_message(|| subject().replace_range(2..3, ""))
+        );
+        assert_eq!(
+            panic_message(|| control().replace_range(0..=1, "")),
+            panic_message(|| subject().replace_range(0..=1, ""))
+        );
+        assert_eq!(
+            panic_message(|| control().replace_range(2..20, "")),
+            panic_message(|| subject().replace_range(2..20, ""))
+        );
+        assert_eq!(
+            panic_message(|| control().remove(6)),
+            panic_message(|| subject().remove(6))
+        );
+        // `String`'s own messages for these vary between assertions, so we
+        // settle for the wording its slicing uses.
+        assert_eq!(
+            "byte index 2 is not a char boundary",
+            panic_message(|| subject().insert(2, 'x'))
+        );
+        assert_eq!(
+            "byte index 2 is not a char boundary",
+            panic_message(|| subject().truncate(2))
+        );
+    }
+
+    #[test]
+    fn fallible_growth_ops() {
+        let mut control = String::new();
+        let mut subject = SmartString::<Compact>::new();
+        control.push('a');
+        subject.try_push('a').unwrap();
+        control.push_str("bcdefghijklmnopqrstuvwxyz");
+        subject.try_push_str("bcdefghijklmnopqrstuvwxyz").unwrap();
+        control.insert(0, '<');
+        subject.try_insert(0, '<').unwrap();
+        control.insert_str(1, "ÅÄÖ");
+        subject.try_insert_str(1, "ÅÄÖ").unwrap();
+        control.replace_range(5.., "!");
+        subject.try_replace_range(5.., "!").unwrap();
+        assert_eq!(control, subject);
+        assert!(subject.is_inline());
+        let long: String = (0..1000).map(|_| 'x').collect();
+        subject.try_push_str(&long).unwrap();
+        subject.try_push_str(&long).unwrap();
+        control.push_str(&long);
+        control.push_str(&long);
+        assert_eq!(control, subject);
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn fallible_growth_past_flag_bits() {
+        use crate::boxed::BoxedString;
+
+        // Capacities which would overlap the digest bits are refused with an
+        // error rather than a panic, whatever the mode.
+        let mut string = BoxedString::new(64);
+        assert!(string.try_ensure_capacity(1 << 46).is_err());
+        assert_eq!(64, string.capacity());
+        assert!(BoxedString::try_from_str(1 << 46, "").is_err());
+    }
+
+    #[test]
+    fn clone_compacts_short_boxed_strings() {
+        let mut s = SmartString::<LazyCompact>::from("a string long enough to be boxed");
+        s.truncate(5);
+        assert!(!s.is_inline());
+        let clone = s.clone();
+        assert_eq!(s, clone);
+        assert!(clone.is_inline());
+    }
+
+    #[test]
+    fn pad_to_matches_format() {
+        use std::fmt::Alignment;
+
+        for content in ["", "ab", "ÅÄÖ", "a string long enough to be boxed"] {
+            for width in [0, 3, 8, 23, 24, 40] {
+                for fill in ['.', 'ü', '🌀'] {
+                    for (align, control) in [
+                        (Alignment::Left, format!("{:.<1$}", content, width)),
+                        (Alignment::Right, format!("{:.>1$}", content, width)),
+                        (Alignment::Center, format!("{:.^1$}", content, width)),
+                    ] {
+                        let control = control.replace('.', fill.encode_utf8(&mut [0; 4]));
+                        let mut subject = SmartString::<Compact>::from(content);
+                        subject.pad_to(width, fill, align);
+                        assert_eq!(control, subject);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inline_chunks() {
+        let string: String = "abcdefghij🌀ÅÄÖ".repeat(10);
+        let smart = SmartString::<LazyCompact>::from(&string);
+        let chunks: Vec<_> = smart.inline_chunks().collect();
+        assert!(chunks.iter().all(|chunk| chunk.is_inline()));
+        assert!(chunks[..chunks.len() - 1]
+            .iter()
+            .all(|chunk| chunk.len() > MAX_INLINE - 4));
+        assert_eq!(string, chunks.concat());
+        assert_eq!(0, SmartString::<LazyCompact>::new().inline_chunks().count());
+    }
+
+    #[test]
+    fn hash_matches_str() {
+        use crate::HashedSmartString;
+        use std::collections::hash_map::{DefaultHasher, RandomState};
+        use std::hash::{BuildHasher, Hash, Hasher};
+
+        fn hash<A: Hash + ?Sized>(value: &A) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let build_hasher = RandomState::new();
+        for string in ["", "short", "a string long enough to be boxed"] {
+            let mut lazy = SmartString::<LazyCompact>::from(string);
+            assert_eq!(hash(string), hash(&lazy));
+            assert_eq!(hash(string), hash(&SmartString::<Compact>::from(string)));
+            lazy.push_str("a string long enough to be boxed");
+            lazy.truncate(string.len());
+            assert_eq!(hash(string), hash(&lazy));
+
+            let hashed = HashedSmartString::new(lazy, &build_hasher);
+            assert_eq!(build_hasher.hash_one(string), hashed.hash_value());
+            assert_eq!(
+                hashed,
+                HashedSmartString::new(SmartString::from(string), &build_hasher)
+            );
+        }
+    }
+
+    #[test]
+    fn normalize_whitespace() {
+        use std::borrow::Cow;
+
+        for string in [
+            "",
+            " ",
+            "clean",
+            "clean string",
+            "  \t lots\u{a0}of \n\r  whitespace\u{3000}in  a string long enough to be boxed  ",
+            "ÅÄÖ \u{2028} åäö",
+        ] {
+            let control = string.split_whitespace().collect::<Vec<_>>().join(" ");
+            let mut subject = SmartString::<Compact>::from(string);
+            let result = subject.normalize_whitespace();
+            assert_eq!(control, result);
+            assert_eq!(control == string, matches!(result, Cow::Borrowed(_)));
+            subject.normalize_whitespace_in_place();
+            assert_eq!(control, subject);
+            assert_eq!(subject.len() <= MAX_INLINE, subject.is_inline());
+        }
+    }
+
+    #[test]
+    fn to_smart_string() {
+        use crate::ToSmartString;
+
+        let short: SmartString<Compact> = 'ü'.to_smart_string();
+        assert_eq!("ü", short);
+        assert!(short.is_inline());
+        let long: SmartString<LazyCompact> = std::f64::consts::PI.to_smart_string();
+        assert_eq!(std::f64::consts::PI.to_string(), long);
+        let long: SmartString<LazyCompact> = "a string long enough to be boxed".to_smart_string();
+        assert!(!long.is_inline());
+    }
+
+    #[test]
+    fn replace_range_degenerate_ranges() {
+        let short = || FromString("short".to_string());
+        let long = || FromString("a string long enough to be boxed".to_string());
+        let empty = String::new;
+        let long_string = || "a replacement long enough to be boxed".to_string();
+        for actions in [
+            vec![ReplaceRange(Range(5, 5), empty())],
+            vec![ReplaceRange(Range(5, 5), "!".to_string())],
+            vec![ReplaceRange(From(5), long_string())],
+            vec![ReplaceRange(Range(6, 6), empty())],
+            vec![ReplaceRange(Range(3, 2), empty())],
+            vec![ReplaceRange(Inclusive(3, 2), "!".to_string())],
+            vec![ReplaceRange(Full, long_string())],
+            vec![ReplaceRange(Full, empty())],
+            vec![ReplaceRangeNear(usize::MAX, 7, empty())],
+        ] {
+            test_everything::<Compact>(short(), actions.clone());
+            test_everything::<LazyCompact>(short(), actions);
+        }
+        for actions in [
+            vec![ReplaceRange(Full, "short".to_string())],
+            vec![ReplaceRange(Range(32, 32), empty())],
+            vec![ReplaceRange(To(30), empty())],
+            vec![ReplaceRange(Range(33, 32), empty())],
+            vec![ReplaceRange(Range(0, 33), empty())],
+        ] {
+            test_everything::<Compact>(long(), actions.clone());
+            test_everything::<LazyCompact>(long(), actions);
+        }
+        test_everything::<Compact>(
+            FromString("ÅÄÖ".to_string()),
+            vec![
+                ReplaceRange(Range(1, 2), empty()),
+                ReplaceRange(Range(0, 1), empty()),
+                ReplaceRange(From(5), empty()),
+            ],
+        );
+    }
+
+    #[test]
+    fn into_path_and_os_string() {
+        use std::{ffi::OsString, path::PathBuf};
+
+        for string in ["short", "a/path/long/enough/to/be/boxed"] {
+            let smart = SmartString::<LazyCompact>::from(string);
+            assert_eq!(PathBuf::from(string), PathBuf::from(smart.clone()));
+            assert_eq!(OsString::from(string), OsString::from(smart.clone()));
+            assert_eq!(PathBuf::from(string), PathBuf::from("").join(&smart));
+        }
+    }
+
+    #[test]
+    fn join_and_concat_slices() {
+        let short: Vec<SmartString<Compact>> = vec!["a".into(), "b".into(), "c".into()];
+        let long: Vec<String> = (0..10).map(|i| format!("field number {}", i)).collect();
+        let joined = SmartString::<Compact>::join(", ", &short);
+        assert_eq!("a, b, c", joined);
+        assert!(joined.is_inline());
+        let joined = SmartString::<LazyCompact>::join(",", &long);
+        assert_eq!(long.join(","), joined);
+        assert_eq!(long.join(",").len(), joined.capacity());
+        let concat = SmartString::<LazyCompact>::concat(&long);
+        assert_eq!(long.concat(), concat);
+        let empty = SmartString::<LazyCompact>::join(",", &[] as &[&str]);
+        assert!(empty.is_empty());
+    }
+
+    #[cfg(feature = "debug-canary")]
+    #[test]
+    #[should_panic(expected = "representation is corrupt")]
+    fn debug_canary_catches_corrupt_marker() {
+        // Don't drop the corrupted string, or the canary fires again while unwinding.
+        let mut s = core::mem::ManuallyDrop::new(SmartString::<Compact>::from("hello"));
+        assert_eq!("hello", s.as_str());
+        #[allow(unsafe_code)]
+        unsafe {
+            let marker: *mut u8 = core::ptr::addr_of_mut!((*s.data.as_mut_ptr()).marker).cast();
+            *marker ^= 0x40;
+        }
+        let _ = s.len();
+    }
+
+    #[cfg(feature = "percent-encoding")]
+    #[test]
+    fn from_percent_encoded() {
+        let short = SmartString::<Compact>::from_percent_encoded("a%2Fb%20c").unwrap();
+        assert_eq!("a/b c", short);
+        assert!(short.is_inline());
+        let encoded = "%E2%9C%93".repeat(4);
+        let long = SmartString::<Compact>::from_percent_encoded(&encoded).unwrap();
+        assert_eq!("\u{2713}".repeat(4), long);
+        assert!(long.is_inline());
+        let longer = SmartString::<Compact>::from_percent_encoded(&"%41bc".repeat(10)).unwrap();
+        assert_eq!("Abc".repeat(10), longer);
+        assert!(!longer.is_inline());
+        let error = SmartString::<Compact>::from_percent_encoded("ok%C3").unwrap_err();
+        assert_eq!(2, error.utf8_error().valid_up_to());
+        assert!(SmartString::<Compact>::from_percent_encoded(&"%FF".repeat(10)).is_err());
+    }
+
+    #[cfg(all(feature = "hex", feature = "base64"))]
+    #[test]
+    fn encode_hex_and_base64() {
+        let digest: Vec<u8> = (0..32).collect();
+        for data in [
+            &digest[..0],
+            &digest[..3],
+            &digest[..11],
+            &digest[..12],
+            &digest[..],
+        ] {
+            let hex = SmartString::<Compact>::encode_hex(data);
+            assert_eq!(data.len() * 2, hex.len());
+            assert_eq!(hex.len() <= MAX_INLINE, hex.is_inline());
+            if !hex.is_inline() {
+                assert!(hex.capacity() <= hex.len().max(MAX_INLINE * 2));
+            }
+            assert_eq!(data, &hex.decode_hex().unwrap()[..]);
+            let base64 = SmartString::<Compact>::encode_base64(data);
+            assert_eq!(base64.len() <= MAX_INLINE, base64.is_inline());
+            assert_eq!(data, &base64.decode_base64().unwrap()[..]);
+        }
+        assert!(SmartString::<Compact>::from("xyz").decode_hex().is_err());
+        assert!(SmartString::<Compact>::from("!!").decode_base64().is_err());
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn from_uuid() {
+        use crate::UuidFormat;
+        use uuid::Uuid;
+
+        let id = Uuid::from_u128(0x67e5504410b1426f9247bb680e5fe0c8);
+        for format in [
+            UuidFormat::Simple,
+            UuidFormat::Hyphenated,
+            UuidFormat::Braced,
+            UuidFormat::Urn,
+        ] {
+            let expected = match format {
+                UuidFormat::Simple => id.simple().to_string(),
+                UuidFormat::Hyphenated => id.hyphenated().to_string(),
+                UuidFormat::Braced => id.braced().to_string(),
+                UuidFormat::Urn => id.urn().to_string(),
+            };
+            let formatted = SmartString::<Compact>::from_uuid(id, format);
+            assert_eq!(expected, formatted);
+            assert_eq!(expected.len() <= MAX_INLINE, formatted.is_inline());
+        }
+        assert_eq!(id.to_string(), SmartString::<LazyCompact>::from(id));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[test]
+    fn from_rfc3339() {
+        use chrono::{FixedOffset, SecondsFormat, TimeZone, Timelike, Utc};
+
+        let utc = Utc
+            .with_ymd_and_hms(2022, 2, 22, 22, 22, 22)
+            .unwrap()
+            .with_nanosecond(123_456_000)
+            .unwrap();
+        let offset = utc.with_timezone(&FixedOffset::east_opt(5 * 3600 + 1800).unwrap());
+        let formats = [
+            SecondsFormat::Secs,
+            SecondsFormat::Millis,
+            SecondsFormat::Micros,
+            SecondsFormat::Nanos,
+            SecondsFormat::AutoSi,
+        ];
+        for format in formats {
+            for use_z in [false, true] {
+                let expected = utc.to_rfc3339_opts(format, use_z);
+                let formatted = SmartString::<Compact>::from_rfc3339(&utc, format, use_z);
+                assert_eq!(expected, formatted);
+                assert_eq!(expected.len() <= MAX_INLINE, formatted.is_inline());
+                let expected = offset.to_rfc3339_opts(format, use_z);
+                assert_eq!(
+                    expected,
+                    SmartString::<Compact>::from_rfc3339(&offset, format, use_z)
+                );
+            }
+        }
+        assert_eq!(
+            "2022-02-22",
+            SmartString::<Compact>::from_datetime(&utc, "%Y-%m-%d")
+        );
+    }
+
+    #[test]
+    fn push_ascii_and_bytes() {
+        let mut s = SmartString::<Compact>::new();
+        for byte in (b'a'..=b'z').cycle().take(40) {
+            s.push_ascii(byte);
+        }
+        assert_eq!("abcdefghijklmnopqrstuvwxyzabcdefghijklmn", s);
+        assert!(!s.is_inline());
+        let mut s = SmartString::<Compact>::from("x".repeat(MAX_INLINE - 1).as_str());
+        #[allow(unsafe_code)]
+        unsafe {
+            for &byte in "\u{e9}!".as_bytes() {
+                s.push_byte_unchecked(byte);
+            }
+        }
+        assert_eq!(format!("{}\u{e9}!", "x".repeat(MAX_INLINE - 1)), s);
+    }
+
+    #[test]
+    #[should_panic(expected = "not ASCII")]
+    fn push_ascii_rejects_non_ascii() {
+        SmartString::<Compact>::new().push_ascii(0xc3);
+    }
+
+    #[cfg(feature = "bumpalo")]
+    #[test]
+    fn bump_str_round_trip() {
+        let arena = bumpalo::Bump::new();
+        let views: Vec<&str> = ["short", "a string long enough to live on the heap"]
+            .iter()
+            .map(|&string| SmartString::<Compact>::from(string).as_bump_str(&arena))
+            .collect();
+        assert_eq!("short", views[0]);
+        assert_eq!("a string long enough to live on the heap", views[1]);
+        let back = SmartString::<Compact>::from_bump_str(views[0]);
+        assert_eq!("short", back);
+        assert!(back.is_inline());
+    }
+
+    #[cfg(feature = "debug-tools")]
+    #[test]
+    fn debug_snapshot_round_trip() {
+        use crate::ReprKind;
+
+        let mut boxed = SmartString::<LazyCompact>::from("a string long enough to be boxed");
+        boxed.truncate(5);
+        boxed.shrink_to_fit();
+        let inline = SmartString::<LazyCompact>::from("inline");
+        for string in [boxed, inline] {
+            let snapshot = string.debug_snapshot();
+            assert_eq!(string.is_inline(), snapshot.kind == ReprKind::Inline);
+            assert_eq!(string.capacity(), snapshot.capacity);
+            let restored = SmartString::<Compact>::from_snapshot(&snapshot);
+            assert_eq!(snapshot, restored.debug_snapshot());
+            assert_eq!(string, restored.as_str());
+        }
+    }
+
+    #[cfg(all(feature = "debug-tools", feature = "serde"))]
+    #[test]
+    fn debug_snapshot_serde() {
+        use crate::{ReprKind, SmartStringSnapshot};
+
+        let string = SmartString::<LazyCompact>::from("snap");
+        let snapshot = string.debug_snapshot();
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert_eq!(
+            format!(
+                r#"{{"kind":"Inline","len":4,"capacity":{},"bytes":[115,110,97,112]}}"#,
+                MAX_INLINE
+            ),
+            json
+        );
+        let back: SmartStringSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(snapshot, back);
+        let back: SmartStringSnapshot =
+            serde_json::from_str(r#"["Boxed",4,64,[115,110,97,112]]"#).unwrap();
+        assert_eq!(ReprKind::Boxed, back.kind);
+        assert_eq!(64, back.capacity);
+    }
+
+    #[test]
+    fn compact_if() {
+        let long = "a string long enough to be boxed, with room to spare";
+        let mut string = SmartString::<LazyCompact>::from(long);
+        string.push_str(long);
+        let cap = string.capacity();
+        assert_eq!(0, string.compact_if(|len, cap| len < cap / 4));
+        string.truncate(long.len());
+        let mut seen = None;
+        let reclaimed = string.compact_if(|len, cap| {
+            seen = Some((len, cap));
+            true
+        });
+        assert_eq!(Some((long.len(), cap)), seen);
+        assert_eq!(cap - long.len(), reclaimed);
+        assert_eq!(long.len(), string.capacity());
+        string.truncate(4);
+        assert_eq!(long.len(), string.compact());
+        assert!(string.is_inline());
+        assert_eq!(0, string.compact_if(|_, _| unreachable!()));
+    }
+
+    #[test]
+    fn escape_json_into() {
+        let inputs = [
+            "",
+            "plain",
+            "\"quoted\" and \\backslashed\\",
+            "tab\tnewline\ncr\rbell\u{7}nul\u{0}esc\u{1b}bs\u{8}ff\u{c}",
+            "non-ASCII: \u{e9}\u{2713}\u{1f600} and DEL \u{7f}",
+        ];
+        for input in inputs {
+            let expected = serde_json::to_string(input).unwrap();
+            let mut out = SmartString::<Compact>::from("\"");
+            SmartString::<LazyCompact>::from(input).escape_json_into(&mut out);
+            out.push('"');
+            assert_eq!(expected, out);
+        }
+    }
+
+    #[test]
+    fn push_line_and_lines_owned() {
+        let mut log = SmartString::<Compact>::new();
+        log.push_line("one");
+        log.push_line("two\r\n");
+        log.push_line("three\n\n");
+        log.push_line("");
+        log.push_line("four\r");
+        log.push_line("a line long enough to need a heap allocation\r\n");
+        assert_eq!(
+            "one\ntwo\nthree\n\nfour\r\na line long enough to need a heap allocation\n",
+            log
+        );
+        let crlf = SmartString::<Compact>::from("one\r\ntwo\n\r\nthree");
+        let lines: Vec<SmartString<Compact>> = crlf.lines_owned().collect();
+        assert_eq!(vec!["one", "two", "", "three"], lines);
+        assert_eq!(
+            log.lines().collect::<Vec<_>>(),
+            log.lines_owned().collect::<Vec<SmartString<Compact>>>()
+        );
+        assert_eq!(Some("three".into()), crlf.lines_owned().next_back());
+    }
+
+    #[test]
+    fn small_string_set() {
+        use crate::SmallStringSet;
+        use std::collections::BTreeSet;
+
+        let words = [
+            "pear",
+            "apple",
+            "fig",
+            "apple",
+            "a rather long fruit name",
+            "fig",
+        ];
+        let mut set: SmallStringSet<Compact> = SmallStringSet::new();
+        let mut control = BTreeSet::new();
+        for word in words {
+            assert_eq!(control.insert(word), set.insert(word.into()));
+        }
+        assert_eq!(control.len(), set.len());
+        assert!(control.iter().copied().eq(set.iter()));
+        assert!(set.contains("fig"));
+        assert!(!set.contains("grape"));
+        assert_eq!(Some("fig".into()), set.remove("fig"));
+        assert_eq!(None, set.remove("fig"));
+        let collected: SmallStringSet<Compact> = words.iter().map(|&w| w.into()).collect();
+        assert_eq!(control.len(), collected.len());
+        assert!(control.iter().copied().eq(collected.iter()));
+    }
+
+    #[test]
+    fn checked_ops() {
+        use crate::OpError;
+
+        let base = SmartString::<Compact>::from("h\u{e9}llo");
+        let mut s = base.clone();
+        assert_eq!(
+            Err(OpError::NotCharBoundary { index: 2 }),
+            s.checked_insert(2, 'x')
+        );
+        assert_eq!(
+            Err(OpError::OutOfBounds { index: 7, len: 6 }),
+            s.checked_insert_str(7, "x")
+        );
+        assert_eq!(
+            Err(OpError::OutOfBounds { index: 6, len: 6 }),
+            s.checked_remove(6)
+        );
+        assert_eq!(
+            Err(OpError::NotCharBoundary { index: 2 }),
+            s.checked_truncate(2)
+        );
+        assert_eq!(
+            Err(OpError::NotCharBoundary { index: 2 }),
+            s.checked_split_off(2).map(|_| ())
+        );
+        assert_eq!(
+            Err(OpError::InvalidRange { start: 4, end: 3 }),
+            s.checked_replace_range((Bound::Included(4), Bound::Excluded(3)), "")
+        );
+        assert_eq!(
+            Err(OpError::OutOfBounds {
+                index: usize::MAX,
+                len: 6
+            }),
+            s.checked_drain((Bound::Excluded(usize::MAX), Bound::Unbounded))
+                .map(|_| ())
+        );
+        assert_eq!(base, s);
+        assert_eq!(Ok('\u{e9}'), s.checked_remove(1));
+        assert_eq!(Ok(()), s.checked_insert(1, 'e'));
+        assert_eq!(Ok(()), s.checked_truncate(100));
+        assert_eq!(Ok("lo".into()), s.checked_split_off(3));
+        assert_eq!(Ok(()), s.checked_replace_range(..=0, "j"));
+        assert_eq!("jel", s.checked_drain(..).unwrap().collect::<String>());
+        assert!(s.is_empty());
+    }
+
+    #[test]
+    fn slice_owned() {
+        use crate::OpError;
+
+        let long = "h\u{e9}llo, a string long enough to live on the heap";
+        let string = SmartString::<Compact>::from(long);
+        for (start, end) in [(0, 0), (0, 6), (3, 30), (7, long.len()), (0, long.len())] {
+            let slice = string.slice_owned(start..end);
+            assert_eq!(&long[start..end], slice);
+            assert_eq!(end - start <= MAX_INLINE, slice.is_inline());
+        }
+        assert_eq!(string, string.slice_owned(..));
+        assert_eq!(
+            Err(OpError::NotCharBoundary { index: 2 }),
+            string.try_slice_owned(2..)
+        );
+        assert_panic(|| string.slice_owned(..100));
+    }
+
+    #[test]
+    fn from_ascii() {
+        let token = SmartString::<Compact>::from_ascii(b"Content-Type").unwrap();
+        assert_eq!("Content-Type", token);
+        assert!(token.is_inline());
+        let long = b"x-a-header-name-long-enough-to-box".as_ref();
+        let mut boxed = SmartString::<Compact>::from_ascii(long).unwrap();
+        assert_eq!(long, boxed.as_bytes());
+        assert_eq!(
+            5,
+            SmartString::<Compact>::from_ascii(b"bad: \xff")
+                .unwrap_err()
+                .valid_up_to()
+        );
+        assert_eq!(Ok(()), boxed.push_ascii_bytes(b"!"));
+        assert!(boxed.push_ascii_bytes(b"\xe2\x9c\x93").is_err());
+        assert_eq!(long.len() + 1, boxed.len());
+    }
+
+    #[test]
+    fn with_capacity() {
+        for cap in [0, 1, MAX_INLINE, MAX_INLINE + 1, 1024] {
+            let mut string = SmartString::<Compact>::with_capacity(cap);
+            assert!(string.is_empty());
+            assert_eq!(cap <= MAX_INLINE, string.is_inline());
+            assert!(string.capacity() >= cap);
+            let capacity = string.capacity();
+            for _ in 0..cap {
+                string.push('x');
+            }
+            assert_eq!(capacity, string.capacity());
+        }
+    }
+
+    #[test]
+    fn reserve() {
+        let mut string = SmartString::<LazyCompact>::from("short");
+        string.reserve(MAX_INLINE - 5);
+        assert!(string.is_inline());
+        string.reserve_exact(MAX_INLINE - 4);
+        assert!(!string.is_inline());
+        assert_eq!("short", string);
+        string.reserve(1000);
+        assert!(string.capacity() >= 1005);
+        let capacity = string.capacity();
+        string.push_str(&"x".repeat(1000));
+        assert_eq!(capacity, string.capacity());
+        string.shrink_to_fit();
+        string.reserve_exact(10);
+        assert_eq!(string.len() + 10, string.capacity());
+        string.reserve_exact(5);
+        assert_eq!(string.len() + 10, string.capacity());
+        assert_panic(|| string.reserve(usize::MAX));
+    }
+
+    #[test]
+    fn make_boxed_with_capacity() {
+        let mut string = SmartString::<Compact>::from("short");
+        string.make_boxed_with_capacity(4);
+        assert!(!string.is_inline());
+        assert_eq!("short", string);
+        string.make_boxed_with_capacity(500);
+        let capacity = string.capacity();
+        assert!(capacity >= 500);
+        string.make_boxed_with_capacity(100);
+        assert_eq!(capacity, string.capacity());
+        string.push_str(&"x".repeat(495));
+        assert_eq!(capacity, string.capacity());
+        string.truncate(3);
+        assert!(string.is_inline());
+    }
+
+    #[test]
+    fn same_repr() {
+        let long = "a string long enough to live on the heap";
+        let boxed = SmartString::<Compact>::from(long);
+        let copy = boxed.clone();
+        assert!(boxed.same_repr(&boxed));
+        assert!(!boxed.same_repr(&copy));
+        assert_eq!(boxed, copy);
+        let mut inline = SmartString::<LazyCompact>::from("short");
+        assert!(inline.same_repr(&SmartString::from("short")));
+        assert!(!inline.same_repr(&SmartString::from("shorter")));
+        let mut lazy = SmartString::<LazyCompact>::from(long);
+        lazy.truncate(5);
+        assert!(!lazy.same_repr(&inline));
+        inline.push_str(" but now long enough to live on the heap");
+        assert!(inline.same_repr(&inline));
+    }
+
+    #[test]
+    fn extend_with_capacity_hint() {
+        let parts: Vec<String> = (0..100).map(|i| format!("{},", i)).collect();
+        let total = parts.iter().map(String::len).sum();
+        let mut string = SmartString::<LazyCompact>::from("numbers: ");
+        string.extend_with_capacity_hint(parts.iter(), total);
+        let capacity = string.capacity();
+        assert!(capacity >= total + 9);
+        assert_eq!(format!("numbers: {}", parts.concat()), string);
+        let mut short = SmartString::<Compact>::new();
+        short.extend_with_capacity_hint("abc".chars(), 3);
+        assert_eq!("abc", short);
+        assert!(short.is_inline());
+    }
+
+    #[test]
+    fn promotion_capacity() {
+        use super::counting_allocator::assert_allocs;
+
+        struct Exact;
+        impl SmartStringMode for Exact {
+            type InlineArray = [u8; MAX_INLINE];
+            const DEALLOC: bool = true;
+            fn promotion_capacity(required: usize) -> usize {
+                required
+            }
+        }
+
+        let mut exact = SmartString::<Exact>::from("x".repeat(MAX_INLINE).as_str());
+        exact.push('y');
+        assert_eq!(MAX_INLINE + 1, exact.capacity());
+        exact.push('z');
+        assert_eq!((MAX_INLINE + 1) * 2, exact.capacity());
+        assert_eq!(
+            MAX_INLINE + 5,
+            SmartString::<Exact>::with_capacity(MAX_INLINE + 5).capacity()
+        );
+        let mut empty = SmartString::<Exact>::new();
+        empty.make_boxed_with_capacity(0);
+        empty.push_str("still works");
+        assert_eq!("still works", empty);
+
+        let mut default = SmartString::<Compact>::from("x".repeat(MAX_INLINE).as_str());
+        default.push('y');
+        assert_eq!(MAX_INLINE * 2, default.capacity());
+
+        // Converting a long string goes through the promotion capacity too, in
+        // a single allocation.
+        let long = "x".repeat(MAX_INLINE + 1);
+        let exact = assert_allocs(1, || SmartString::<Exact>::from(long.as_str()));
+        assert_eq!(MAX_INLINE + 1, exact.capacity());
+        let default = assert_allocs(1, || SmartString::<LazyCompact>::from(long.as_str()));
+        assert_eq!(MAX_INLINE * 2, default.capacity());
+
+        // Exact reservations don't.
+        let mut reserved = SmartString::<Compact>::from("short");
+        reserved.reserve_exact(30);
+        assert_eq!(35, reserved.capacity());
+        let mut boxed = SmartString::<Compact>::from("short");
+        boxed.make_boxed_with_capacity(8);
+        assert_eq!(8, boxed.capacity());
+    }
+
+    #[test]
+    fn from_utf8() {
+        let long = "h\u{e9}llo, a string long enough to need the heap";
+        for input in ["", "h\u{e9}llo", long] {
+            let string = SmartString::<Compact>::from_utf8(input.as_bytes().to_vec()).unwrap();
+            assert_eq!(input, string);
+            assert_eq!(input.len() <= MAX_INLINE, string.is_inline());
+            assert_eq!(
+                input,
+                SmartString::<Compact>::from_utf8_lossy(input.as_bytes())
+            );
+        }
+        for input in [b"bad \xc3".to_vec(), [long.as_bytes(), b"\xff"].concat()] {
+            let error = SmartString::<Compact>::from_utf8(input.clone()).unwrap_err();
+            assert_eq!(input, error.into_bytes());
+            assert_eq!(
+                String::from_utf8_lossy(&input).as_ref(),
+                SmartString::<Compact>::from_utf8_lossy(&input)
+            );
+        }
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn from_utf8_unchecked() {
+        for input in ["", "h\u{e9}llo", LONG] {
+            let string = unsafe { SmartString::<Compact>::from_utf8_unchecked(input.as_bytes()) };
+            assert_eq!(input, string);
+            assert_eq!(input.len() <= MAX_INLINE, string.is_inline());
+            assert!(string.capacity() >= input.len());
+        }
+    }
+
+    #[test]
+    fn pop_n() {
+        let input = "a string long enough to need the heap, with \u{2603}s";
+        for count in 0..=input.chars().count() + 1 {
+            let mut string = SmartString::<Compact>::from(input);
+            let mut control = String::from(input);
+            let mut tail: Vec<char> = (0..count).filter_map(|_| control.pop()).collect();
+            tail.reverse();
+            assert_eq!(tail.into_iter().collect::<String>(), string.pop_n(count));
+            assert_eq!(control, string);
+            assert_eq!(string.len() <= MAX_INLINE, string.is_inline());
+        }
+    }
+
+    #[test]
+    fn from_utf16() {
+        let long = "a string long enough to need the heap, with \u{1f600}s";
+        for input in ["", "h\u{e9}llo \u{1f600}", long] {
+            let utf16: Vec<u16> = input.encode_utf16().collect();
+            let string = SmartString::<Compact>::from_utf16(&utf16).unwrap();
+            assert_eq!(input, string);
+            assert_eq!(input.len() <= MAX_INLINE, string.is_inline());
+            assert_eq!(input, SmartString::<Compact>::from_utf16_lossy(&utf16));
+        }
+        let mut bad: Vec<u16> = long.encode_utf16().collect();
+        bad.push(0xdc00);
+        for input in [&[0xd800, 0x61][..], &bad] {
+            assert!(SmartString::<Compact>::from_utf16(input).is_err());
+            assert_eq!(
+                String::from_utf16_lossy(input),
+                SmartString::<Compact>::from_utf16_lossy(input)
+            );
+        }
+    }
+
+    #[test]
+    fn build_exact() {
+        let long = "a fixed width record long enough to need the heap";
+        for input in ["", "h\u{e9}llo", long] {
+            let string = SmartString::<Compact>::build_exact(input.len(), |buffer| {
+                assert!(buffer.iter().all(|&byte| byte == 0));
+                buffer.copy_from_slice(input.as_bytes())
+            })
+            .unwrap();
+            assert_eq!(input, string);
+            assert_eq!(input.len() <= MAX_INLINE, string.is_inline());
+        }
+        let padded =
+            SmartString::<Compact>::build_exact(4, |buffer| buffer[..2].copy_from_slice(b"ab"));
+        assert_eq!("ab\0\0", padded.unwrap());
+        let error = SmartString::<Compact>::build_exact(long.len(), |buffer| {
+            buffer.copy_from_slice(long.as_bytes());
+            buffer[10] = 0xff;
+        })
+        .unwrap_err();
+        assert_eq!(10, error.valid_up_to());
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn bytes_mut() {
+        for input in ["hello", LONG] {
+            let mut string = SmartString::<Compact>::from(input);
+            unsafe { string.as_bytes_mut() }.make_ascii_uppercase();
+            assert_eq!(input.to_ascii_uppercase(), string);
+            assert_eq!(input.to_ascii_uppercase().into_bytes(), string.into_bytes());
+        }
+    }
+
+    #[test]
+    fn segmented() {
+        for input in [
+            "",
+            "hello",
+            "h\u{e9}llo w\u{f6}rld, \u{1f600}",
+            "h\u{e9}llo w\u{f6}rld, a string long enough to need the heap",
+        ] {
+            let mut segmented = SegmentedSmartString::new(SmartString::<Compact>::from(input));
+            let chars: Vec<char> = input.chars().collect();
+            assert_eq!(chars.len(), segmented.segment_count());
+            for start in 0..=chars.len() + 1 {
+                let expected = chars.get(start).map(|ch| ch.to_string());
+                assert_eq!(expected.as_deref(), segmented.segment(start));
+                for end in 0..=chars.len() + 1 {
+                    let expected = chars
+                        .get(start..end)
+                        .map(|slice| slice.iter().collect::<String>());
+                    assert_eq!(expected.as_deref(), segmented.slice(start..end));
+                }
+            }
+            assert_eq!(None, segmented.segment(usize::MAX));
+            segmented.modify(|string| string.push('\u{2603}'));
+            assert_eq!(chars.len() + 1, segmented.segment_count());
+            assert_eq!(Some("\u{2603}"), segmented.segment(chars.len()));
+        }
+    }
+
+    #[test]
+    fn option_niche() {
+        let mut strings: Vec<Option<SmartString<LazyCompact>>> = ["", "short", LONG]
+            .iter()
+            .map(|&s| Some(SmartString::from(s)))
+            .collect();
+        strings.push(Some(SmartString::from(String::with_capacity(64))));
+        strings.push(None);
+        assert_eq!(
+            vec![Some(""), Some("short"), Some(LONG), Some(""), None],
+            strings.iter().map(|s| s.as_deref()).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn static_smart_string() {
+        const EMPTY: StaticSmartString = StaticSmartString::empty();
+        static HELLO: StaticSmartString = StaticSmartString::new("h\u{e9}llo");
+        assert_eq!("", EMPTY);
+        assert_eq!("h\u{e9}llo", HELLO);
+        assert_eq!(Some(HELLO), StaticSmartString::try_new("h\u{e9}llo"));
+        assert_eq!(
+            None,
+            StaticSmartString::try_new(&"x".repeat(MAX_INLINE + 1))
+        );
+        let full = "x".repeat(MAX_INLINE);
+        assert_eq!(full, StaticSmartString::new(&full).as_str());
+        let string: SmartString<Compact> = HELLO.into();
+        assert!(string.is_inline());
+        assert_eq!(HELLO.as_str(), string);
+        assert_panic(|| StaticSmartString::new(&"x".repeat(MAX_INLINE + 1)));
+    }
+
+    #[test]
+    fn smart_str() {
+        for input in ["", "short", LONG] {
+            let frozen = SmartStr::new(input);
+            assert_eq!(input, frozen);
+            assert_eq!(input.len() <= MAX_INLINE, frozen.is_inline());
+            assert_eq!(frozen, frozen.clone());
+            assert_eq!(frozen, SmartStr::from(String::from(input)));
+
+            let mut string = SmartString::<LazyCompact>::from(input);
+            string.reserve(100);
+            let frozen = SmartStr::from(string);
+            assert_eq!(input, frozen);
+            assert_eq!(input.len() <= MAX_INLINE, frozen.is_inline());
+            let thawed = SmartString::<Compact>::from(frozen);
+            assert_eq!(input, thawed);
+            if !thawed.is_inline() {
+                assert_eq!(thawed.len(), thawed.capacity());
+            }
+        }
+        let set: std::collections::HashSet<SmartStr> = [SmartStr::new(LONG)].into_iter().collect();
+        assert!(set.contains(LONG));
+
+        // Freezing a pinned string lets it move inline.
+        let mut pinned = SmartString::<Compact>::from("short");
+        pinned.pin_boxed();
+        let frozen = SmartStr::from(pinned);
+        assert!(frozen.is_inline());
+        let mut pinned = SmartString::<LazyCompact>::from(LONG);
+        pinned.pin_boxed();
+        let thawed = SmartString::<Compact>::from(SmartStr::from(pinned));
+        assert!(!thawed.is_pinned());
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn smart_arc_string() {
+        for input in ["", "short", LONG] {
+            let string = SmartArcString::new(input);
+            assert_eq!(input, string);
+            assert_eq!(input.len() <= MAX_INLINE, string.is_inline());
+            let clone = string.clone();
+            assert_eq!(string, clone);
+            assert_eq!(!string.is_inline(), SmartArcString::ptr_eq(&string, &clone));
+            drop(string);
+            assert_eq!(input, clone);
+
+            let mut modified = clone.clone();
+            modified.modify(|string| string.push_str(LONG));
+            assert_eq!(format!("{}{}", input, LONG).as_str(), modified);
+            assert_eq!(input, clone);
+            modified.modify(|string| string.truncate(input.len()));
+            assert_eq!(clone, modified);
+            assert_eq!(input.len() <= MAX_INLINE, modified.is_inline());
+            assert_eq!(input, SmartString::<Compact>::from(modified));
+        }
+        let arc: std::sync::Arc<str> = LONG.into();
+        let string = SmartArcString::from(arc.clone());
+        assert_eq!(LONG, string);
+        assert_eq!(1, std::sync::Arc::strong_count(&arc));
+        assert!(SmartArcString::from(std::sync::Arc::<str>::from("short")).is_inline());
+    }
+
+    #[test]
+    fn split_terminator_owned() {
+        let long = "a segment long enough to need the heap";
+        let inputs = [
+            String::new(),
+            "/".into(),
+            "//".into(),
+            "a".into(),
+            "/users/bodil/".into(),
+            format!("/{}/x", long),
+            "h\u{e9}llo\u{2603}w\u{f6}rld\u{2603}".into(),
+        ];
+        for input in inputs.iter() {
+            let string = SmartString::<Compact>::from(input.as_str());
+            for separator in ['/', '\u{2603}'] {
+                let expected: Vec<&str> = input.split_terminator(separator).collect();
+                let segments: Vec<SmartString<Compact>> =
+                    string.split_terminator_owned(separator).collect();
+                assert_eq!(expected, segments);
+                assert!(segments
+                    .iter()
+                    .all(|s| s.is_inline() == (s.len() <= MAX_INLINE)));
+                let mut reversed: Vec<SmartString<Compact>> =
+                    string.split_terminator_owned(separator).rev().collect();
+                reversed.reverse();
+                assert_eq!(segments, reversed);
+                assert_eq!(expected.len(), string.segments_count(separator));
+            }
+        }
+    }
+
+    #[test]
+    fn from_float_with() {
+        let values = [
+            0.0,
+            -0.0,
+            1.0,
+            -2.5,
+            1.23456,
+            1e-7,
+            1.5e300,
+            f64::NAN,
+            f64::INFINITY,
+        ];
+        for &value in values.iter() {
+            for precision in [0, 1, 3, 20] {
+                let expected = format!("{:.*}", precision, value);
+                let string = SmartString::<Compact>::from_float_with(value, precision);
+                assert_eq!(expected, string);
+                assert_eq!(expected.len() <= MAX_INLINE, string.is_inline());
+                let mut pushed = SmartString::<Compact>::from("x=");
+                pushed.push_float_with(value, precision);
+                assert_eq!(format!("x={}", expected), pushed);
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn smart_arc_string_cow() {
+        use super::counting_allocator::{assert_allocs, assert_no_alloc};
+
+        let original = SmartArcString::new(LONG);
+        let mut string = original.clone();
+        string.push('!');
+        assert!(!SmartArcString::ptr_eq(&string, &original));
+        string.make_mut().reserve(LONG.len());
+        assert_no_alloc(|| string.push_str(LONG));
+        assert_eq!(format!("{}!{}", LONG, LONG).as_str(), string);
+        assert_eq!(LONG, original);
+        string.truncate(LONG.len() + 5);
+        assert_eq!(format!("{}!a st", LONG).as_str(), string);
+        string.truncate(5);
+        assert!(string.is_inline());
+        assert_eq!("a str", string);
+        let mut shared = original.clone();
+        shared.clear();
+        assert_eq!("", shared);
+        assert!(shared.is_inline());
+        assert_eq!(LONG, original);
+        let mut short = SmartArcString::new("short");
+        assert_no_alloc(|| short.push_str(" and "));
+        assert_no_alloc(|| short.push('\u{2603}'));
+        assert_eq!("short and \u{2603}", short);
+        assert_panic(|| SmartArcString::new("h\u{e9}llo").truncate(2));
+
+        // A string nothing else shares is modified in place.
+        let mut string = SmartArcString::new(LONG);
+        let pushes = 1000;
+        let (allocs, ()) = super::counting_allocator::count_allocs(|| {
+            for _ in 0..pushes {
+                string.push('x');
+            }
+        });
+        assert!(allocs < 16, "{} allocations for {} pushes", allocs, pushes);
+        assert_eq!(LONG.len() + pushes, string.len());
+        let copy = string.clone();
+        assert_allocs(2, || string.push('y'));
+        assert_eq!(LONG.len() + pushes, copy.len());
+        assert_no_alloc(|| string.truncate(LONG.len()));
+        assert_eq!(LONG, string);
+        assert!(copy.starts_with(LONG));
+        let moved = SmartString::<Compact>::from(string);
+        assert_eq!(LONG, moved);
+        let shared = assert_allocs(1, || SmartArcString::from(moved));
+        assert_eq!(LONG, shared);
+        assert_allocs(1, || SmartString::<LazyCompact>::from(shared.clone()));
+        assert_no_alloc(|| SmartString::<LazyCompact>::from(shared));
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn smart_arc_string_make_mut() {
+        let mut string = SmartArcString::new(LONG);
+        let original = string.clone();
+        string.insert(0, '>');
+        string.insert_str(1, "> ");
+        assert_eq!(format!(">> {}", LONG).as_str(), string);
+        assert_eq!('>', string.remove(0));
+        assert_eq!(Some('p'), string.pop());
+        string.retain(|ch| ch != ' ');
+        assert_eq!(">astringlongenoughtoneedthehea", string);
+        string.replace_range(1..8, "");
+        assert_eq!(">longenoughtoneedthehea", string);
+        assert!(string.is_inline());
+        {
+            let mut guard = string.make_mut();
+            let drained: String = guard.drain(..7).collect();
+            assert_eq!(">longen", drained);
+            guard.push_str(LONG);
+        }
+        assert!(!string.is_inline());
+        assert_eq!(format!("oughtoneedthehea{}", LONG).as_str(), string);
+        assert_eq!(LONG, original);
+
+        let slice = string.slice(16..);
+        string.truncate(0);
+        assert_eq!(LONG, slice);
+        assert!(string.is_inline());
+
+        assert_panic(|| SmartArcString::new(LONG).insert(LONG.len() + 1, 'x'));
+        assert_panic(|| SmartArcString::new("h\u{e9}llo").remove(2));
+    }
+
+    #[test]
+    fn unwind_safe() {
+        let short = SmartString::<Compact>::from("short");
+        let long = SmartString::<LazyCompact>::from(LONG);
+        let result = std::panic::catch_unwind(|| short.len() + long.len());
+        assert_eq!(short.len() + long.len(), result.unwrap());
+        let mut string = SmartString::<Compact>::from("short");
+        let result = std::panic::catch_unwind(move || {
+            string.push_str(" and then some more, to move it to the heap");
+            string
+        });
+        assert!(!result.unwrap().is_inline());
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn arbitrary_inline_boundary() {
+        use arbitrary::{Arbitrary, Unstructured};
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(31337);
+        let mut lengths = std::collections::BTreeSet::new();
+        let mut boxed_short = false;
+        let mut straddling = false;
+        for _ in 0..1000 {
+            let data: Vec<u8> = (0..128).map(|_| rng.gen()).collect();
+            let string = SmartString::<Compact>::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            lengths.insert(string.len());
+            boxed_short |= !string.is_inline() && string.len() <= MAX_INLINE;
+            straddling |= !string.is_char_boundary(MAX_INLINE.min(string.len()));
+        }
+        for len in MAX_INLINE - 1..=MAX_INLINE + 1 {
+            assert!(
+                lengths.contains(&len),
+                "no strings of length {} generated",
+                len
+            );
+        }
+        assert!(boxed_short);
+        assert!(straddling);
+    }
+
+    #[test]
+    fn common_prefix_len() {
+        fn control(left: &str, right: &str) -> usize {
+            left.char_indices()
+                .zip(right.chars())
+                .find(|((_, left), right)| left != right)
+                .map_or(left.len().min(right.len()), |((index, _), _)| index)
+        }
+        let inputs = [
+            "",
+            "a",
+            "autocomplete",
+            "automatic",
+            LONG,
+            "a string long enough to need the heap!",
+            "a string long enough to need \u{e9}",
+            "a string long enough to need \u{e8}",
+            "h\u{e9}llo w\u{f6}rld, this is a string that's longer",
+        ];
+        for &left in inputs.iter() {
+            let string = SmartString::<Compact>::from(left);
+            for &right in inputs.iter() {
+                let expected = control(left, right);
+                assert_eq!(
+                    expected,
+                    string.common_prefix_len(right),
+                    "{:?} {:?}",
+                    left,
+                    right
+                );
+                for n in 0..=left.len().max(right.len()) + 1 {
+                    let bounded = |s: &str| s.as_bytes()[..n.min(s.len())].to_vec();
+                    assert_eq!(
+                        bounded(left) == bounded(right),
+                        string.eq_str_prefix(n, right)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn replace_smart() {
+        for input in ["", "aaa", "banana", LONG, "h\u{e9}llo w\u{f6}rld"] {
+            let string = SmartString::<Compact>::from(input);
+            for &(from, to) in [
+                ("a", "x"),
+                ("an", ""),
+                ("", "-"),
+                ("\u{e9}", "e"),
+                ("a", LONG),
+            ]
+            .iter()
+            {
+                let replaced = string.replace_smart(from, to);
+                assert_eq!(input.replace(from, to), replaced);
+                assert_eq!(replaced.len() <= MAX_INLINE, replaced.is_inline());
+                let std_replaced: String = string.replace(from, to);
+                assert_eq!(std_replaced, replaced);
+                for count in 0..4 {
+                    assert_eq!(
+                        input.replacen(from, to, count),
+                        string.replacen_smart(from, to, count)
+                    );
+                }
+            }
+            assert_eq!(input.replace('a', "o"), string.replace_smart('a', "o"));
+            assert_eq!(
+                input.replace(&['a', 'n'][..], ""),
+                string.replace_smart(&['a', 'n'][..], "")
+            );
+            assert_eq!(
+                input.replace(['a', 'n'], ""),
+                string.replace_smart(['a', 'n'], "")
+            );
+            assert_eq!(
+                input.replace(char::is_whitespace, "_"),
+                string.replace_smart(char::is_whitespace, "_")
+            );
+            let from = SmartString::<LazyCompact>::from("na");
+            assert_eq!(input.replace("na", "NA"), string.replace_smart(&from, "NA"));
+            assert_eq!(
+                input.replace("na", "NA"),
+                string.replace_smart(&String::from("na"), "NA")
+            );
+        }
+    }
+
+    #[test]
+    fn case_conversion() {
+        for input in [
+            "",
+            "Hello World",
+            "Grüße, Jürgen",
+            "ΟΔΥΣΣΕΥΣ ΣΑ",
+            "İstanbul",
+            LONG,
+            "ﬁ ǅ",
+        ] {
+            let string = SmartString::<Compact>::from(input);
+            let upper = string.to_uppercase_smart();
+            let lower = string.to_lowercase_smart();
+            assert_eq!(input.to_uppercase(), upper);
+            assert_eq!(input.to_lowercase(), lower);
+            // The `str` methods still resolve through `Deref`.
+            let std_upper: String = string.to_uppercase();
+            assert_eq!(upper, std_upper);
+            assert_eq!(upper.len() <= MAX_INLINE, upper.is_inline());
+            assert_eq!(lower.len() <= MAX_INLINE, lower.is_inline());
+
+            let mut ascii = string.clone();
+            ascii.make_ascii_uppercase();
+            assert_eq!(input.to_ascii_uppercase(), ascii);
+            assert_eq!(string.is_inline(), ascii.is_inline());
+            ascii.make_ascii_lowercase();
+            assert_eq!(input.to_ascii_lowercase(), ascii);
+        }
+    }
+
+    #[test]
+    fn fingerprint() {
+        for &(input, expected) in [
+            ("", 0xcbf2_9ce4_8422_2325),
+            ("a", 0xaf63_dc4c_8601_ec8c),
+            ("foobar", 0x8594_4171_f739_67e8),
+        ]
+        .iter()
+        {
+            assert_eq!(expected, SmartString::<Compact>::from(input).fingerprint());
+            assert_eq!(
+                expected,
+                SmartString::<LazyCompact>::from(input).fingerprint()
+            );
+        }
+        let boxed = SmartString::<Compact>::from(LONG);
+        let mut grown = SmartString::<LazyCompact>::from("a string");
+        grown.push_str(&LONG[8..]);
+        assert!(!boxed.is_inline());
+        assert_eq!(boxed.fingerprint(), grown.fingerprint());
+        assert_ne!(
+            boxed.fingerprint(),
+            SmartString::<Compact>::from("a string").fingerprint()
+        );
+    }
+
+    #[test]
+    fn bulk_edit_defers_demotion() {
+        let long = "x".repeat(MAX_INLINE + 1);
+        let mut string: SmartString<Compact> = long.as_str().into();
+        {
+            let mut edit = string.begin_bulk_edit();
+            for _ in 0..4 {
+                edit.truncate(MAX_INLINE - 1);
+                assert!(!edit.is_inline());
+                edit.push_str("xx");
+            }
+            edit.pop();
+            edit.pop();
+            assert!(!edit.is_inline());
+            assert_eq!(&long[..MAX_INLINE - 1], edit.as_str());
+        }
+        assert!(string.is_inline());
+        assert_eq!(&long[..MAX_INLINE - 1], string);
+
+        let mut lazy: SmartString<LazyCompact> = long.as_str().into();
+        lazy.begin_bulk_edit().truncate(1);
+        assert!(!lazy.is_inline());
+
+        let mut grown = SmartString::<Compact>::new();
+        grown.begin_bulk_edit().push_str(&long);
+        assert_eq!(long, grown);
+        assert!(!grown.is_inline());
+        let chars: SmartString<Compact> = long.chars().collect();
+        assert_eq!(long, chars);
+    }
+
+    #[test]
+    fn trim_in_place() {
+        let padded = format!(" \t{}\u{3000}\n", "x".repeat(MAX_INLINE - 2));
+        for input in [
+            "",
+            "   ",
+            "abc",
+            "  abc",
+            "abc  ",
+            "\u{a0}\u{e9}t\u{e9} ",
+            padded.as_str(),
+        ] {
+            let string = SmartString::<Compact>::from(input);
+            let mut trimmed = string.clone();
+            trimmed.trim_in_place();
+            assert_eq!(input.trim(), trimmed);
+            let mut start = string.clone();
+            start.trim_start_in_place();
+            assert_eq!(input.trim_start(), start);
+            let mut end = string.clone();
+            end.trim_end_in_place();
+            assert_eq!(input.trim_end(), end);
+            for result in [trimmed, start, end] {
+                assert_eq!(result.len() <= MAX_INLINE, result.is_inline());
+            }
+        }
+
+        let mut lazy = SmartString::<LazyCompact>::from(padded.as_str());
+        lazy.trim_in_place();
+        assert_eq!(padded.trim(), lazy);
+        assert!(!lazy.is_inline());
+    }
+
+    #[test]
+    fn try_from_iter() {
+        for input in ["", "short", LONG] {
+            let chars = input.chars().map(Ok::<char, ()>);
+            let string = SmartString::<Compact>::try_from_iter(chars).unwrap();
+            assert_eq!(input, string);
+            assert_eq!(input.len() <= MAX_INLINE, string.is_inline());
+        }
+
+        let mut taken = 0;
+        let result = SmartString::<LazyCompact>::try_from_iter(LONG.chars().map(|ch| {
+            taken += 1;
+            if ch == 'g' {
+                Err(taken)
+            } else {
+                Ok(ch)
+            }
+        }));
+        assert_eq!(Err(8), result);
+        assert_eq!(8, taken);
+
+        let mut string = SmartString::<Compact>::from(LONG);
+        assert_eq!(Err(()), string.try_extend(vec![Ok('!'), Err(()), Ok('?')]));
+        assert_eq!(format!("{}!", LONG), string);
+    }
+
+    #[test]
+    #[allow(unsafe_code)]
+    fn spare_capacity() {
+        let mut string = SmartString::<Compact>::from("abc");
+        assert_eq!(MAX_INLINE - 3, unsafe { string.spare_capacity_mut() }.len());
+        unsafe { string.spare_capacity_mut()[0].write(b'd') };
+        unsafe { string.set_len(4) };
+        assert_eq!("abcd", string);
+        assert!(string.is_inline());
+
+        let tail = "x".repeat(MAX_INLINE);
+        string.reserve(tail.len());
+        let spare = unsafe { string.spare_capacity_mut() };
+        assert!(spare.len() >= tail.len());
+        for (slot, byte) in spare.iter_mut().zip(tail.bytes()) {
+            slot.write(byte);
+        }
+        unsafe { string.set_len(4 + tail.len()) };
+        assert_eq!(format!("abcd{}", tail), string);
+        assert!(!string.is_inline());
+        assert_eq!(
+            string.capacity() - string.len(),
+            unsafe { string.spare_capacity_mut() }.len()
+        );
+    }
+
+    #[test]
+    fn utf8_validation_matches_std() {
+        let long = "a string long enough to need the heap, and then some";
+        let mut inputs: Vec<Vec<u8>> = vec![b"".to_vec(), long.as_bytes().to_vec()];
+        for bad in [
+            &[0xff][..],
+            &[0xe2, 0x82],
+            &[0xed, 0xa0, 0x80],
+            &[0xc0, 0xaf],
+        ] {
+            for prefix in ["", "abc", long] {
+                let mut input = prefix.as_bytes().to_vec();
+                input.extend_from_slice(bad);
+                input.extend_from_slice(b"tail");
+                inputs.push(input);
+            }
+        }
+        for input in inputs {
+            let expected = String::from_utf8(input.clone());
+            let actual = SmartString::<Compact>::from_utf8(input.clone());
+            match (expected, actual) {
+                (Ok(expected), Ok(actual)) => assert_eq!(expected, actual),
+                (Err(expected), Err(actual)) => {
+                    assert_eq!(expected.utf8_error(), actual.utf8_error());
+                    assert_eq!(expected.into_bytes(), actual.into_bytes());
+                }
+                (expected, actual) => panic!("expected {:?}, got {:?}", expected, actual),
+            }
+            let built = SmartString::<Compact>::build_exact(input.len(), |buffer| {
+                buffer.copy_from_slice(&input)
+            });
+            assert_eq!(core::str::from_utf8(&input).map(|_| ()), built.map(|_| ()));
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn io_writer() {
+        use std::io::Write;
+
+        let text = "héllo wörld, ∞ 🦀 and a string long enough to need the heap";
+        for chunk_size in 1..6 {
+            let mut string = SmartString::<Compact>::new();
+            let mut writer = string.writer();
+            for chunk in text.as_bytes().chunks(chunk_size) {
+                writer.write_all(chunk).unwrap();
+            }
+            writer.finish().unwrap();
+            assert_eq!(text, string);
+        }
+
+        let mut string = SmartString::<Compact>::new();
+        let mut writer = string.writer();
+        writer.write_all(b"abc").unwrap();
+        assert_eq!(1, writer.write(&[b'd', 0xff]).unwrap());
+        assert!(writer.write(&[0xff]).is_err());
+        assert!(writer.write_all(&[b'e', 0xff]).is_err());
+        assert_eq!("abcde", string);
+
+        // A bad continuation drops the partial character, so writing can go on.
+        let mut string = SmartString::<Compact>::new();
+        let mut writer = string.writer();
+        writer.write_all(&[0xe2, 0x88]).unwrap();
+        assert!(writer.write_all(b"x").is_err());
+        writer.write_all(b"ok").unwrap();
+        writer.finish().unwrap();
+        assert_eq!("ok", string);
+
+        let mut string = SmartString::<Compact>::new();
+        let mut writer = string.writer();
+        writer.write_all(&[b'a', 0xf0, 0x9f]).unwrap();
+        assert_eq!(
+            std::io::ErrorKind::InvalidData,
+            writer.finish().unwrap_err().kind()
+        );
+        assert_eq!("a", string);
+    }
+
+    #[test]
+    #[cfg(target_has_atomic = "ptr")]
+    fn smart_slice() {
+        let text = "fn main() { println!(\"a string long enough to need the heap\"); }";
+        let input = SmartArcString::new(text);
+        for &(start, end) in [
+            (0, 0),
+            (0, 2),
+            (3, 7),
+            (12, text.len() - 2),
+            (0, text.len()),
+        ]
+        .iter()
+        {
+            let slice = input.slice(start..end);
+            assert_eq!(&text[start..end], slice);
+            assert_eq!(end - start <= MAX_INLINE, slice.is_inline());
+            let sub = slice.slice(1.min(slice.len())..);
+            assert_eq!(&text[(start + 1).min(end)..end], sub);
+            assert_eq!(
+                SmartString::<Compact>::from(&text[start..end]),
+                SmartString::<Compact>::from(slice)
+            );
+        }
+        let short = SmartArcString::new("short");
+        assert_eq!("ort", short.slice(2..));
+        assert_panic(|| input.slice(..text.len() + 1));
+        assert_panic(|| SmartArcString::new("é").slice(1..));
+    }
+
+    #[test]
+    fn explicit_representation() {
+        let mut lazy = SmartString::<LazyCompact>::from(LONG);
+        lazy.truncate(5);
+        let lazy = lazy.try_into_inline().unwrap();
+        assert!(lazy.is_inline());
+        assert_eq!("a str", lazy);
+
+        let boxed = SmartString::<Compact>::from(LONG)
+            .try_into_inline()
+            .unwrap_err();
+        assert!(!boxed.is_inline());
+        assert_eq!(LONG, boxed);
+
+        let mut string = SmartString::<Compact>::from("short");
+        string.force_box();
+        assert!(!string.is_inline());
+        assert_eq!("short", string);
+        assert_eq!("short".len(), string.capacity());
+        let ptr = string.as_ptr();
+        string.force_box();
+        assert_eq!(ptr, string.as_ptr());
+        assert!(string.try_into_inline().unwrap().is_inline());
+    }
+
+    #[test]
+    fn write_smart() {
+        let mut string = SmartString::<Compact>::new();
+        crate::write_smart!(string, "{}-{}", 1, "two").unwrap();
+        assert_eq!("1-two", string);
+        assert!(string.is_inline());
+
+        crate::write_smart!(&mut string, " {:>60}", LONG).unwrap();
+        assert_eq!(format!("1-two {:>60}", LONG), string);
+        assert_eq!(string.len(), string.capacity());
+
+        struct Failing;
+        impl core::fmt::Display for Failing {
+            fn fmt(&self, _: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                Err(core::fmt::Error)
+            }
+        }
+        assert!(crate::write_smart!(string, "{}", Failing).is_err());
+    }
+
+    #[test]
+    fn pinned_strings_stay_boxed() {
+        let mut string = SmartString::<Compact>::from("short");
+        assert!(!string.is_pinned());
+        string.pin_boxed();
+        assert!(string.is_pinned());
+        assert!(!string.is_inline());
+        let capacity = string.capacity();
+        let ptr = string.as_ptr();
+
+        string.push_str(&LONG[..10]);
+        string.truncate(2);
+        string.pop();
+        string.shrink_to_fit();
+        assert_eq!("s", string);
+        assert!(!string.is_inline());
+        assert_eq!(ptr, string.as_ptr());
+        assert_eq!(capacity, string.capacity());
+
+        let clone = string.clone();
+        assert!(clone.is_inline());
+        assert!(!clone.is_pinned());
+
+        let mut cleared = SmartString::<Compact>::from(LONG);
+        cleared.pin_boxed();
+        let cleared_ptr = cleared.as_ptr();
+        cleared.clear();
+        assert_eq!("", cleared);
+        assert!(cleared.is_pinned());
+        assert_eq!(cleared_ptr, cleared.as_ptr());
+
+        let mut string = string.try_into_inline().unwrap_err();
+        string.unpin();
+        assert!(!string.is_pinned());
+        assert!(!string.is_inline());
+        string.pop();
+        assert!(string.is_inline());
+    }
+
+    #[test]
+    fn cmp_bytes() {
+        let inputs = [
+            "",
+            "a",
+            "B",
+            "ab",
+            "a\u{0}",
+            "\u{ff}",
+            "\u{ffff}",
+            "\u{10000}",
+            LONG,
+            "a string long enough to need the heap!",
+        ];
+        for left in inputs.iter() {
+            let smart = SmartString::<Compact>::from(*left);
+            for right in inputs.iter() {
+                let expected = left.as_bytes().cmp(right.as_bytes());
+                assert_eq!(expected, smart.cmp_bytes(right));
+                assert_eq!(expected, smart.cmp(&SmartString::from(*right)));
+            }
+        }
+    }
+
+    #[test]
+    fn get_ranges() {
+        let text = "héllo, a string long enough to need the heap";
+        for input in [&text[..6], text] {
+            let mut string = SmartString::<Compact>::from(input);
+            for end in 0..input.len() + 2 {
+                assert_eq!(input.get(..end), string.get(..end));
+                assert_eq!(input.get(1..end), string.get(1..end));
+                assert_eq!(input.get(end..), string.get(end..));
+                assert_eq!(input.get(..end).is_some(), string.get_mut(..end).is_some());
+            }
+            #[allow(unsafe_code)]
+            let unchecked = unsafe { string.get_unchecked(3..6) };
+            assert_eq!(&input[3..6], unchecked);
+        }
+    }
+
+    crate::newtype! {
+        /// An email address.
+        pub(crate) struct Email(SmartString<LazyCompact>);
+    }
+
+    #[test]
+    fn newtype_macro() {
+        use std::collections::HashSet;
+
+        let mut string: SmartString<LazyCompact> = "bodil@example.com".into();
+        assert_eq!("bodil@example.com", Email::from_ref(&string));
+        assert_eq!(string.as_ptr(), Email::from_ref(&string).as_ptr());
+        Email::from_mut(&mut string).0.push('!');
+        assert_eq!("bodil@example.com!", string);
+
+        let email = Email::from("a@example.com");
+        assert_eq!(Email::from(String::from("a@example.com")), email);
+        let later = Email::from("b@example.com");
+        assert!(email < later);
+        assert_eq!("\"a@example.com\"", format!("{:?}", email));
+        assert_eq!("a@example.com", email.to_string());
+        let mut set = HashSet::new();
+        set.insert(email.clone());
+        assert!(set.contains("a@example.com"));
+        assert_eq!(
+            SmartString::<LazyCompact>::from("a@example.com"),
+            email.into_inner()
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn newtype_macro_serde() {
+        let email = Email::from("a@example.com");
+        let json = serde_json::to_string(&email).unwrap();
+        assert_eq!("\"a@example.com\"", json);
+        assert_eq!(email, serde_json::from_str::<Email>(&json).unwrap());
+    }
+
+    #[test]
+    #[allow(clippy::cmp_owned)]
+    fn cross_mode_comparisons() {
+        for (left, right) in [("abc", "abc"), ("abc", "abd"), (LONG, LONG), (LONG, "abc")] {
+            let compact = SmartString::<Compact>::from(left);
+            let lazy = SmartString::<LazyCompact>::from(right);
+            assert_eq!(left == right, compact.eq_any_mode(&lazy));
+            assert_eq!(left == right, lazy.eq_any_mode(&compact));
+            assert_eq!(left.cmp(right), compact.cmp_any_mode(&lazy));
+            assert_eq!(right.cmp(left), lazy.cmp_any_mode(&compact));
+        }
+        // Comparing with a string of an inferred mode still compiles.
+        let compact = SmartString::<Compact>::from("x");
+        assert!(compact == SmartString::from("x"));
+        assert_eq!(compact, SmartString::from("x"));
+        assert!(compact < SmartString::from("y"));
+    }
+
+    #[test]
+    fn cross_mode_conversions() {
+        let compact = SmartString::<Compact>::from(LONG);
+        let ptr = compact.as_ptr();
+        let lazy = SmartString::<LazyCompact>::from(compact);
+        assert_eq!(LONG, lazy);
+        assert_eq!(ptr, lazy.as_ptr());
+
+        let mut lazy = lazy;
+        lazy.truncate(8);
+        assert!(!lazy.is_inline());
+        let compact = SmartString::<Compact>::from(lazy);
+        assert_eq!("a string", compact);
+        assert!(compact.is_inline());
+
+        let lazy = SmartString::<LazyCompact>::from(SmartString::<Compact>::from("short"));
+        assert!(lazy.is_inline());
+        assert_eq!("short", lazy);
+    }
+
+    #[test]
+    fn convert_between_modes() {
+        struct Exact;
+        impl SmartStringMode for Exact {
+            type InlineArray = [u8; MAX_INLINE];
+            const DEALLOC: bool = true;
+            fn promotion_capacity(required: usize) -> usize {
+                required
+            }
+        }
+
+        let mut lazy = SmartString::<LazyCompact>::from(LONG);
+        lazy.reserve(100);
+        let capacity = lazy.capacity();
+        let ptr = lazy.as_ptr();
+        let exact: SmartString<Exact> = lazy.convert();
+        assert_eq!(LONG, exact);
+        assert_eq!(ptr, exact.as_ptr());
+        assert_eq!(capacity, exact.capacity());
+
+        let mut lazy: SmartString<LazyCompact> = exact.convert();
+        lazy.truncate(3);
+        assert!(!lazy.is_inline());
+        let same: SmartString<LazyCompact> = lazy.convert();
+        assert!(!same.is_inline());
+        let compact: SmartString<Compact> = same.convert();
+        assert!(compact.is_inline());
+        assert_eq!("a s", compact);
+    }
+
+    #[test]
+    fn with_promoted() {
+        use core::fmt::Write;
+
+        let mut string = SmartString::<Compact>::from("start: ");
+        let len = string.with_promoted(8, |out| {
+            assert!(out.capacity() >= 8);
+            for _ in 0..3 {
+                out.push_str(LONG);
+                out.push('/');
+            }
+            write!(out, "{}", 42).unwrap();
+            out.len()
+        });
+        let expected = format!("start: {0}/{0}/{0}/42", LONG);
+        assert_eq!(expected, string);
+        assert_eq!(expected.len(), len);
+        assert!(!string.is_inline());
+
+        let mut short = SmartString::<Compact>::new();
+        short.with_promoted(1024, |out| out.push_str("tiny"));
+        assert_eq!("tiny", short);
+        assert!(short.is_inline());
+
+        let mut lazy = SmartString::<LazyCompact>::new();
+        lazy.with_promoted(0, |out| {
+            out.reserve(100);
+            out.push('x')
+        });
+        assert_eq!("x", lazy);
+        assert!(!lazy.is_inline());
+        assert!(lazy.capacity() >= 100);
+    }
+
+    #[test]
+    fn inline_ordering_matches_str() {
+        use crate::{casts::StringCastMut, ops::GenericString};
+        use rand::{Rng, SeedableRng};
+
+        let mut rng = rand::rngs::StdRng::seed_from_u64(528);
+        let alphabet = ['a', 'b', '\u{0}', '\u{ff}', '\u{e9}', '\u{10000}'];
+        let strings: Vec<String> = (0..300)
+            .map(|_| {
+                let mut string = String::new();
+                while let Some(&ch) = alphabet.get(rng.gen_range(0..alphabet.len() + 2)) {
+                    if string.len() + ch.len_utf8() > MAX_INLINE {
+                        break;
+                    }
+                    string.push(ch);
+                }
+                string
+            })
+            .collect();
+        for left in &strings {
+            // Leave leftover bytes past the end of the string in the buffer.
+            let mut smart_left = SmartString::<Compact>::from("x".repeat(MAX_INLINE).as_str());
+            smart_left.truncate(0);
+            smart_left.push_str(left);
+            assert!(smart_left.is_inline());
+            match smart_left.cast_mut() {
+                StringCastMut::Inline(inline) => assert!(inline.as_mut_capacity_slice()
+                    [left.len()..]
+                    .iter()
+                    .all(|&byte| byte == b'x')),
+                StringCastMut::Boxed(_) => unreachable!(),
+            }
+            for right in &strings {
+                let smart_right = SmartString::<LazyCompact>::from(right.as_str());
+                let expected = left.as_str().cmp(right.as_str());
+                assert_eq!(expected, smart_left.cmp(&smart_right.clone().convert()));
+                assert_eq!(expected, smart_left.cmp_any_mode(&smart_right));
+            }
+        }
+    }
+
+    #[test]
+    fn counting_allocations() {
+        use super::counting_allocator::{assert_allocs, assert_no_alloc, count_allocs};
+
+        let long = "a string which is too long to be inlined";
+        let mut string: SmartString<Compact> = assert_no_alloc(|| "short".into());
+        assert_no_alloc(|| string.push_str(" and sweet"));
+        assert_allocs(1, || string.push_str(long));
+        assert_no_alloc(|| string.truncate(5));
+        assert!(string.is_inline());
+        let (count, boxed) = count_allocs(|| {
+            let mut string = SmartString::<LazyCompact>::new();
+            string.push_str(long);
+            string
+        });
+        assert_eq!(1, count);
+        assert_eq!(long, boxed);
+        assert_panic(|| assert_no_alloc(|| SmartString::<Compact>::from(long)));
+    }
+
+    #[test]
+    fn word_keyed_hash() {
+        use crate::WordKeyed;
+        use std::collections::{hash_map::DefaultHasher, HashSet};
+        use std::hash::{Hash, Hasher};
+
+        fn hash<Mode: SmartStringMode>(string: SmartString<Mode>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            WordKeyed::new(string).hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let long = "a string which is too long to be inlined";
+        // Leave leftover bytes in the inline buffer past the end of the string.
+        let mut inline = SmartString::<LazyCompact>::from("leftovers");
+        inline.truncate(4);
+        let mut boxed = SmartString::<LazyCompact>::with_capacity(MAX_INLINE * 2);
+        boxed.push_str("left");
+        assert!(inline.is_inline());
+        assert!(!boxed.is_inline());
+        let inline_hash = hash(inline);
+        assert_eq!(inline_hash, hash(boxed));
+        assert_ne!(
+            inline_hash,
+            hash(SmartString::<LazyCompact>::from("left\0"))
+        );
+
+        let strings = ["", "\0", "left", "leftovers", long, &long[..MAX_INLINE]];
+        let set: HashSet<_> = strings
+            .iter()
+            .map(|&string| WordKeyed::new(SmartString::<Compact>::from(string)))
+            .collect();
+        assert_eq!(strings.len(), set.len());
+        for string in strings {
+            assert!(set.contains(&WordKeyed::new(SmartString::from(string))));
+        }
+    }
+
+    #[test]
+    fn inline_str_macro() {
+        use crate::inline_str;
+
+        let empty: SmartString<LazyCompact> = inline_str!("");
+        assert!(empty.is_inline());
+        assert_eq!("", empty);
+        let portable: SmartString<Compact> = inline_str!(portable: "eleven byte");
+        assert!(portable.is_inline());
+        assert_eq!("eleven byte", portable);
+        #[cfg(target_pointer_width = "64")]
+        {
+            let full: SmartString<Compact> = inline_str!("twenty-three bytes long");
+            assert_eq!(MAX_INLINE, full.len());
+            assert!(full.is_inline());
+        }
+    }
+
+    #[test]
+    fn extend_from_chars_exact() {
+        use super::counting_allocator::assert_allocs;
+
+        let mut string = SmartString::<Compact>::from("ascii ");
+        assert_allocs(1, || string.extend_from_chars_exact((0..100).map(|_| 'x')));
+        assert_eq!(format!("ascii {}", "x".repeat(100)), string);
+
+        let mixed: Vec<char> = "añb€c😀".chars().cycle().take(60).collect();
+        let mut string = SmartString::<LazyCompact>::new();
+        string.extend_from_chars_exact(mixed.iter().copied());
+        assert_eq!(mixed.iter().collect::<String>(), string);
+
+        let mut string = SmartString::<Compact>::new();
+        string.extend_from_chars_exact(['é'; 5]);
+        assert!(string.is_inline());
+        assert_eq!("ééééé", string);
+    }
+
+    #[test]
+    fn digested_strings() {
+        use crate::Digested;
+
+        let prefix = "a string which is too long to be inlined";
+        let one = SmartString::<Digested<LazyCompact>>::from(format!("{}: one", prefix));
+        let two = SmartString::<Digested<Compact>>::from(format!("{}: two", prefix));
+        let plain = SmartString::<LazyCompact>::from(format!("{}: one", prefix));
+        assert!(!one.eq_any_mode(&two));
+        assert_eq!(one, one.clone());
+        assert!(one.eq_any_mode(&plain));
+        #[cfg(target_pointer_width = "64")]
+        {
+            use crate::StringCast;
+            let digest = |string: &SmartString<Digested<LazyCompact>>| match string.cast() {
+                StringCast::Boxed(string) => string.digest(),
+                StringCast::Inline(_) => None,
+            };
+            assert!(digest(&one).is_some());
+            assert_eq!(digest(&one), digest(&one.clone()));
+        }
+
+        // Mutation discards the digest, so a stale one can't cause a mismatch.
+        let mut edited = one.clone();
+        edited.replace_range(prefix.len().., ": two");
+        assert!(two.eq_any_mode(&edited));
+        edited.store_digest();
+        assert!(two.eq_any_mode(&edited));
+        assert_ne!(one, edited);
+
+        // Nor can one carried over from a mode without digests.
+        let mut converted: SmartString<LazyCompact> = one.clone().convert();
+        converted.make_ascii_uppercase();
+        let converted: SmartString<Digested<LazyCompact>> = converted.convert();
+        assert_eq!(one.to_uppercase_smart(), converted);
+        assert_eq!(prefix.len() + 5, converted.len());
+        assert_eq!(one.capacity(), one.clone().capacity());
+    }
+
+    #[test]
+    fn collect_chars_reserves_lower_bound() {
+        use super::counting_allocator::{assert_allocs, assert_no_alloc};
+
+        let long: SmartString<Compact> =
+            assert_allocs(1, || (0..MAX_INLINE * 4).map(|_| 'x').collect());
+        assert_eq!(MAX_INLINE * 4, long.len());
+        let short: SmartString<Compact> = assert_no_alloc(|| "short".chars().collect());
+        assert!(short.is_inline());
+        // An iterator whose lower bound is too low still collects correctly.
+        let filtered: SmartString<LazyCompact> =
+            (0..200).filter(|i| i % 2 == 0).map(|_| 'é').collect();
+        assert_eq!("é".repeat(100), filtered);
+    }
+
+    #[test]
+    fn add_assign() {
+        let mut string = SmartString::<Compact>::from("a");
+        string += "b";
+        string += 'c';
+        string += &SmartString::<Compact>::from("d");
+        string += SmartString::<Compact>::from("e");
+        string += &String::from("f");
+        string += String::from("g");
+        assert_eq!("abcdefg", string);
+        let string = string + 'h' + "i";
+        assert_eq!("abcdefghi", string);
+
+        // `String` only has `AddAssign<&str>`, so a `&String` on the right
+        // still coerces to a `&str`.
+        let mut std_string = String::from("x");
+        std_string += &String::from("y");
+        std_string += &string;
+        assert_eq!("xyabcdefghi", std_string);
+    }
+
+    #[test]
+    fn try_into_static() {
+        let long = "a string much too long to be stored inline";
+        let mut boxed = SmartString::<LazyCompact>::from(long);
+        boxed.truncate(MAX_INLINE);
+        assert!(boxed.as_static().is_none());
+        let inline = boxed.try_into_static().unwrap();
+        assert_eq!(&long[..MAX_INLINE], inline);
+
+        let compact = SmartString::<Compact>::from("short");
+        assert_eq!(Some("short"), compact.as_static().map(|s| s.as_str()));
+        assert_eq!("short", compact.try_into_static().unwrap());
+
+        let too_long = SmartString::<Compact>::from(long);
+        assert_eq!(long, too_long.try_into_static().unwrap_err());
+    }
+
+    #[test]
+    fn reserve_total() {
+        let mut string = SmartString::<Compact>::from("short");
+        string.reserve_total(MAX_INLINE);
+        assert!(string.is_inline());
+        string.reserve_total(2);
+        assert!(string.is_inline());
+        string.reserve_total(200);
+        assert!(!string.is_inline());
+        assert_eq!(200, string.capacity());
+        string.reserve_total(100);
+        assert_eq!(200, string.capacity());
+        string.reserve_total(300);
+        assert_eq!(300, string.capacity());
+        assert_eq!("short", string);
+    }
+
+    #[test]
+    fn join_and_concat_iterators() {
+        use super::counting_allocator::{assert_allocs, assert_no_alloc};
+
+        let words = ["alpha", "beta", "gamma", "delta", "epsilon", "zeta"];
+        let joined = assert_allocs(1, || SmartString::<Compact>::join(" + ", &words));
+        assert_eq!(words.join(" + "), joined);
+        let short = assert_no_alloc(|| SmartString::<Compact>::join("/", &words[..2]));
+        assert_eq!("alpha/beta", short);
+        assert!(short.is_inline());
+        let concat = SmartString::<LazyCompact>::concat(words.iter().rev());
+        assert_eq!("zetaepsilondeltagammabetaalpha", concat);
+        assert_eq!("", SmartString::<Compact>::join(",", Vec::<&str>::new()));
+    }
+
+    #[test]
+    fn repeat_char() {
+        use super::counting_allocator::{assert_allocs, assert_no_alloc};
+
+        for ch in ['-', 'é', '€', '😀'] {
+            for count in [0, 1, 5, MAX_INLINE / ch.len_utf8(), 100] {
+                let string: SmartString<Compact> = SmartString::repeat_char(ch, count);
+                assert_eq!(ch.to_string().repeat(count), string);
+                assert_eq!(string.len() <= MAX_INLINE, string.is_inline());
+            }
+        }
+        assert_no_alloc(|| SmartString::<Compact>::repeat_char('-', MAX_INLINE));
+        assert_allocs(1, || SmartString::<LazyCompact>::repeat_char('-', 1000));
+        assert_panic(|| SmartString::<Compact>::repeat_char('€', usize::MAX / 2));
+    }
 }
@@ -0,0 +1,128 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A global allocator which counts allocations, for asserting that code
+//! stays within an allocation budget.
+//!
+//! Install [`CountingAllocator`] as the global allocator of your test binary,
+//! then wrap the code you want to check in [`assert_allocs()`] or
+//! [`assert_no_alloc()`]:
+//!
+//! ```rust
+//! use smartstring::{alias::CompactString, test::counting_allocator::*};
+//!
+//! #[global_allocator]
+//! static ALLOCATOR: CountingAllocator = CountingAllocator::new();
+//!
+//! let mut string: CompactString = assert_no_alloc(|| "short".into());
+//! assert_no_alloc(|| string.push_str(" and sweet"));
+//! assert_allocs(1, || string.push_str(" and now too long to be inlined"));
+//! ```
+//!
+//! Allocations are counted per thread, so tests running in parallel don't
+//! disturb each other's counts. A reallocation counts as an allocation, a
+//! deallocation doesn't count at all.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+thread_local! {
+    // A `const` initialiser, which needs Rust 1.59, spares every allocation a
+    // check for whether the counter has been initialised yet.
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    // The counter may already be gone while a thread is shutting down.
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+/// A [`GlobalAlloc`] which counts the allocations made through it on each
+/// thread, and passes them on to another allocator, [`System`] by default.
+#[derive(Debug, Default)]
+pub struct CountingAllocator<A = System> {
+    inner: A,
+}
+
+impl CountingAllocator {
+    /// Count allocations made through the [`System`] allocator.
+    pub const fn new() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> CountingAllocator<A> {
+    /// Count allocations made through `inner`.
+    pub const fn wrap(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+#[allow(unsafe_code)]
+unsafe impl<A: GlobalAlloc> GlobalAlloc for CountingAllocator<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        self.inner.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count();
+        self.inner.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        self.inner.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+}
+
+/// The number of allocations made on the current thread so far.
+///
+/// This is always zero unless a [`CountingAllocator`] is the global allocator.
+pub fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// Run `f` and return the number of allocations it made on the current
+/// thread, along with its result.
+pub fn count_allocs<A, F>(f: F) -> (usize, A)
+where
+    F: FnOnce() -> A,
+{
+    let before = allocations();
+    let result = f();
+    (allocations() - before, result)
+}
+
+/// Run `f`, panicking if it didn't make exactly `expected` allocations on the
+/// current thread.
+///
+/// Note that this can't tell whether a [`CountingAllocator`] is installed,
+/// so without one it passes only when `expected` is zero.
+pub fn assert_allocs<A, F>(expected: usize, f: F) -> A
+where
+    F: FnOnce() -> A,
+{
+    let (actual, result) = count_allocs(f);
+    assert_eq!(
+        expected, actual,
+        "expected {} allocations but counted {}",
+        expected, actual
+    );
+    result
+}
+
+/// Run `f`, panicking if it made any allocations on the current thread.
+pub fn assert_no_alloc<A, F>(f: F) -> A
+where
+    F: FnOnce() -> A,
+{
+    assert_allocs(0, f)
+}
@@ -0,0 +1,37 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! UTF-8 validation for the validating constructors.
+//!
+//! With the `simdutf8` feature enabled, bytes are validated with
+//! [`simdutf8`](https://crates.io/crates/simdutf8)'s SIMD accelerated
+//! validator. It doesn't say where validation failed, so invalid input is
+//! validated again by the standard library to get exactly the error it would
+//! have reported. Invalid input is the rare case, and the cost of the second
+//! pass is only paid for that.
+
+use alloc::{string::FromUtf8Error, string::String, vec::Vec};
+use core::str::Utf8Error;
+
+/// Validate `bytes` as UTF-8, like [`core::str::from_utf8()`].
+#[inline]
+pub(crate) fn from_utf8(bytes: &[u8]) -> Result<&str, Utf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if let Ok(string) = simdutf8::basic::from_utf8(bytes) {
+        return Ok(string);
+    }
+    core::str::from_utf8(bytes)
+}
+
+/// Validate `bytes` as UTF-8, like [`String::from_utf8()`].
+#[inline]
+pub(crate) fn string_from_utf8(bytes: Vec<u8>) -> Result<String, FromUtf8Error> {
+    #[cfg(feature = "simdutf8")]
+    if simdutf8::basic::from_utf8(&bytes).is_ok() {
+        // We've just checked it's valid.
+        #[allow(unsafe_code)]
+        return Ok(unsafe { String::from_utf8_unchecked(bytes) });
+    }
+    String::from_utf8(bytes)
+}
@@ -0,0 +1,75 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{SmartString, SmartStringMode};
+use uuid::{
+    fmt::{Braced, Hyphenated, Simple, Urn},
+    Uuid,
+};
+
+/// The textual format to write a [`Uuid`] in.
+///
+/// See [`SmartString::from_uuid()`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum UuidFormat {
+    /// 32 hex digits with no separators, eg. `67e5504410b1426f9247bb680e5fe0c8`.
+    Simple,
+    /// The familiar hyphenated format, eg.
+    /// `67e55044-10b1-426f-9247-bb680e5fe0c8`.
+    Hyphenated,
+    /// The hyphenated format wrapped in braces, eg.
+    /// `{67e55044-10b1-426f-9247-bb680e5fe0c8}`.
+    Braced,
+    /// A URN, eg. `urn:uuid:67e55044-10b1-426f-9247-bb680e5fe0c8`.
+    Urn,
+}
+
+impl UuidFormat {
+    fn len(self) -> usize {
+        match self {
+            Self::Simple => Simple::LENGTH,
+            Self::Hyphenated => Hyphenated::LENGTH,
+            Self::Braced => Braced::LENGTH,
+            Self::Urn => Urn::LENGTH,
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> SmartString<Mode> {
+    /// Format a [`Uuid`] as lowercase hex, in the given format.
+    ///
+    /// This writes directly into the [`SmartString`], using the [`uuid`] crate's
+    /// buffer API, without going through a [`String`] first.
+    ///
+    /// ```rust
+    /// # use smartstring::{alias::String, UuidFormat};
+    /// # use uuid::Uuid;
+    /// let id = Uuid::from_u128(0x67e5504410b1426f9247bb680e5fe0c8);
+    /// let simple = String::from_uuid(id, UuidFormat::Simple);
+    /// assert_eq!("67e5504410b1426f9247bb680e5fe0c8", simple);
+    /// let hyphenated = String::from_uuid(id, UuidFormat::Hyphenated);
+    /// assert_eq!("67e55044-10b1-426f-9247-bb680e5fe0c8", hyphenated);
+    /// ```
+    pub fn from_uuid(uuid: Uuid, format: UuidFormat) -> Self {
+        #[allow(unsafe_code)]
+        unsafe {
+            Self::from_utf8_fill(format.len(), |buffer| {
+                match format {
+                    UuidFormat::Simple => uuid.simple().encode_lower(buffer),
+                    UuidFormat::Hyphenated => uuid.hyphenated().encode_lower(buffer),
+                    UuidFormat::Braced => uuid.braced().encode_lower(buffer),
+                    UuidFormat::Urn => uuid.urn().encode_lower(buffer),
+                };
+            })
+        }
+    }
+}
+
+impl<Mode: SmartStringMode> From<Uuid> for SmartString<Mode> {
+    /// Format a [`Uuid`] in the [hyphenated][UuidFormat::Hyphenated] format,
+    /// like its [`Display`][core::fmt::Display] implementation does.
+    fn from(uuid: Uuid) -> Self {
+        Self::from_uuid(uuid, UuidFormat::Hyphenated)
+    }
+}
@@ -0,0 +1,117 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{
+    inline::{bytes_to_words, INLINE_WORDS},
+    SmartString, SmartStringMode, StringCast, MAX_INLINE,
+};
+use core::{
+    cmp::Ordering,
+    fmt::{Debug, Error, Formatter},
+    hash::{Hash, Hasher},
+    ops::Deref,
+};
+
+/// A [`SmartString`] which hashes short strings a word at a time.
+///
+/// A [`SmartString`] hashes like a [`str`], feeding its bytes to the
+/// [`Hasher`] one slice at a time. This wrapper instead hashes a string which
+/// is short enough to be inlined as a fixed number of `u64`s followed by its
+/// length, which most hashers can process faster, especially for strings held
+/// inline. Longer strings are hashed like a `str`. It's meant for hash maps
+/// keyed by lots of short strings.
+///
+/// Because it doesn't hash like a [`str`], it can't be looked up by `&str`;
+/// probe with another `WordKeyed` instead. Equal strings still hash the same
+/// regardless of their mode or whether they're inline.
+///
+/// ```rust
+/// # use smartstring::{WordKeyed, alias::String};
+/// # use std::collections::HashSet;
+/// let mut set = HashSet::new();
+/// set.insert(WordKeyed::new(String::from("hello")));
+/// assert!(set.contains(&WordKeyed::new(String::from("hello"))));
+/// ```
+#[repr(transparent)]
+pub struct WordKeyed<Mode: SmartStringMode>(pub SmartString<Mode>);
+
+impl<Mode: SmartStringMode> WordKeyed<Mode> {
+    /// Wrap a [`SmartString`].
+    pub fn new(string: SmartString<Mode>) -> Self {
+        Self(string)
+    }
+
+    /// Unwrap the [`SmartString`].
+    pub fn into_inner(self) -> SmartString<Mode> {
+        self.0
+    }
+}
+
+impl<Mode: SmartStringMode> Clone for WordKeyed<Mode> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<Mode: SmartStringMode> Deref for WordKeyed<Mode> {
+    type Target = SmartString<Mode>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<Mode: SmartStringMode> From<SmartString<Mode>> for WordKeyed<Mode> {
+    fn from(string: SmartString<Mode>) -> Self {
+        Self(string)
+    }
+}
+
+impl<Mode: SmartStringMode> From<WordKeyed<Mode>> for SmartString<Mode> {
+    fn from(key: WordKeyed<Mode>) -> Self {
+        key.0
+    }
+}
+
+impl<Mode: SmartStringMode> PartialEq for WordKeyed<Mode> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<Mode: SmartStringMode> Eq for WordKeyed<Mode> {}
+
+impl<Mode: SmartStringMode> PartialOrd for WordKeyed<Mode> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<Mode: SmartStringMode> Ord for WordKeyed<Mode> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<Mode: SmartStringMode> Hash for WordKeyed<Mode> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        let words: [u64; INLINE_WORDS] = match self.0.cast() {
+            StringCast::Inline(string) => string.words(),
+            StringCast::Boxed(string) if string.len() <= MAX_INLINE => {
+                bytes_to_words(string.as_bytes())
+            }
+            StringCast::Boxed(string) => return string.hash(state),
+        };
+        for word in words {
+            state.write_u64(word);
+        }
+        state.write_usize(self.0.len());
+    }
+}
+
+impl<Mode: SmartStringMode> Debug for WordKeyed<Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), Error> {
+        Debug::fmt(&self.0, f)
+    }
+}
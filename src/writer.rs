@@ -0,0 +1,138 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+use crate::{utf8, SmartString, SmartStringMode};
+use core::{
+    fmt::{Debug, Formatter},
+    str::from_utf8_unchecked,
+};
+use std::io::{self, ErrorKind};
+
+/// A byte oriented [`Write`][io::Write] adapter which appends UTF-8 to a
+/// [`SmartString`].
+///
+/// This is returned by [`SmartString::writer()`]. Bytes are validated as
+/// they're written, and appended straight to the string, which stays inline if
+/// the output fits. A multi-byte character split across two writes is held back
+/// until the rest of it arrives.
+///
+/// A write which runs into bytes that aren't valid UTF-8 appends the valid
+/// text before them and reports only that much as written, so the next write
+/// starts at the bad bytes and fails with an error of kind
+/// [`InvalidData`][ErrorKind::InvalidData]. Call [`finish()`][SmartStringWriter::finish] when you're done
+/// to check that the output didn't end partway through a character: dropping
+/// the writer discards an incomplete character silently.
+///
+/// ```rust
+/// # use smartstring::alias::String;
+/// use std::io::Write;
+///
+/// let mut string = String::new();
+/// let mut writer = string.writer();
+/// write!(writer, "{} + {} = ", 1, 2).unwrap();
+/// writer.write_all(&[0xe2, 0x88]).unwrap();
+/// writer.write_all(&[0x9e]).unwrap();
+/// writer.finish().unwrap();
+/// assert_eq!("1 + 2 = ∞", string);
+/// assert!(string.is_inline());
+/// ```
+pub struct SmartStringWriter<'a, Mode: SmartStringMode> {
+    string: &'a mut SmartString<Mode>,
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl<'a, Mode: SmartStringMode> SmartStringWriter<'a, Mode> {
+    pub(crate) fn new(string: &'a mut SmartString<Mode>) -> Self {
+        Self {
+            string,
+            pending: [0; 4],
+            pending_len: 0,
+        }
+    }
+
+    /// Finish writing, checking that the output didn't end partway through a
+    /// character.
+    pub fn finish(self) -> io::Result<()> {
+        if self.pending_len > 0 {
+            Err(invalid_data())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Feed bytes into the held back partial character, returning how many
+    /// were used. If they don't complete a valid character, the partial
+    /// character is dropped along with them.
+    fn complete_pending(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let width = match self.pending[0] {
+            0xc0..=0xdf => 2,
+            0xe0..=0xef => 3,
+            _ => 4,
+        };
+        let used = (width - self.pending_len).min(buf.len());
+        self.pending[self.pending_len..self.pending_len + used].copy_from_slice(&buf[..used]);
+        self.pending_len += used;
+        if self.pending_len == width {
+            self.pending_len = 0;
+            let ch = utf8::from_utf8(&self.pending[..width]).map_err(|_| invalid_data())?;
+            self.string.push_str(ch);
+        }
+        Ok(used)
+    }
+}
+
+fn invalid_data() -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+}
+
+impl<'a, Mode: SmartStringMode> io::Write for SmartStringWriter<'a, Mode> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut used = 0;
+        if self.pending_len > 0 {
+            used = self.complete_pending(buf)?;
+            if self.pending_len > 0 {
+                return Ok(used);
+            }
+        }
+        let rest = &buf[used..];
+        match utf8::from_utf8(rest) {
+            Ok(string) => self.string.push_str(string),
+            Err(error) => {
+                let valid = error.valid_up_to();
+                // The validator has just checked this much.
+                #[allow(unsafe_code)]
+                self.string
+                    .push_str(unsafe { from_utf8_unchecked(&rest[..valid]) });
+                if error.error_len().is_some() {
+                    // Report the valid prefix as written, and leave the error
+                    // for the next call, which will start at the bad bytes.
+                    return if used + valid > 0 {
+                        Ok(used + valid)
+                    } else {
+                        Err(invalid_data())
+                    };
+                }
+                // The input ends partway through a character.
+                let tail = &rest[valid..];
+                self.pending[..tail.len()].copy_from_slice(tail);
+                self.pending_len = tail.len();
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a, Mode: SmartStringMode> Debug for SmartStringWriter<'a, Mode> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SmartStringWriter")
+            .field("string", &self.string)
+            .field("pending", &&self.pending[..self.pending_len])
+            .finish()
+    }
+}
@@ -0,0 +1,65 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Checks that the optional integrations compose when they're all enabled at
+//! once: strings generated by `proptest` and `arbitrary` must survive a serde
+//! round trip in every mode, and come back in canonical form.
+
+use arbitrary::{Arbitrary, Unstructured};
+use proptest::prelude::*;
+use smartstring::{Compact, LazyCompact, SmartString, SmartStringMode, MAX_INLINE};
+
+fn assert_round_trip<Mode: SmartStringMode>(string: &SmartString<Mode>) {
+    let json = serde_json::to_string(string).unwrap();
+    assert_eq!(serde_json::to_string(string.as_str()).unwrap(), json);
+    let back: SmartString<Mode> = serde_json::from_str(&json).unwrap();
+    assert_eq!(string, &back);
+    assert_eq!(back.len() <= MAX_INLINE, back.is_inline());
+
+    let bytes = serde_json::to_vec(&Bytes(string.clone())).unwrap();
+    let back: Bytes<Mode> = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(string, &back.0);
+}
+
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound = "")]
+struct Bytes<Mode: SmartStringMode>(#[serde(with = "smartstring::serde::bytes")] SmartString<Mode>);
+
+fn arbitrary_round_trip<Mode: SmartStringMode + 'static>(data: &[u8]) {
+    let mut input = Unstructured::new(data);
+    while let Ok(string) = SmartString::<Mode>::arbitrary(&mut input) {
+        assert_round_trip(&string);
+        if input.is_empty() {
+            break;
+        }
+    }
+    let string = SmartString::<Mode>::arbitrary_take_rest(Unstructured::new(data)).unwrap();
+    assert_round_trip(&string);
+}
+
+proptest! {
+    #[test]
+    fn proptest_compact(string in smartstring::proptest::string_regex::<Compact>(".*").unwrap()) {
+        assert_round_trip(&string);
+    }
+
+    #[test]
+    fn proptest_lazy_compact(string in smartstring::proptest::string_regex::<LazyCompact>(".*").unwrap()) {
+        assert_round_trip(&string);
+    }
+
+    #[test]
+    fn arbitrary(data: Vec<u8>) {
+        arbitrary_round_trip::<Compact>(&data);
+        arbitrary_round_trip::<LazyCompact>(&data);
+    }
+}
+
+#[cfg(feature = "test")]
+proptest! {
+    #[test]
+    fn actions((constructor, actions) in smartstring::proptest::actions()) {
+        smartstring::test::test_everything::<LazyCompact>(constructor, actions);
+    }
+}
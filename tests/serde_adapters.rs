@@ -0,0 +1,99 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! Checks the serde adapters from inside a type with derived serde impls,
+//! which is how they're meant to be used. The derives can't live in the
+//! crate's own tests, where `rust_2018_idioms` is forbidden.
+
+use serde::{Deserialize, Serialize};
+use smartstring::{serde::chunked::CHUNK_SIZE, Compact, SmartString};
+
+#[test]
+fn bytes_adapter() {
+    use serde_test::{assert_de_tokens, assert_de_tokens_error, assert_tokens, Token};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug)]
+    struct Record {
+        #[serde(with = "smartstring::serde::bytes")]
+        name: SmartString<Compact>,
+    }
+
+    let record = Record {
+        name: "h\u{e9}llo".into(),
+    };
+    let fields = |value| {
+        [
+            Token::Struct {
+                name: "Record",
+                len: 1,
+            },
+            Token::Str("name"),
+            value,
+            Token::StructEnd,
+        ]
+    };
+    assert_tokens(&record, &fields(Token::Bytes("h\u{e9}llo".as_bytes())));
+    assert_de_tokens(&record, &fields(Token::ByteBuf("h\u{e9}llo".as_bytes())));
+    assert_de_tokens(&record, &fields(Token::Str("h\u{e9}llo")));
+    assert_de_tokens_error::<Record>(
+        &fields(Token::Bytes(b"\xff")),
+        "invalid value: byte array, expected UTF-8 encoded bytes",
+    );
+
+    // A sequence's length comes from the input, and mustn't be trusted.
+    let record = Record { name: "hi".into() };
+    assert_de_tokens(
+        &record,
+        &[
+            Token::Struct {
+                name: "Record",
+                len: 1,
+            },
+            Token::Str("name"),
+            Token::Seq {
+                len: Some(usize::MAX),
+            },
+            Token::U8(b'h'),
+            Token::U8(b'i'),
+            Token::SeqEnd,
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[test]
+fn chunked() {
+    use serde_test::{assert_tokens, Token};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Chunked(#[serde(with = "smartstring::serde::chunked")] SmartString<Compact>);
+
+    assert_tokens(
+        &Chunked("".into()),
+        &[
+            Token::NewtypeStruct { name: "Chunked" },
+            Token::Seq { len: None },
+            Token::SeqEnd,
+        ],
+    );
+
+    // A chunk never splits a character, so it may come up short.
+    let long: &'static str =
+        Box::leak(format!("{}é{}", "x".repeat(CHUNK_SIZE - 1), "y".repeat(10)).into_boxed_str());
+    let (first, rest) = long.split_at(CHUNK_SIZE - 1);
+    assert_tokens(
+        &Chunked(long.into()),
+        &[
+            Token::NewtypeStruct { name: "Chunked" },
+            Token::Seq { len: None },
+            Token::Str(first),
+            Token::Str(rest),
+            Token::SeqEnd,
+        ],
+    );
+
+    let json = serde_json::to_string(&Chunked(long.into())).unwrap();
+    let value: Chunked = serde_json::from_str(&json).unwrap();
+    assert_eq!(long, value.0);
+}
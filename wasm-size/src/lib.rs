@@ -0,0 +1,38 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+
+//! A wasm module exercising the common string ops in both modes. CI builds it
+//! for `wasm32-unknown-unknown` and fails if it grows past a size budget, to
+//! keep an eye on how much code the crate adds to a wasm bundle. See the
+//! `wasm-size` job in the CI workflow for the budget.
+
+use smartstring::{Compact, LazyCompact, SmartString, SmartStringMode};
+
+fn exercise<Mode: SmartStringMode>(count: u32) -> usize {
+    let mut string: SmartString<Mode> = SmartString::new();
+    for index in 0..count {
+        string.push(char::from(b'a' + (index % 26) as u8));
+        string.push_str("-");
+    }
+    string.insert_str(0, "start:");
+    string.insert(0, '>');
+    let removed = string.remove(0);
+    string.retain(|ch| ch != removed);
+    string.replace_range(..5, "begin");
+    string.truncate(string.len() / 2);
+    let popped = string.pop();
+    let copy = string.clone();
+    let tail = string.split_off(string.len() / 2);
+    usize::from(copy > string) + usize::from(popped.is_some()) + tail.len() + string.len()
+}
+
+#[no_mangle]
+pub extern "C" fn exercise_compact(count: u32) -> usize {
+    exercise::<Compact>(count)
+}
+
+#[no_mangle]
+pub extern "C" fn exercise_lazy_compact(count: u32) -> usize {
+    exercise::<LazyCompact>(count)
+}